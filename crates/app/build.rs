@@ -0,0 +1,64 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// ビルド情報（git ハッシュ・ビルド日）を `$OUT_DIR/build_info.rs` に書き出す
+fn main() {
+    let out_dir = PathBuf::from(std::env::var("OUT_DIR").expect("OUT_DIR is set by cargo"));
+    let git_hash = git_short_hash().unwrap_or_else(|| "unknown".into());
+    let build_date = build_date().unwrap_or_else(|| "unknown".into());
+
+    let contents = format!(
+        "pub const GIT_HASH: &str = {git_hash:?};\n\
+         pub const BUILD_DATE: &str = {build_date:?};\n\
+         pub const VERSION: &str = env!(\"CARGO_PKG_VERSION\");\n"
+    );
+    std::fs::write(out_dir.join("build_info.rs"), contents).expect("failed to write build_info.rs");
+
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    // コミットやブランチ切り替えで再生成する
+    let git_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../.git");
+    for entry in ["HEAD", "refs/heads"] {
+        let path = git_dir.join(entry);
+        if path.exists() {
+            println!("cargo:rerun-if-changed={}", path.display());
+        }
+    }
+}
+
+fn git_short_hash() -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--short=7", "HEAD"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let hash = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (!hash.is_empty()).then_some(hash)
+}
+
+/// UTC の YYYY-MM-DD（SOURCE_DATE_EPOCH があれば優先して再現可能にする）
+fn build_date() -> Option<String> {
+    let secs = match std::env::var("SOURCE_DATE_EPOCH") {
+        Ok(epoch) => epoch.trim().parse::<u64>().ok()?,
+        Err(_) => SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs(),
+    };
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    Some(format!("{year:04}-{month:02}-{day:02}"))
+}
+
+/// 1970-01-01 からの日数をグレゴリオ暦の年月日へ変換
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
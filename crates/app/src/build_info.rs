@@ -0,0 +1,8 @@
+// build.rs が生成する GIT_HASH / BUILD_DATE / VERSION
+
+include!(concat!(env!("OUT_DIR"), "/build_info.rs"));
+
+/// 表示用のバージョン文字列（例: `yStrokey v0.1.4 (abc1234)`）
+pub fn version_string() -> String {
+    format!("yStrokey v{VERSION} ({GIT_HASH})")
+}
@@ -0,0 +1,131 @@
+use std::path::PathBuf;
+
+pub const USAGE: &str = "\
+usage: ystrokey [options]
+
+options:
+  --config <path>    use the given config file instead of config.json next to the exe
+  --profile <name>   use <exe_dir>/profiles/<name>.json as the config file
+  --replay <path>    replay input events recorded with diagnostics.record_events
+  --toggle           toggle the OSD of the running instance and exit
+  --reset-config     back up the config file, write the default config and exit
+  --print-schema     print the JSON Schema of the config file and exit
+  --doctor           run self-tests, print a PASS/FAIL report and exit
+  --version          print version information and exit
+  --help             print this help and exit";
+
+/// コマンドライン引数
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Args {
+    /// `--config <path>`: 設定ファイルのパス
+    pub config: Option<PathBuf>,
+    /// `--profile <name>`: `<exe_dir>/profiles/<name>.json` を設定ファイルとして使う
+    pub profile: Option<String>,
+    /// `--replay <path>`: 記録済みの入力イベントを起動後に再生する
+    pub replay: Option<PathBuf>,
+    /// `--toggle`: 起動中のインスタンスの表示を切り替えて終了
+    pub toggle: bool,
+    /// `--reset-config`: 設定ファイルをバックアップして既定値で作り直し、終了
+    pub reset_config: bool,
+    /// `--print-schema`: 設定ファイルの JSON Schema を出力して終了
+    pub print_schema: bool,
+    /// `--doctor`: 自己診断の結果を出力して終了
+    pub doctor: bool,
+    /// `--version`: バージョンを表示して終了
+    pub version: bool,
+    /// `--help`: 使い方を表示して終了
+    pub help: bool,
+}
+
+/// `std::env::args()` を解釈（不正な引数はエラー表示して終了）
+pub fn parse_args() -> Args {
+    match parse_from(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("yStrokey: {message}");
+            eprintln!("{USAGE}");
+            std::process::exit(2);
+        }
+    }
+}
+
+fn parse_from(mut iter: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut args = Args::default();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--config" => {
+                let path = iter.next().ok_or("--config requires a path")?;
+                args.config = Some(PathBuf::from(path));
+            }
+            "--profile" => {
+                let name = iter.next().ok_or("--profile requires a name")?;
+                if name.is_empty() || name.contains(['/', '\\', ':']) || name.contains("..") {
+                    return Err(format!("invalid profile name: {name}"));
+                }
+                args.profile = Some(name);
+            }
+            "--replay" => {
+                let path = iter.next().ok_or("--replay requires a path")?;
+                args.replay = Some(PathBuf::from(path));
+            }
+            "--toggle" => args.toggle = true,
+            "--reset-config" => args.reset_config = true,
+            "--print-schema" => args.print_schema = true,
+            "--doctor" => args.doctor = true,
+            "--version" => args.version = true,
+            "--help" | "-h" => args.help = true,
+            other => return Err(format!("unknown argument: {other}")),
+        }
+    }
+    if args.config.is_some() && args.profile.is_some() {
+        return Err("--config and --profile cannot be used together".into());
+    }
+    Ok(args)
+}
+
+impl Args {
+    /// 使用する設定ファイルのパスを解決（未指定時は exe と同じディレクトリの config.json）
+    pub fn config_path(&self) -> PathBuf {
+        if let Some(path) = &self.config {
+            // 相対パスは起動時のカレントディレクトリ基準で固定する
+            return std::path::absolute(path).unwrap_or_else(|_| path.clone());
+        }
+        let exe_dir = std::env::current_exe()
+            .ok()
+            .and_then(|p| p.parent().map(|d| d.to_path_buf()));
+        match (&self.profile, exe_dir) {
+            (Some(name), Some(dir)) => dir.join("profiles").join(format!("{name}.json")),
+            (Some(name), None) => PathBuf::from("profiles").join(format!("{name}.json")),
+            (None, Some(dir)) => dir.join("config.json"),
+            (None, None) => PathBuf::from("config.json"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args, String> {
+        parse_from(args.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn parses_known_flags_and_rejects_invalid_input() {
+        let args = parse(&["--config", "C:\\my\\config.json", "--toggle"]).unwrap();
+        assert_eq!(args.config, Some(PathBuf::from("C:\\my\\config.json")));
+        assert!(args.toggle);
+        assert!(!args.version);
+        assert!(parse(&["--help"]).unwrap().help);
+        assert!(parse(&["--reset-config"]).unwrap().reset_config);
+        assert!(parse(&["--doctor"]).unwrap().doctor);
+
+        assert_eq!(parse(&["--profile", "stream"]).unwrap().profile.as_deref(), Some("stream"));
+        assert_eq!(parse(&["--replay", "events.jsonl"]).unwrap().replay, Some(PathBuf::from("events.jsonl")));
+        assert!(parse(&["--config"]).is_err());
+        assert!(parse(&["--replay"]).is_err());
+        assert!(parse(&["--profile", "..\\evil"]).is_err());
+        assert!(parse(&["--config", "a.json", "--profile", "b"]).is_err());
+        assert!(parse(&["--bogus"]).is_err());
+    }
+}
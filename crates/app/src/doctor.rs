@@ -0,0 +1,82 @@
+use std::path::Path;
+use std::sync::mpsc;
+use std::time::Duration;
+
+use windows::Win32::Foundation::POINT;
+use windows::Win32::Graphics::Gdi::{MonitorFromPoint, MONITOR_DEFAULTTOPRIMARY};
+use windows::Win32::UI::HiDpi::{GetDpiForMonitor, MDT_EFFECTIVE_DPI};
+
+use ystrokey_core::{AppConfig, InputEvent};
+use ystrokey_input::install_keyboard_hook;
+use ystrokey_render::D2DRenderer;
+
+use crate::autostart;
+
+/// フックスレッドが失敗で終了するのを待つ時間
+const HOOK_PROBE_WAIT: Duration = Duration::from_millis(300);
+
+/// `--doctor`: 各機能の初期化を試して結果を出力する（すべて成功なら true）
+pub fn run(config_path: &Path) -> bool {
+    let checks = [
+        ("config", check_config(config_path)),
+        ("keyboard hook", check_keyboard_hook()),
+        ("Direct2D / DirectWrite", check_renderer()),
+        ("primary monitor DPI", check_primary_dpi()),
+        ("autostart registry", check_autostart()),
+    ];
+
+    let mut all_passed = true;
+    for (name, result) in &checks {
+        match result {
+            Ok(detail) => println!("PASS  {name}: {detail}"),
+            Err(e) => {
+                all_passed = false;
+                println!("FAIL  {name}: {e}");
+            }
+        }
+    }
+    all_passed
+}
+
+fn check_config(config_path: &Path) -> Result<String, String> {
+    AppConfig::load_strict(config_path)
+        .map(|_| config_path.display().to_string())
+        .map_err(|e| format!("{}: {e}", config_path.display()))
+}
+
+fn check_keyboard_hook() -> Result<String, String> {
+    let (tx, _rx) = mpsc::sync_channel::<InputEvent>(1);
+    let handle = install_keyboard_hook(tx);
+    // 成功時はメッセージループに入り続け、失敗時はスレッドが終了する
+    std::thread::sleep(HOOK_PROBE_WAIT);
+    if handle.is_finished() {
+        Err("hook thread exited (SetWindowsHookExW failed, see stderr)".into())
+    } else {
+        Ok("installed".into())
+    }
+}
+
+fn check_renderer() -> Result<String, String> {
+    D2DRenderer::new(&AppConfig::default().style)
+        .map(|_| "factories created".into())
+        .map_err(|e| e.to_string())
+}
+
+fn check_primary_dpi() -> Result<String, String> {
+    let (mut dpi_x, mut dpi_y) = (0, 0);
+    unsafe {
+        let monitor = MonitorFromPoint(POINT { x: 0, y: 0 }, MONITOR_DEFAULTTOPRIMARY);
+        GetDpiForMonitor(monitor, MDT_EFFECTIVE_DPI, &mut dpi_x, &mut dpi_y)
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(format!("{dpi_x}x{dpi_y}"))
+}
+
+fn check_autostart() -> Result<String, String> {
+    let state = if autostart::is_autostart_enabled() {
+        "enabled"
+    } else {
+        "disabled"
+    };
+    Ok(state.into())
+}
@@ -0,0 +1,197 @@
+//! 外部配信（HTTP / WebSocket）向けの JSON 表現
+
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use serde_json::{json, Value};
+
+use ystrokey_core::{
+    ClipboardContent, ClipboardTextFormat, DisplayItem, DisplayItemKind, DisplayPhase, ImeEventKind, InputEvent,
+    KeyAction, Modifiers, MouseAction, MouseButton,
+};
+use ystrokey_render::format_item_text;
+
+/// `Instant` を UNIX 時刻 (ms) に換算
+pub fn instant_to_unix_ms(instant: Instant) -> u64 {
+    let now_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    now_ms.saturating_sub(instant.elapsed().as_millis() as u64)
+}
+
+/// 表示アイテムを JSON に変換（kind / label / opacity / phase / pinned）
+pub fn display_item_json(item: &DisplayItem) -> Value {
+    let kind = match &item.kind {
+        DisplayItemKind::KeyStroke { .. } => "key-stroke",
+        DisplayItemKind::KeyStrokeGroup { .. } => "key-stroke-group",
+        DisplayItemKind::Shortcut { .. } => "shortcut",
+        DisplayItemKind::ImeComposition { .. } => "ime-composition",
+        DisplayItemKind::ImeCandidateList { .. } => "ime-candidate-list",
+        DisplayItemKind::ClipboardPreview { .. } => "clipboard-preview",
+        DisplayItemKind::LockIndicator { .. } => "lock-indicator",
+        DisplayItemKind::GamepadInput { .. } => "gamepad-input",
+        DisplayItemKind::ModifierState { .. } => "modifier-state",
+    };
+    let phase = match item.phase {
+        DisplayPhase::Active => "active",
+        DisplayPhase::FadingOut => "fading-out",
+        DisplayPhase::Expired => "expired",
+    };
+    json!({
+        "id": item.id,
+        "kind": kind,
+        "label": format_item_text(&item.kind),
+        "opacity": item.opacity,
+        "phase": phase,
+        "pinned": item.pinned,
+    })
+}
+
+/// 入力イベントを JSON に変換（`type` / `timestamp_ms` + イベント固有フィールド）
+///
+/// 設定UIやDPI変更などの内部イベントは配信対象外のため `None`。
+pub fn input_event_json(event: &InputEvent) -> Option<Value> {
+    let value = match event {
+        InputEvent::Key(ke) => json!({
+            "type": "key",
+            "timestamp_ms": instant_to_unix_ms(ke.timestamp),
+            "key": ke.key.label(),
+            "vk": ke.key.0,
+            "action": key_action_name(ke.action),
+            "modifiers": modifiers_json(&ke.modifiers),
+            "is_numpad": ke.is_numpad,
+            "text": ke.text,
+        }),
+        InputEvent::Mouse(me) => {
+            let (action, delta) = match me.action {
+                MouseAction::Down => ("down", None),
+                MouseAction::Up => ("up", None),
+                MouseAction::Wheel(d) => ("wheel", Some(d)),
+            };
+            json!({
+                "type": "mouse",
+                "timestamp_ms": instant_to_unix_ms(me.timestamp),
+                "button": mouse_button_name(me.button),
+                "action": action,
+                "wheel_delta": delta,
+                "x": me.position.0,
+                "y": me.position.1,
+            })
+        }
+        InputEvent::Ime(ie) => {
+            let mut value = json!({
+                "type": "ime",
+                "timestamp_ms": instant_to_unix_ms(ie.timestamp),
+            });
+            let fields = match &ie.kind {
+                ImeEventKind::StateChanged { enabled } => {
+                    json!({ "kind": "state-changed", "enabled": enabled })
+                }
+                ImeEventKind::CompositionUpdate { text } => {
+                    json!({ "kind": "composition-update", "text": text })
+                }
+                ImeEventKind::CompositionEnd { result } => {
+                    json!({ "kind": "composition-end", "text": result })
+                }
+                ImeEventKind::CandidateUpdate { text } => {
+                    json!({ "kind": "candidate-update", "text": text })
+                }
+                ImeEventKind::ConversionModeChanged { katakana } => {
+                    json!({ "kind": "conversion-mode-changed", "katakana": katakana })
+                }
+            };
+            merge(&mut value, fields);
+            value
+        }
+        InputEvent::Clipboard(ce) => {
+            let mut value = json!({
+                "type": "clipboard",
+                "timestamp_ms": instant_to_unix_ms(ce.timestamp),
+            });
+            let fields = match &ce.content {
+                ClipboardContent::Text {
+                    text,
+                    source_app,
+                    format,
+                } => {
+                    let format = match format {
+                        ClipboardTextFormat::Unicode => "unicode",
+                        ClipboardTextFormat::Html => "html",
+                        ClipboardTextFormat::Rtf => "rtf",
+                    };
+                    json!({ "kind": "text", "text": text, "source_app": source_app, "format": format })
+                }
+                ClipboardContent::Files(files) => json!({ "kind": "files", "files": files }),
+                ClipboardContent::Image { width, height } => {
+                    json!({ "kind": "image", "width": width, "height": height })
+                }
+                ClipboardContent::Other => json!({ "kind": "other" }),
+            };
+            merge(&mut value, fields);
+            value
+        }
+        InputEvent::LockState(ls) => json!({
+            "type": "lock-state",
+            "timestamp_ms": instant_to_unix_ms(ls.timestamp),
+            "caps_lock": ls.caps_lock,
+            "num_lock": ls.num_lock,
+            "scroll_lock": ls.scroll_lock,
+        }),
+        InputEvent::Gamepad(ge) => json!({
+            "type": "gamepad",
+            "timestamp_ms": instant_to_unix_ms(ge.timestamp),
+            "controller_id": ge.controller_id,
+            "button": ge.button.label(),
+            "action": key_action_name(ge.action),
+        }),
+        InputEvent::ImeCandidates(entries) => json!({
+            "type": "ime-candidates",
+            "candidates": entries,
+        }),
+        InputEvent::PreviewMode { .. }
+        | InputEvent::PreviewConfig { .. }
+        | InputEvent::DpiChanged { .. }
+        | InputEvent::DisplayChanged
+        | InputEvent::ConfigChanged
+        | InputEvent::ToggleOsd
+        | InputEvent::ClearDisplay
+        | InputEvent::MacroRecord { .. }
+        | InputEvent::MacroPlay
+        | InputEvent::Screenshot
+        | InputEvent::StateSnapshot => return None,
+    };
+    Some(value)
+}
+
+fn merge(target: &mut Value, fields: Value) {
+    if let (Some(target), Value::Object(fields)) = (target.as_object_mut(), fields) {
+        target.extend(fields);
+    }
+}
+
+fn modifiers_json(modifiers: &Modifiers) -> Value {
+    json!({
+        "ctrl": modifiers.ctrl,
+        "shift": modifiers.shift,
+        "alt": modifiers.alt,
+        "win": modifiers.win,
+        "altgr": modifiers.altgr,
+    })
+}
+
+fn key_action_name(action: KeyAction) -> &'static str {
+    match action {
+        KeyAction::Down => "down",
+        KeyAction::Up => "up",
+    }
+}
+
+fn mouse_button_name(button: MouseButton) -> &'static str {
+    match button {
+        MouseButton::Left => "left",
+        MouseButton::Right => "right",
+        MouseButton::Middle => "middle",
+        MouseButton::X1 => "x1",
+        MouseButton::X2 => "x2",
+    }
+}
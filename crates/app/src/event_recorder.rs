@@ -0,0 +1,81 @@
+//! `diagnostics.record_events` による入力イベントの記録
+
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use ystrokey_core::{DiagnosticsConfig, DiagnosticsLevel, EventRecorder, InputEvent};
+
+use crate::logger;
+
+struct RecorderState {
+    base_dir: PathBuf,
+    recorder: Option<EventRecorder>,
+    /// `--replay` 中は再生したイベントを記録し直さない（再生中のファイルを上書きしてしまう）
+    disabled: bool,
+}
+
+static RECORDER: OnceLock<Mutex<RecorderState>> = OnceLock::new();
+
+/// 記録先の相対パスを解決する基準ディレクトリを決め、設定を反映する
+///
+/// `replaying` なら設定に関わらずこのセッションでは記録しない。
+pub fn init(base_dir: &Path, config: &DiagnosticsConfig, replaying: bool) {
+    let _ = RECORDER.set(Mutex::new(RecorderState {
+        base_dir: base_dir.to_path_buf(),
+        recorder: None,
+        disabled: replaying,
+    }));
+    update_config(config);
+}
+
+/// 記録の有効/無効・記録先の変更を反映（記録先が同じなら開き直さない）
+pub fn update_config(config: &DiagnosticsConfig) {
+    let Some(lock) = RECORDER.get() else {
+        return;
+    };
+    let Ok(mut state) = lock.lock() else {
+        return;
+    };
+    if !config.record_events || state.disabled {
+        state.recorder = None;
+        return;
+    }
+    let path = match &config.events_file {
+        Some(file) => state.base_dir.join(file),
+        None => state.base_dir.join("logs").join("events.jsonl"),
+    };
+    if state.recorder.as_ref().is_some_and(|r| r.path() == path) {
+        return;
+    }
+    state.recorder = match EventRecorder::open(&path) {
+        Ok(recorder) => Some(recorder),
+        Err(e) => {
+            logger::log(
+                DiagnosticsLevel::Warn,
+                &format!("Event recording disabled ({}): {e}", path.display()),
+            );
+            None
+        }
+    };
+}
+
+/// 記録中なら 1 イベント追記する（書き込みに失敗したら記録を止める）
+pub fn record(event: &InputEvent) {
+    let Some(lock) = RECORDER.get() else {
+        return;
+    };
+    let Ok(mut state) = lock.lock() else {
+        return;
+    };
+    let Some(recorder) = state.recorder.as_mut() else {
+        return;
+    };
+    if let Err(e) = recorder.record(event) {
+        let path = recorder.path().display().to_string();
+        state.recorder = None;
+        logger::log(
+            DiagnosticsLevel::Warn,
+            &format!("Event recording stopped ({path}): {e}"),
+        );
+    }
+}
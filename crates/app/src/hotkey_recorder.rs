@@ -0,0 +1,273 @@
+use std::cell::RefCell;
+
+use windows::core::w;
+use windows::Win32::Foundation::*;
+use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::UI::Input::KeyboardAndMouse::*;
+use windows::Win32::UI::WindowsAndMessaging::*;
+
+/// 録音ダイアログの状態（ダイアログとフックは同じ UI スレッドで動く）
+struct Recorder {
+    dialog: HWND,
+    edit: HWND,
+    ok: HWND,
+    captured: Option<String>,
+    accepted: bool,
+    done: bool,
+}
+
+thread_local! {
+    static RECORDER: RefCell<Option<Recorder>> = const { RefCell::new(None) };
+}
+
+const DIALOG_WIDTH: i32 = 360;
+const DIALOG_HEIGHT: i32 = 170;
+
+/// キー入力を待つモーダルダイアログを開き、押された組み合わせを `"Ctrl+Alt+F12"` 形式で返す
+///
+/// ダイアログの間だけ `WH_KEYBOARD_LL` フックを入れ、最初の非修飾キーの押下と
+/// その時点の修飾キー状態を記録する。Esc・Cancel・閉じるボタンでは None。
+pub fn record_hotkey(parent: HWND) -> Option<String> {
+    if RECORDER.with(|r| r.borrow().is_some()) {
+        return None;
+    }
+
+    unsafe {
+        let wc = WNDCLASSEXW {
+            cbSize: std::mem::size_of::<WNDCLASSEXW>() as u32,
+            lpfnWndProc: Some(recorder_wnd_proc),
+            hCursor: LoadCursorW(None, IDC_ARROW).unwrap_or_default(),
+            hbrBackground: windows::Win32::Graphics::Gdi::GetSysColorBrush(
+                windows::Win32::Graphics::Gdi::COLOR_BTNFACE,
+            ),
+            lpszClassName: w!("yStrokeyHotkeyRecorder"),
+            ..Default::default()
+        };
+        let _ = RegisterClassExW(&wc);
+
+        // 親ウィンドウの中央に出す
+        let mut parent_rect = RECT::default();
+        let _ = GetWindowRect(parent, &mut parent_rect);
+        let x = parent_rect.left + (parent_rect.right - parent_rect.left - DIALOG_WIDTH) / 2;
+        let y = parent_rect.top + (parent_rect.bottom - parent_rect.top - DIALOG_HEIGHT) / 2;
+
+        let dialog = CreateWindowExW(
+            WS_EX_DLGMODALFRAME,
+            w!("yStrokeyHotkeyRecorder"),
+            w!("Record hotkey"),
+            WS_POPUP | WS_CAPTION | WS_SYSMENU,
+            x,
+            y,
+            DIALOG_WIDTH,
+            DIALOG_HEIGHT,
+            parent,
+            None,
+            None,
+            None,
+        )
+        .ok()?;
+
+        let mut client = RECT::default();
+        let _ = GetClientRect(dialog, &mut client);
+        let width = client.right - client.left - 32;
+
+        let _ = CreateWindowExW(
+            WINDOW_EX_STYLE::default(),
+            w!("STATIC"),
+            w!("Press a key combination (Esc to cancel)"),
+            WS_CHILD | WS_VISIBLE,
+            16,
+            14,
+            width,
+            20,
+            dialog,
+            None,
+            None,
+            None,
+        );
+        let edit = CreateWindowExW(
+            WS_EX_CLIENTEDGE,
+            w!("EDIT"),
+            None,
+            WS_CHILD | WS_VISIBLE | WINDOW_STYLE((ES_AUTOHSCROLL | ES_READONLY) as u32),
+            16,
+            40,
+            width,
+            24,
+            dialog,
+            None,
+            None,
+            None,
+        )
+        .unwrap_or_default();
+        let ok = create_button(dialog, w!("OK"), IDOK.0, client.right - 16 - 180 - 8, client.bottom - 40);
+        let _ = create_button(dialog, w!("Cancel"), IDCANCEL.0, client.right - 16 - 90, client.bottom - 40);
+        // 何か記録するまで OK は押せない
+        let _ = EnableWindow(ok, false);
+
+        RECORDER.with(|r| {
+            r.replace(Some(Recorder {
+                dialog,
+                edit,
+                ok,
+                captured: None,
+                accepted: false,
+                done: false,
+            }))
+        });
+
+        let hmod = GetModuleHandleW(None).ok().map(|h| HINSTANCE(h.0));
+        let hook = SetWindowsHookExW(WH_KEYBOARD_LL, Some(record_hook_proc), hmod.unwrap_or_default(), 0);
+        if hook.is_ok() {
+            let _ = EnableWindow(parent, false);
+            let _ = ShowWindow(dialog, SW_SHOW);
+
+            // ダイアログを閉じるまでここでメッセージを回す（LL フックにも必要）
+            let mut msg = MSG::default();
+            while !RECORDER.with(|r| r.borrow().as_ref().is_none_or(|rec| rec.done)) {
+                let ret = GetMessageW(&mut msg, None, 0, 0);
+                if ret.0 <= 0 {
+                    if ret.0 == 0 {
+                        // WM_QUIT は外側のループに引き渡す
+                        PostQuitMessage(msg.wParam.0 as i32);
+                    }
+                    break;
+                }
+                let _ = TranslateMessage(&msg);
+                DispatchMessageW(&msg);
+            }
+
+            let _ = EnableWindow(parent, true);
+        }
+        if let Ok(hook) = hook {
+            let _ = UnhookWindowsHookEx(hook);
+        }
+        let _ = DestroyWindow(dialog);
+
+        let recorder = RECORDER.with(|r| r.borrow_mut().take())?;
+        if recorder.accepted {
+            recorder.captured
+        } else {
+            None
+        }
+    }
+}
+
+unsafe fn create_button(parent: HWND, text: windows::core::PCWSTR, id: i32, x: i32, y: i32) -> HWND {
+    CreateWindowExW(
+        WINDOW_EX_STYLE::default(),
+        w!("BUTTON"),
+        text,
+        WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE(BS_PUSHBUTTON as u32),
+        x,
+        y,
+        90,
+        28,
+        parent,
+        HMENU(id as usize as *mut _),
+        None,
+        None,
+    )
+    .unwrap_or_default()
+}
+
+fn finish(accepted: bool) {
+    RECORDER.with(|r| {
+        if let Some(rec) = r.borrow_mut().as_mut() {
+            rec.accepted = accepted && rec.captured.is_some();
+            rec.done = true;
+        }
+    });
+}
+
+unsafe extern "system" fn recorder_wnd_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    match msg {
+        WM_COMMAND => {
+            match (wparam.0 & 0xFFFF) as i32 {
+                id if id == IDOK.0 => finish(true),
+                id if id == IDCANCEL.0 => finish(false),
+                _ => {}
+            }
+            LRESULT(0)
+        }
+        // 破棄は record_hotkey 側で行う
+        WM_CLOSE => {
+            finish(false);
+            LRESULT(0)
+        }
+        _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+    }
+}
+
+fn is_modifier_vk(vk: u32) -> bool {
+    [
+        VK_SHIFT, VK_LSHIFT, VK_RSHIFT, VK_CONTROL, VK_LCONTROL, VK_RCONTROL, VK_MENU, VK_LMENU,
+        VK_RMENU, VK_LWIN, VK_RWIN,
+    ]
+    .iter()
+    .any(|k| k.0 as u32 == vk)
+}
+
+unsafe fn is_down(vk: VIRTUAL_KEY) -> bool {
+    GetAsyncKeyState(vk.0 as i32) < 0
+}
+
+/// 現在の修飾キー状態とキー名から `"Mod+Key"` 文字列を作る
+unsafe fn format_combo(key_name: &str) -> String {
+    let mut parts = Vec::new();
+    if is_down(VK_CONTROL) {
+        parts.push("Ctrl");
+    }
+    if is_down(VK_MENU) {
+        parts.push("Alt");
+    }
+    if is_down(VK_SHIFT) {
+        parts.push("Shift");
+    }
+    if is_down(VK_LWIN) || is_down(VK_RWIN) {
+        parts.push("Win");
+    }
+    parts.push(key_name);
+    parts.join("+")
+}
+
+unsafe extern "system" fn record_hook_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    if code >= 0 && matches!(wparam.0 as u32, WM_KEYDOWN | WM_SYSKEYDOWN) {
+        let kb = &*(lparam.0 as *const KBDLLHOOKSTRUCT);
+        let Some((dialog, edit, ok, captured)) = RECORDER.with(|r| {
+            r.borrow()
+                .as_ref()
+                .map(|rec| (rec.dialog, rec.edit, rec.ok, rec.captured.is_some()))
+        }) else {
+            return CallNextHookEx(None, code, wparam, lparam);
+        };
+
+        if kb.vkCode == VK_ESCAPE.0 as u32 {
+            let _ = PostMessageW(dialog, WM_CLOSE, WPARAM(0), LPARAM(0));
+            return LRESULT(1);
+        }
+        // 修飾キーは状態を GetAsyncKeyState で読むので素通しする
+        if !captured && !is_modifier_vk(kb.vkCode) {
+            if let Some(name) = crate::vk_to_key_name(kb.vkCode) {
+                let combo = format_combo(&name);
+                let wide: Vec<u16> = combo.encode_utf16().chain(std::iter::once(0)).collect();
+                RECORDER.with(|r| {
+                    if let Some(rec) = r.borrow_mut().as_mut() {
+                        rec.captured = Some(combo);
+                    }
+                });
+                // SetWindowTextW は EN_CHANGE をダイアログへ送るので借用の外で呼ぶ
+                let _ = SetWindowTextW(edit, windows::core::PCWSTR(wide.as_ptr()));
+                let _ = EnableWindow(ok, true);
+                // 記録したキーは他のアプリ（Alt+F4 など）に渡さない
+                return LRESULT(1);
+            }
+        }
+    }
+    CallNextHookEx(None, code, wparam, lparam)
+}
@@ -0,0 +1,269 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+use ystrokey_core::{DiagnosticsLevel, DisplayItem, InputEvent};
+
+use crate::event_json::{display_item_json, input_event_json};
+use crate::logger;
+
+/// SSE クライアントへの書き込みタイムアウト（詰まったクライアントは切断）
+const WRITE_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// `allowed_origin` に一致した Origin へ返す CORS ヘッダ（`*` は返さない）
+fn cors_headers(origin: Option<&str>) -> String {
+    origin.map_or_else(String::new, |origin| {
+        format!(
+            "Access-Control-Allow-Origin: {origin}\r\n\
+             Vary: Origin\r\n\
+             Access-Control-Allow-Methods: GET, OPTIONS\r\n\
+             Access-Control-Allow-Headers: *\r\n"
+        )
+    })
+}
+
+/// ブラウザからの接続を許可するか判定する。
+///
+/// `Origin` ヘッダのない非ブラウザクライアントは常に許可し、
+/// ブラウザは `allowed` と一致する Origin だけを許可する。
+pub(crate) fn origin_allowed(origin: Option<&str>, allowed: Option<&str>) -> bool {
+    match origin {
+        None => true,
+        Some(origin) => allowed.is_some_and(|allowed| {
+            allowed
+                .trim()
+                .trim_end_matches('/')
+                .eq_ignore_ascii_case(origin.trim())
+        }),
+    }
+}
+
+/// OSD 状態を配信する簡易 HTTP サーバー（localhost のみ）
+///
+/// - `GET /state`  : 表示中アイテムの JSON スナップショット
+/// - `GET /events` : 入力イベントの SSE ストリーム（chunked）
+pub struct HttpServer {
+    items: Arc<Mutex<Vec<DisplayItem>>>,
+    event_tx: Sender<String>,
+}
+
+impl HttpServer {
+    /// 指定ポートで待ち受けを開始
+    ///
+    /// `allowed_origin` 以外の `Origin` を持つリクエストは 403 で拒否する。
+    pub fn start(port: u16, allowed_origin: Option<String>) -> std::io::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+        let items: Arc<Mutex<Vec<DisplayItem>>> = Arc::new(Mutex::new(Vec::new()));
+        let subscribers: Arc<Mutex<Vec<TcpStream>>> = Arc::new(Mutex::new(Vec::new()));
+        let (event_tx, event_rx) = mpsc::channel::<String>();
+
+        let accept_items = Arc::clone(&items);
+        let accept_subscribers = Arc::clone(&subscribers);
+        thread::Builder::new()
+            .name("http-accept".into())
+            .spawn(move || {
+                accept_loop(listener, allowed_origin, accept_items, accept_subscribers)
+            })?;
+
+        thread::Builder::new()
+            .name("http-sse".into())
+            .spawn(move || broadcast_loop(event_rx, subscribers))?;
+
+        Ok(Self { items, event_tx })
+    }
+
+    /// `state.tick()` 後に表示中アイテムを更新
+    pub fn update_items(&self, items: &[DisplayItem]) {
+        if let Ok(mut shared) = self.items.lock() {
+            shared.clear();
+            shared.extend_from_slice(items);
+        }
+    }
+
+    /// 入力イベントを SSE 購読者へ配信
+    pub fn publish_event(&self, event: &InputEvent) {
+        if let Some(value) = input_event_json(event) {
+            let _ = self.event_tx.send(value.to_string());
+        }
+    }
+}
+
+fn accept_loop(
+    listener: TcpListener,
+    allowed_origin: Option<String>,
+    items: Arc<Mutex<Vec<DisplayItem>>>,
+    subscribers: Arc<Mutex<Vec<TcpStream>>>,
+) {
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else {
+            continue;
+        };
+        let items = Arc::clone(&items);
+        let subscribers = Arc::clone(&subscribers);
+        let allowed_origin = allowed_origin.clone();
+        let spawned = thread::Builder::new()
+            .name("http-client".into())
+            .spawn(move || {
+                if let Err(e) =
+                    handle_connection(stream, allowed_origin.as_deref(), &items, &subscribers)
+                {
+                    logger::log(DiagnosticsLevel::Debug, &format!("HTTP client error: {e}"));
+                }
+            });
+        if let Err(e) = spawned {
+            logger::log(
+                DiagnosticsLevel::Warn,
+                &format!("HTTP client thread spawn failed: {e}"),
+            );
+        }
+    }
+}
+
+fn handle_connection(
+    mut stream: TcpStream,
+    allowed_origin: Option<&str>,
+    items: &Mutex<Vec<DisplayItem>>,
+    subscribers: &Mutex<Vec<TcpStream>>,
+) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    // Origin 以外のヘッダは読み捨て（空行まで）
+    let mut origin: Option<String> = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("origin") {
+                origin = Some(value.trim().to_string());
+            }
+        }
+    }
+
+    if !origin_allowed(origin.as_deref(), allowed_origin) {
+        logger::log(
+            DiagnosticsLevel::Debug,
+            &format!(
+                "HTTP request from disallowed origin rejected: {}",
+                origin.unwrap_or_default()
+            ),
+        );
+        return write_response(&mut stream, "403 Forbidden", "text/plain", "", "forbidden");
+    }
+    let cors = cors_headers(origin.as_deref());
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("");
+    let path = parts.next().unwrap_or("");
+    let path = path.split('?').next().unwrap_or("");
+
+    match (method, path) {
+        ("OPTIONS", _) => write_response(&mut stream, "204 No Content", "text/plain", &cors, ""),
+        ("GET", "/state") => {
+            let snapshot: Vec<Value> = items
+                .lock()
+                .map(|items| items.iter().map(display_item_json).collect())
+                .unwrap_or_default();
+            let body = json!({ "active_items": snapshot }).to_string();
+            write_response(&mut stream, "200 OK", "application/json", &cors, &body)
+        }
+        ("GET", "/events") => {
+            let header = format!(
+                "HTTP/1.1 200 OK\r\n\
+                 Content-Type: text/event-stream\r\n\
+                 Cache-Control: no-cache\r\n\
+                 Connection: keep-alive\r\n\
+                 Transfer-Encoding: chunked\r\n\
+                 {cors}\r\n"
+            );
+            stream.write_all(header.as_bytes())?;
+            stream.flush()?;
+            if let Ok(mut subs) = subscribers.lock() {
+                subs.push(stream);
+            }
+            Ok(())
+        }
+        ("GET", _) => write_response(
+            &mut stream,
+            "404 Not Found",
+            "text/plain",
+            &cors,
+            "not found",
+        ),
+        _ => write_response(
+            &mut stream,
+            "405 Method Not Allowed",
+            "text/plain",
+            &cors,
+            "method not allowed",
+        ),
+    }
+}
+
+fn write_response(
+    stream: &mut TcpStream,
+    status: &str,
+    content_type: &str,
+    cors: &str,
+    body: &str,
+) -> std::io::Result<()> {
+    let response = format!(
+        "HTTP/1.1 {status}\r\n\
+         Content-Type: {content_type}; charset=utf-8\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\
+         {cors}\r\n{body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes())?;
+    stream.flush()
+}
+
+fn broadcast_loop(rx: Receiver<String>, subscribers: Arc<Mutex<Vec<TcpStream>>>) {
+    while let Ok(payload) = rx.recv() {
+        let data = format!("data: {payload}\n\n");
+        let chunk = format!("{:x}\r\n{data}\r\n", data.len());
+        if let Ok(mut subs) = subscribers.lock() {
+            // 書き込みに失敗したクライアントは切断済みとして除外
+            subs.retain_mut(|stream| stream.write_all(chunk.as_bytes()).is_ok());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn origin_allowed_accepts_only_the_configured_origin() {
+        // Origin なし（curl やツール類）は常に許可
+        assert!(origin_allowed(None, None));
+        // 設定がなければブラウザからのアクセスはすべて拒否
+        assert!(!origin_allowed(Some("https://evil.example"), None));
+
+        let allowed = Some("http://localhost:3000/");
+        assert!(origin_allowed(Some("http://localhost:3000"), allowed));
+        assert!(origin_allowed(Some("HTTP://LOCALHOST:3000"), allowed));
+        assert!(!origin_allowed(Some("http://localhost:3001"), allowed));
+        assert!(!origin_allowed(Some("null"), allowed));
+    }
+
+    #[test]
+    fn cors_headers_echo_origin_instead_of_wildcard() {
+        assert_eq!(cors_headers(None), "");
+        let headers = cors_headers(Some("http://localhost:3000"));
+        assert!(headers.contains("Access-Control-Allow-Origin: http://localhost:3000\r\n"));
+        assert!(!headers.contains("Allow-Origin: *"));
+    }
+}
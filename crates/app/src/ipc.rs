@@ -0,0 +1,274 @@
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::SyncSender;
+use std::sync::{Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use windows::core::HSTRING;
+use windows::Win32::Foundation::{CloseHandle, GetLastError, ERROR_PIPE_CONNECTED, HANDLE};
+use windows::Win32::Storage::FileSystem::{
+    FlushFileBuffers, ReadFile, WriteFile, PIPE_ACCESS_DUPLEX,
+};
+use windows::Win32::System::Pipes::{
+    ConnectNamedPipe, CreateNamedPipeW, DisconnectNamedPipe, PIPE_READMODE_BYTE,
+    PIPE_REJECT_REMOTE_CLIENTS, PIPE_TYPE_BYTE, PIPE_UNLIMITED_INSTANCES, PIPE_WAIT,
+};
+
+use ystrokey_core::{DiagnosticsLevel, DisplayStateSnapshot, InputEvent};
+
+use crate::logger;
+
+const PIPE_NAME: &str = r"\\.\pipe\yStrokey";
+
+const PIPE_BUFFER_SIZE: u32 = 4096;
+
+/// 1行あたりの最大長（これを超える入力は接続ごと破棄）
+const MAX_LINE_BYTES: usize = 64 * 1024;
+
+/// パイプ作成に失敗したときの再試行間隔
+const PIPE_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// `status` でメインループのスナップショットを待つ上限
+const SNAPSHOT_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// メインループが `InputEvent::StateSnapshot` に応じて置いていく表示状態
+struct SnapshotSlot {
+    /// 置かれるたびに増える（要求後に新しいものが届いたかの判定用）
+    generation: u64,
+    snapshot: Option<DisplayStateSnapshot>,
+}
+
+static SNAPSHOT: Mutex<SnapshotSlot> = Mutex::new(SnapshotSlot {
+    generation: 0,
+    snapshot: None,
+});
+static SNAPSHOT_READY: Condvar = Condvar::new();
+
+/// クライアントからのコマンド（改行区切りJSON）
+#[derive(Debug, Deserialize)]
+#[serde(tag = "cmd", rename_all = "kebab-case")]
+enum IpcCommand {
+    Toggle,
+    Clear,
+    Reload,
+    Status,
+}
+
+/// クライアントへの応答
+#[derive(Debug, Serialize)]
+struct IpcResponse {
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    enabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    state: Option<DisplayStateSnapshot>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl IpcResponse {
+    fn ok() -> Self {
+        Self {
+            ok: true,
+            enabled: None,
+            state: None,
+            error: None,
+        }
+    }
+
+    fn error(message: impl Into<String>) -> Self {
+        Self {
+            ok: false,
+            enabled: None,
+            state: None,
+            error: Some(message.into()),
+        }
+    }
+}
+
+/// スレッド間で受け渡すためのパイプハンドル（Drop時に切断・解放）
+struct PipeHandle(HANDLE);
+
+// 名前付きパイプのハンドルは1スレッドのみが所有して使う
+unsafe impl Send for PipeHandle {}
+
+impl Drop for PipeHandle {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = FlushFileBuffers(self.0);
+            let _ = DisconnectNamedPipe(self.0);
+            let _ = CloseHandle(self.0);
+        }
+    }
+}
+
+/// 外部ツール（StreamDeck / OBS スクリプト等）向けの名前付きパイプサーバー
+///
+/// `\\.\pipe\yStrokey` で接続を待ち受け、接続ごとにスレッドを起動する。
+/// コマンドは既存のイベントチャネルへ `InputEvent` として送る。
+pub struct IpcServer {
+    _listener: JoinHandle<()>,
+}
+
+impl IpcServer {
+    /// リスナースレッドを起動
+    pub fn start(
+        tx: SyncSender<InputEvent>,
+        osd_enabled: &'static AtomicBool,
+    ) -> std::io::Result<Self> {
+        let listener = thread::Builder::new()
+            .name("ipc-listener".into())
+            .spawn(move || listen(tx, osd_enabled))?;
+        Ok(Self {
+            _listener: listener,
+        })
+    }
+}
+
+/// メインループから表示状態のスナップショットを受け取り、待っている `status` 応答へ渡す
+pub fn publish_snapshot(snapshot: DisplayStateSnapshot) {
+    if let Ok(mut slot) = SNAPSHOT.lock() {
+        slot.generation += 1;
+        slot.snapshot = Some(snapshot);
+        SNAPSHOT_READY.notify_all();
+    }
+}
+
+/// メインループへスナップショットを要求し、届くまで待つ（時間切れなら None）
+fn request_snapshot(tx: &SyncSender<InputEvent>) -> Option<DisplayStateSnapshot> {
+    let slot = SNAPSHOT.lock().ok()?;
+    let requested = slot.generation;
+    tx.try_send(InputEvent::StateSnapshot).ok()?;
+    let (slot, _) = SNAPSHOT_READY
+        .wait_timeout_while(slot, SNAPSHOT_TIMEOUT, |slot| slot.generation == requested)
+        .ok()?;
+    if slot.generation == requested {
+        return None;
+    }
+    slot.snapshot.clone()
+}
+
+/// 起動中のインスタンスへコマンドを1つ送り、応答行を返す（クライアント側）
+pub fn send_command(cmd: &str) -> std::io::Result<String> {
+    let mut pipe = OpenOptions::new().read(true).write(true).open(PIPE_NAME)?;
+    let request = serde_json::json!({ "cmd": cmd });
+    writeln!(pipe, "{}", request)?;
+    pipe.flush()?;
+
+    let mut response = String::new();
+    BufReader::new(pipe).read_line(&mut response)?;
+    Ok(response.trim_end().to_string())
+}
+
+fn listen(tx: SyncSender<InputEvent>, osd_enabled: &'static AtomicBool) {
+    loop {
+        let handle = unsafe {
+            CreateNamedPipeW(
+                &HSTRING::from(PIPE_NAME),
+                PIPE_ACCESS_DUPLEX,
+                // SMB 経由の別マシンからの接続は受け付けない
+                PIPE_TYPE_BYTE | PIPE_READMODE_BYTE | PIPE_WAIT | PIPE_REJECT_REMOTE_CLIENTS,
+                PIPE_UNLIMITED_INSTANCES,
+                PIPE_BUFFER_SIZE,
+                PIPE_BUFFER_SIZE,
+                0,
+                None,
+            )
+        };
+        if handle.is_invalid() {
+            logger::log(
+                DiagnosticsLevel::Warn,
+                &format!("IPC pipe creation failed: {:?}", unsafe { GetLastError() }),
+            );
+            thread::sleep(PIPE_RETRY_DELAY);
+            continue;
+        }
+        let pipe = PipeHandle(handle);
+
+        // クライアントが CreateNamedPipe と ConnectNamedPipe の間に接続した場合も成功扱い
+        let connected = unsafe { ConnectNamedPipe(pipe.0, None) }.is_ok()
+            || unsafe { GetLastError() } == ERROR_PIPE_CONNECTED;
+        if !connected {
+            continue;
+        }
+
+        let client_tx = tx.clone();
+        let spawned = thread::Builder::new()
+            .name("ipc-client".into())
+            .spawn(move || serve_client(pipe, client_tx, osd_enabled));
+        if let Err(e) = spawned {
+            logger::log(
+                DiagnosticsLevel::Warn,
+                &format!("IPC client thread spawn failed: {e}"),
+            );
+        }
+    }
+}
+
+fn serve_client(pipe: PipeHandle, tx: SyncSender<InputEvent>, osd_enabled: &'static AtomicBool) {
+    let mut pending: Vec<u8> = Vec::new();
+    let mut buf = [0u8; PIPE_BUFFER_SIZE as usize];
+
+    loop {
+        let mut read = 0u32;
+        let result = unsafe { ReadFile(pipe.0, Some(&mut buf), Some(&mut read), None) };
+        if result.is_err() || read == 0 {
+            return;
+        }
+        pending.extend_from_slice(&buf[..read as usize]);
+
+        while let Some(pos) = pending.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = pending.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let response = handle_command(line, &tx, osd_enabled);
+            let mut out = serde_json::to_vec(&response).unwrap_or_default();
+            out.push(b'\n');
+            let mut written = 0u32;
+            if unsafe { WriteFile(pipe.0, Some(&out), Some(&mut written), None) }.is_err() {
+                return;
+            }
+        }
+
+        if pending.len() > MAX_LINE_BYTES {
+            logger::log(DiagnosticsLevel::Warn, "IPC request too long; closing connection");
+            return;
+        }
+    }
+}
+
+fn handle_command(
+    line: &str,
+    tx: &SyncSender<InputEvent>,
+    osd_enabled: &AtomicBool,
+) -> IpcResponse {
+    let command: IpcCommand = match serde_json::from_str(line) {
+        Ok(c) => c,
+        Err(e) => return IpcResponse::error(format!("invalid command: {e}")),
+    };
+
+    let event = match command {
+        IpcCommand::Status => {
+            return IpcResponse {
+                enabled: Some(osd_enabled.load(Ordering::Relaxed)),
+                state: request_snapshot(tx),
+                ..IpcResponse::ok()
+            };
+        }
+        IpcCommand::Toggle => InputEvent::ToggleOsd,
+        IpcCommand::Clear => InputEvent::ClearDisplay,
+        IpcCommand::Reload => InputEvent::ConfigChanged,
+    };
+
+    match tx.try_send(event) {
+        Ok(()) => IpcResponse::ok(),
+        Err(_) => IpcResponse::error("event queue is full"),
+    }
+}
@@ -1,20 +1,32 @@
+use std::collections::VecDeque;
 use std::fs::{self, OpenOptions};
 use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::sync::{Mutex, OnceLock};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use ystrokey_core::{DiagnosticsConfig, DiagnosticsLevel};
+use windows::Win32::System::SystemInformation::GetLocalTime;
+
+use ystrokey_core::{DiagnosticsConfig, DiagnosticsLevel, LogFormat, LogTimestamp};
 
 struct FileLogger {
     config: DiagnosticsConfig,
     log_path: PathBuf,
+    /// 直近のログ行（設定画面の Logs 表示用）
+    recent: VecDeque<String>,
 }
 
+/// メモリに保持する直近ログ行の上限
+const RECENT_LINES_CAPACITY: usize = 100;
+
 static LOGGER: OnceLock<Mutex<FileLogger>> = OnceLock::new();
 
+/// 初回 `init` 時に決まるログファイルのパス
+static LOG_PATH: OnceLock<PathBuf> = OnceLock::new();
+
 pub fn init(base_dir: &Path, config: &DiagnosticsConfig) {
     let log_path = base_dir.join("logs").join("ystrokey.log");
+    let _ = LOG_PATH.set(log_path.clone());
     if let Some(lock) = LOGGER.get() {
         if let Ok(mut logger) = lock.lock() {
             logger.log_path = log_path;
@@ -26,8 +38,44 @@ pub fn init(base_dir: &Path, config: &DiagnosticsConfig) {
     let logger = FileLogger {
         config: config.clone(),
         log_path,
+        recent: VecDeque::with_capacity(RECENT_LINES_CAPACITY),
     };
     let _ = LOGGER.set(Mutex::new(logger));
+    // core など `log` クレート経由の出力もこのロガーに流す
+    if log::set_logger(&LOG_BRIDGE).is_ok() {
+        log::set_max_level(log::LevelFilter::Debug);
+    }
+}
+
+/// `log` クレートのマクロを `log` 関数へ中継する
+struct LogBridge;
+
+static LOG_BRIDGE: LogBridge = LogBridge;
+
+impl log::Log for LogBridge {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::Level::Debug
+    }
+
+    fn log(&self, record: &log::Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let level = match record.level() {
+            log::Level::Error => DiagnosticsLevel::Error,
+            log::Level::Warn => DiagnosticsLevel::Warn,
+            log::Level::Info => DiagnosticsLevel::Info,
+            log::Level::Debug | log::Level::Trace => DiagnosticsLevel::Debug,
+        };
+        log(level, &record.args().to_string());
+    }
+
+    fn flush(&self) {}
+}
+
+/// ログファイルのパス（`init` 前は None）
+pub fn log_path() -> Option<&'static Path> {
+    LOG_PATH.get().map(PathBuf::as_path)
 }
 
 pub fn update_config(config: &DiagnosticsConfig) {
@@ -44,7 +92,7 @@ pub fn log(level: DiagnosticsLevel, message: &str) {
         return;
     };
 
-    let Ok(logger) = lock.lock() else {
+    let Ok(mut logger) = lock.lock() else {
         eprintln!("[{}] {}", level_name(level), message);
         return;
     };
@@ -53,14 +101,12 @@ pub fn log(level: DiagnosticsLevel, message: &str) {
         return;
     }
 
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_secs();
-    let line = format!("[{}][{}] {}\n", now, level_name(level), message);
+    let now = current_timestamp(logger.config.timestamp);
+    let line = format_line(logger.config.log_format, &now, level, message);
 
     // Keep stderr output for visibility in development.
     eprint!("{}", line);
+    push_recent(&mut logger.recent, &line);
 
     if logger.config.file_logging_enabled {
         if let Err(err) = append_with_rotation(
@@ -74,12 +120,119 @@ pub fn log(level: DiagnosticsLevel, message: &str) {
     }
 }
 
+/// 直近のログ行（古い順、最大 `RECENT_LINES_CAPACITY` 行）
+pub fn recent_lines() -> Vec<String> {
+    LOGGER
+        .get()
+        .and_then(|lock| lock.lock().ok())
+        .map(|logger| logger.recent.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+fn push_recent(recent: &mut VecDeque<String>, line: &str) {
+    if recent.len() >= RECENT_LINES_CAPACITY {
+        recent.pop_front();
+    }
+    recent.push_back(line.trim_end().to_string());
+}
+
+/// 致命的エラーを記録し、ファイルへの書き込みを待つ
+pub fn log_fatal(message: &str) {
+    log(DiagnosticsLevel::Error, message);
+    std::thread::sleep(Duration::from_millis(100));
+}
+
+/// パニック内容と発生位置をログに残すフックを登録
+///
+/// 記録後は既定のフックを呼ぶため、パニック自体は抑止しない。
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let payload = info
+            .payload()
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| info.payload().downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("<non-string panic payload>");
+        let location = info
+            .location()
+            .map(|l| format!("{}:{}", l.file(), l.line()))
+            .unwrap_or_else(|| "unknown location".to_string());
+        log_fatal(&format!("panic at {}: {}", location, payload));
+        default_hook(info);
+    }));
+}
+
+/// ログ行の時刻
+enum Timestamp {
+    /// Unix 秒
+    Epoch(u64),
+    /// ローカル時刻の ISO-8601 表記（タイムゾーンなし）
+    LocalIso(String),
+}
+
+impl Timestamp {
+    fn to_json(&self) -> serde_json::Value {
+        match self {
+            Timestamp::Epoch(secs) => serde_json::json!(secs),
+            Timestamp::LocalIso(text) => serde_json::json!(text),
+        }
+    }
+}
+
+impl std::fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Timestamp::Epoch(secs) => write!(f, "{}", secs),
+            Timestamp::LocalIso(text) => f.write_str(text),
+        }
+    }
+}
+
+fn current_timestamp(kind: LogTimestamp) -> Timestamp {
+    match kind {
+        LogTimestamp::Epoch => Timestamp::Epoch(
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        ),
+        LogTimestamp::LocalIso => {
+            let t = unsafe { GetLocalTime() };
+            Timestamp::LocalIso(format_iso(t.wYear, t.wMonth, t.wDay, t.wHour, t.wMinute, t.wSecond))
+        }
+    }
+}
+
+fn format_iso(year: u16, month: u16, day: u16, hour: u16, minute: u16, second: u16) -> String {
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        year, month, day, hour, minute, second
+    )
+}
+
+fn format_line(format: LogFormat, ts: &Timestamp, level: DiagnosticsLevel, message: &str) -> String {
+    match format {
+        LogFormat::Text => format!("[{}][{}] {}\n", ts, level_name(level), message),
+        LogFormat::Json => {
+            // JSONL では設定値と同じ小文字表記にそろえる
+            let value = serde_json::json!({
+                "ts": ts.to_json(),
+                "level": level_name(level).to_ascii_lowercase(),
+                "msg": message,
+            });
+            format!("{}\n", value)
+        }
+    }
+}
+
 fn enabled(message_level: DiagnosticsLevel, configured_level: DiagnosticsLevel) -> bool {
-    message_level <= configured_level
+    configured_level != DiagnosticsLevel::Off && message_level <= configured_level
 }
 
 fn level_name(level: DiagnosticsLevel) -> &'static str {
     match level {
+        DiagnosticsLevel::Off => "OFF",
         DiagnosticsLevel::Error => "ERROR",
         DiagnosticsLevel::Warn => "WARN",
         DiagnosticsLevel::Info => "INFO",
@@ -143,3 +296,50 @@ fn rotate(log_path: &Path, max_files: u32) -> std::io::Result<()> {
 fn rotated_path(log_path: &Path, idx: u32) -> PathBuf {
     PathBuf::from(format!("{}.{}", log_path.to_string_lossy(), idx))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_format_line_has_fields() {
+        let line = format_line(
+            LogFormat::Json,
+            &Timestamp::Epoch(1234567890),
+            DiagnosticsLevel::Warn,
+            "a \"quoted\" msg",
+        );
+        assert!(line.ends_with('\n'));
+
+        let value: serde_json::Value = serde_json::from_str(line.trim_end()).unwrap();
+        assert_eq!(value["ts"], 1234567890);
+        assert_eq!(value["level"], "warn");
+        assert_eq!(value["msg"], "a \"quoted\" msg");
+    }
+
+    #[test]
+    fn recent_lines_keep_only_the_newest() {
+        let mut recent = VecDeque::new();
+        for i in 0..RECENT_LINES_CAPACITY + 5 {
+            push_recent(&mut recent, &format!("line {}\n", i));
+        }
+        assert_eq!(recent.len(), RECENT_LINES_CAPACITY);
+        assert_eq!(recent.front().map(String::as_str), Some("line 5"));
+        assert_eq!(recent.back().map(String::as_str), Some("line 104"));
+    }
+
+    #[test]
+    fn off_level_suppresses_everything() {
+        assert!(!enabled(DiagnosticsLevel::Error, DiagnosticsLevel::Off));
+        assert!(!enabled(DiagnosticsLevel::Off, DiagnosticsLevel::Off));
+        assert!(enabled(DiagnosticsLevel::Error, DiagnosticsLevel::Warn));
+        assert!(!enabled(DiagnosticsLevel::Debug, DiagnosticsLevel::Info));
+    }
+
+    #[test]
+    fn local_iso_timestamp_in_text_line() {
+        let ts = Timestamp::LocalIso(format_iso(2024, 5, 1, 13, 45, 2));
+        let line = format_line(LogFormat::Text, &ts, DiagnosticsLevel::Info, "started");
+        assert_eq!(line, "[2024-05-01T13:45:02][INFO] started\n");
+    }
+}
@@ -0,0 +1,191 @@
+use std::fs;
+use std::io;
+use std::mem;
+use std::path::Path;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use windows::Win32::UI::Input::KeyboardAndMouse::*;
+
+use ystrokey_core::{DiagnosticsLevel, KeyAction, KeyCode, KeyEvent};
+
+use crate::logger;
+
+/// 記録したキー操作の1ステップ
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MacroStep {
+    /// 記録開始からの経過時間 (ms)
+    pub offset_ms: u64,
+    pub vk: u16,
+    pub scan_code: u16,
+    pub key_up: bool,
+    /// 拡張キー（テンキーの Enter など、VK だけでは区別できないもの）
+    #[serde(default)]
+    pub extended: bool,
+}
+
+/// `macros.json` の内容
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct MacroFile {
+    steps: Vec<MacroStep>,
+}
+
+/// `DisplayState` が記録したキーイベントを保存用のステップに変換
+pub fn steps_from_recording(events: &[(Duration, KeyEvent)]) -> Vec<MacroStep> {
+    events
+        .iter()
+        .map(|(offset, ke)| MacroStep {
+            offset_ms: offset.as_millis() as u64,
+            // テンキー区別用の上位ビットを落として VK に戻す
+            vk: (ke.key.0 & 0xFF) as u16,
+            scan_code: ke.scan_code as u16,
+            key_up: ke.action == KeyAction::Up,
+            extended: ke.key == KeyCode::NUMPAD_ENTER,
+        })
+        .collect()
+}
+
+/// 保存済みマクロを読み込む（ファイルが無ければ空）
+pub fn load(path: &Path) -> io::Result<Vec<MacroStep>> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let file: MacroFile = serde_json::from_str(&text)?;
+    Ok(file.steps)
+}
+
+pub fn save(path: &Path, steps: &[MacroStep]) -> io::Result<()> {
+    let file = MacroFile {
+        steps: steps.to_vec(),
+    };
+    let text = serde_json::to_string_pretty(&file)?;
+    fs::write(path, text)
+}
+
+/// 別スレッドで記録時の間隔を再現しながら SendInput で再生する
+///
+/// スレッドを起動できなければログに残して None を返す。
+pub fn play(steps: Vec<MacroStep>) -> Option<JoinHandle<()>> {
+    let spawned = std::thread::Builder::new()
+        .name("macro-player".into())
+        .spawn(move || {
+            let mut elapsed_ms = 0;
+            for step in steps {
+                std::thread::sleep(Duration::from_millis(step.offset_ms.saturating_sub(elapsed_ms)));
+                elapsed_ms = elapsed_ms.max(step.offset_ms);
+                send_key(&step);
+            }
+        });
+    match spawned {
+        Ok(handle) => Some(handle),
+        Err(e) => {
+            logger::log(
+                DiagnosticsLevel::Warn,
+                &format!("macro player thread spawn failed: {e}"),
+            );
+            None
+        }
+    }
+}
+
+/// SendInput で KEYEVENTF_EXTENDEDKEY が要るキー（付けないとテンキー側・左側のキーとして届く）
+fn is_extended_vk(vk: u16) -> bool {
+    matches!(
+        VIRTUAL_KEY(vk),
+        VK_LEFT
+            | VK_UP
+            | VK_RIGHT
+            | VK_DOWN
+            | VK_INSERT
+            | VK_DELETE
+            | VK_HOME
+            | VK_END
+            | VK_PRIOR
+            | VK_NEXT
+            | VK_RCONTROL
+            | VK_RMENU
+            | VK_LWIN
+            | VK_RWIN
+            | VK_APPS
+            | VK_DIVIDE
+            | VK_NUMLOCK
+            | VK_SNAPSHOT
+            | VK_CANCEL
+    )
+}
+
+fn send_key(step: &MacroStep) {
+    let mut flags = if step.key_up {
+        KEYEVENTF_KEYUP
+    } else {
+        KEYBD_EVENT_FLAGS(0)
+    };
+    if step.extended || is_extended_vk(step.vk) {
+        flags |= KEYEVENTF_EXTENDEDKEY;
+    }
+    let input = INPUT {
+        r#type: INPUT_KEYBOARD,
+        Anonymous: INPUT_0 {
+            ki: KEYBDINPUT {
+                wVk: VIRTUAL_KEY(step.vk),
+                wScan: step.scan_code,
+                dwFlags: flags,
+                time: 0,
+                dwExtraInfo: 0,
+            },
+        },
+    };
+    unsafe {
+        SendInput(&[input], mem::size_of::<INPUT>() as i32);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+    use ystrokey_core::{KeyCode, Modifiers};
+
+    #[test]
+    fn recording_converts_to_steps_and_roundtrips() {
+        let event = |key, action| KeyEvent {
+            key,
+            action,
+            modifiers: Modifiers::default(),
+            is_numpad: false,
+            scan_code: 0x1C,
+            text: None,
+            timestamp: Instant::now(),
+        };
+        let steps = steps_from_recording(&[
+            (Duration::from_millis(0), event(KeyCode::NUMPAD_ENTER, KeyAction::Down)),
+            (Duration::from_millis(80), event(KeyCode::NUMPAD_ENTER, KeyAction::Up)),
+        ]);
+        assert_eq!(steps[0].vk, 0x0D);
+        assert!(steps[0].extended);
+        assert_eq!(steps[1].offset_ms, 80);
+        assert!(steps[1].key_up);
+
+        let path = std::env::temp_dir().join(format!("ystrokey-macros-{}.json", std::process::id()));
+        save(&path, &steps).unwrap();
+        assert_eq!(load(&path).unwrap(), steps);
+        let _ = fs::remove_file(&path);
+        assert!(load(&path).unwrap().is_empty());
+    }
+
+    #[test]
+    fn extended_keys_are_flagged_for_send_input() {
+        assert!(is_extended_vk(VK_LEFT.0));
+        assert!(is_extended_vk(VK_RCONTROL.0));
+        assert!(!is_extended_vk(VK_LCONTROL.0));
+        assert!(!is_extended_vk(VK_RETURN.0));
+        // 拡張フラグのない古い macros.json も読める
+        let file: MacroFile =
+            serde_json::from_str(r#"{"steps":[{"offset_ms":0,"vk":13,"scan_code":28,"key_up":false}]}"#)
+                .unwrap();
+        assert!(!file.steps[0].extended);
+    }
+}
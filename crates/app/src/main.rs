@@ -1,36 +1,69 @@
 mod autostart;
+mod build_info;
+mod cli;
+mod doctor;
+mod event_json;
+mod event_recorder;
+mod hotkey_recorder;
+mod http_server;
+mod ipc;
 mod logger;
+mod macros;
+mod relay;
 mod settings_io;
 mod settings_window;
 mod tray;
+mod watcher;
+mod ws_server;
 
 use std::cell::RefCell;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::mpsc::{self, SyncSender};
+use std::sync::mpsc::SyncSender;
 use std::sync::{Mutex, OnceLock};
+use std::thread::JoinHandle;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-use windows::core::HSTRING;
+use windows::core::{w, HSTRING};
 use windows::Win32::Foundation::*;
 use windows::Win32::Graphics::Gdi::*;
+use windows::Win32::System::SystemInformation::GetLocalTime;
+use windows::Win32::System::Threading::CreateMutexW;
 use windows::Win32::UI::HiDpi::*;
+use windows::Win32::UI::Shell::ShellExecuteW;
 use windows::Win32::UI::Input::KeyboardAndMouse::*;
 use windows::Win32::UI::WindowsAndMessaging::*;
 
 use ystrokey_core::{
     AppConfig, ClipboardContent, ClipboardEvent, ConfigError, DiagnosticsLevel, DisplayState,
-    GhostModifier, InputEvent, MenuLanguage,
+    FollowMode, GhostModifier, HotkeyConfig, HotkeyMode, InputEvent, MenuLanguage, MouseInputMode, RenderError,
+    MAX_EXTRA_TOGGLES, replay_events,
+};
+use ystrokey_input::{
+    get_foreground_process_name, get_lock_state_event, install_gamepad_poller, install_keyboard_hook, install_mouse_hook,
+    is_privacy_target, poll_ime_state, read_raw_mouse_events, register_raw_mouse,
+    take_dropped_event_count, take_foreground_changed, ClipboardListener, ForegroundHook,
+};
+use ystrokey_render::{
+    cursor_monitor, get_monitor_device_name, save_png, snap_rect_to_work_area, work_area_fraction,
+    MultiOsdManager, OsdWindow,
 };
-use ystrokey_input::{install_keyboard_hook, is_privacy_target, poll_ime_state, ClipboardListener};
-use ystrokey_render::{get_monitor_device_name, D2DRenderer, OsdWindow};
 
 use tray::{
     show_context_menu, ID_TRAY_AUTOSTART, ID_TRAY_EXIT, ID_TRAY_EXPORT, ID_TRAY_IMPORT,
-    ID_TRAY_SETTINGS, ID_TRAY_TOGGLE, WM_TRAYICON,
+    ID_TRAY_MACRO_PLAY, ID_TRAY_MACRO_RECORD, ID_TRAY_RESET_POSITION, ID_TRAY_SETTINGS,
+    ID_TRAY_TOGGLE, ID_TRAY_VIEWLOG, WM_TRAYICON,
 };
 
 const HOTKEY_TOGGLE_ID: i32 = 1;
+const HOTKEY_PANIC_ID: i32 = 2;
+const HOTKEY_SCREENSHOT_ID: i32 = 3;
+const HOTKEY_PRIVACY_ID: i32 = 4;
+/// `hotkey.extra_toggles` の登録ID（先頭から順に割り当て）
+const HOTKEY_EXTRA_TOGGLE_BASE_ID: i32 = 100;
+
+/// 表示が無いときの待機の上限（入力が来れば即座に起きる）
+const IDLE_WAIT_FALLBACK: Duration = Duration::from_secs(5);
 
 /// wnd_proc からイベント送信用のグローバルチャネル
 static EVENT_TX: OnceLock<SyncSender<InputEvent>> = OnceLock::new();
@@ -38,6 +71,15 @@ static EVENT_TX: OnceLock<SyncSender<InputEvent>> = OnceLock::new();
 /// OSD 有効/無効（トレイメニューから切替）
 static OSD_ENABLED: AtomicBool = AtomicBool::new(true);
 
+/// パニックホットキーによる一時非表示（再度押すまで OSD を抑止）
+static PANIC_HIDDEN: AtomicBool = AtomicBool::new(false);
+
+/// プライバシーホットキーで強制プライバシーモードにした時刻（`privacy_override_timeout_ms` で自動解除）
+static PRIVACY_OVERRIDE: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// マクロ記録中か（トレイメニューの表示切替用）
+static MACRO_RECORDING: AtomicBool = AtomicBool::new(false);
+
 /// 設定ファイルパス（wnd_proc からアクセス用）
 static CONFIG_PATH: OnceLock<PathBuf> = OnceLock::new();
 
@@ -49,12 +91,15 @@ static GHOST_INTERACTIVE: AtomicBool = AtomicBool::new(false);
 
 // クリップボード重複検知用（wnd_proc はメインスレッドのみで呼ばれる）
 thread_local! {
-    static LAST_CLIPBOARD: RefCell<String> = const { RefCell::new(String::new()) };
+    static LAST_CLIPBOARD: RefCell<Option<ClipboardContent>> = const { RefCell::new(None) };
 }
 
 /// WM_CLIPBOARDUPDATE (Windows Vista+)
 const WM_CLIPBOARD_UPDATE: u32 = 0x031D;
 
+/// 多重起動時に既存インスタンスへ送る通知
+const WM_SECOND_INSTANCE: u32 = WM_USER + 100;
+
 enum ApplyReason {
     Startup,
     HotReload,
@@ -63,13 +108,67 @@ enum ApplyReason {
 
 struct RuntimeIntervals {
     frame_duration: Duration,
+    fade_frame_duration: Duration,
     ime_poll_interval: Duration,
     config_reload_interval: Duration,
 }
 
+/// 描画時間と入力イベント破棄数の集計（`frame_interval_ms` 調整用に Debug ログへ出す）
+struct PerfStats {
+    frames: u32,
+    total_render: Duration,
+    max_render: Duration,
+    last_report: Instant,
+}
+
+impl PerfStats {
+    /// 集計結果をログに出す間隔
+    const REPORT_INTERVAL: Duration = Duration::from_secs(5);
+
+    fn new(now: Instant) -> Self {
+        Self {
+            frames: 0,
+            total_render: Duration::ZERO,
+            max_render: Duration::ZERO,
+            last_report: now,
+        }
+    }
+
+    fn record_frame(&mut self, render_time: Duration) {
+        self.frames += 1;
+        self.total_render += render_time;
+        self.max_render = self.max_render.max(render_time);
+    }
+
+    fn maybe_report(&mut self, now: Instant) {
+        if now.duration_since(self.last_report) < Self::REPORT_INTERVAL {
+            return;
+        }
+        let dropped = take_dropped_event_count();
+        if self.frames > 0 || dropped > 0 {
+            let avg_ms = if self.frames > 0 {
+                self.total_render.as_secs_f64() * 1000.0 / self.frames as f64
+            } else {
+                0.0
+            };
+            logger::log(
+                DiagnosticsLevel::Debug,
+                &format!(
+                    "perf: frames={} render_avg={:.2}ms render_max={:.2}ms dropped_events={}",
+                    self.frames,
+                    avg_ms,
+                    self.max_render.as_secs_f64() * 1000.0,
+                    dropped
+                ),
+            );
+        }
+        *self = Self::new(now);
+    }
+}
+
 /// 致命的エラー時にメッセージボックスを表示して終了
 fn fatal_error(msg: &str) -> ! {
-    logger::log(DiagnosticsLevel::Error, msg);
+    logger::log_fatal(msg);
     unsafe {
         let text = HSTRING::from(msg);
         let caption = HSTRING::from("yStrokey Error");
@@ -94,6 +193,7 @@ unsafe extern "system" fn app_wnd_proc(
                     menu_lang,
                     osd_enabled,
                     autostart::is_autostart_enabled(),
+                    MACRO_RECORDING.load(Ordering::Relaxed),
                 );
             }
             LRESULT(0)
@@ -134,6 +234,19 @@ unsafe extern "system" fn app_wnd_proc(
                         }
                     }
                 }
+                ID_TRAY_VIEWLOG => open_log_file(),
+                ID_TRAY_RESET_POSITION => reset_saved_positions(),
+                ID_TRAY_MACRO_RECORD => {
+                    if let Some(tx) = EVENT_TX.get() {
+                        let start = !MACRO_RECORDING.load(Ordering::Relaxed);
+                        let _ = tx.try_send(InputEvent::MacroRecord { start });
+                    }
+                }
+                ID_TRAY_MACRO_PLAY => {
+                    if let Some(tx) = EVENT_TX.get() {
+                        let _ = tx.try_send(InputEvent::MacroPlay);
+                    }
+                }
                 ID_TRAY_EXPORT => {
                     if let Some(cfg_mutex) = CURRENT_CONFIG.get() {
                         let cfg_clone = cfg_mutex.lock().ok().map(|c| c.clone());
@@ -188,17 +301,17 @@ unsafe extern "system" fn app_wnd_proc(
         }
         WM_CLIPBOARD_UPDATE => {
             if let Some(tx) = EVENT_TX.get() {
-                if let Some(text) = ClipboardListener::get_text(hwnd) {
+                if let Some(content) = ClipboardListener::get_content(hwnd) {
                     let changed = LAST_CLIPBOARD.with(|cell| {
                         let prev = cell.borrow();
-                        text != *prev
+                        prev.as_ref() != Some(&content)
                     });
                     if changed {
                         LAST_CLIPBOARD.with(|cell| {
-                            *cell.borrow_mut() = text.clone();
+                            *cell.borrow_mut() = Some(content.clone());
                         });
                         let event = InputEvent::Clipboard(ClipboardEvent {
-                            content: ClipboardContent::Text(text),
+                            content,
                             timestamp: Instant::now(),
                         });
                         let _ = tx.try_send(event);
@@ -214,14 +327,57 @@ unsafe extern "system" fn app_wnd_proc(
             }
             LRESULT(0)
         }
+        WM_MOVING => {
+            // ドラッグ中の矩形を書き換えて作業領域の端へ吸着させる
+            let rect = &mut *(lparam.0 as *mut RECT);
+            snap_rect_to_work_area(rect, current_snap_to_edge_px());
+            LRESULT(1)
+        }
         WM_EXITSIZEMOVE => {
             save_current_position(hwnd);
             LRESULT(0)
         }
         WM_HOTKEY => {
-            if wparam.0 as i32 == HOTKEY_TOGGLE_ID {
+            if is_toggle_hotkey_id(wparam.0 as i32) {
                 let prev = OSD_ENABLED.load(Ordering::Relaxed);
                 OSD_ENABLED.store(!prev, Ordering::Relaxed);
+            } else if wparam.0 as i32 == HOTKEY_PANIC_ID {
+                let hidden = !PANIC_HIDDEN.load(Ordering::Relaxed);
+                PANIC_HIDDEN.store(hidden, Ordering::Relaxed);
+                if hidden {
+                    if let Some(tx) = EVENT_TX.get() {
+                        let _ = tx.try_send(InputEvent::ClearDisplay);
+                    }
+                }
+            } else if wparam.0 as i32 == HOTKEY_PRIVACY_ID {
+                let forced = match PRIVACY_OVERRIDE.lock() {
+                    Ok(mut since) => {
+                        *since = since.is_none().then(Instant::now);
+                        since.is_some()
+                    }
+                    Err(_) => false,
+                };
+                if forced {
+                    if let Some(tx) = EVENT_TX.get() {
+                        let _ = tx.try_send(InputEvent::ClearDisplay);
+                    }
+                }
+            } else if wparam.0 as i32 == HOTKEY_SCREENSHOT_ID {
+                // 描画はメインループが持つレンダラーで行う
+                if let Some(tx) = EVENT_TX.get() {
+                    let _ = tx.try_send(InputEvent::Screenshot);
+                }
+            }
+            LRESULT(0)
+        }
+        WM_SECOND_INSTANCE => {
+            // 二重起動されたら OSD を有効にして設定画面を前面に出す
+            OSD_ENABLED.store(true, Ordering::Relaxed);
+            if let (Some(path), Some(cfg_mutex)) = (CONFIG_PATH.get(), CURRENT_CONFIG.get()) {
+                if let Ok(cfg) = cfg_mutex.lock() {
+                    let notify_tx = EVENT_TX.get().cloned();
+                    settings_window::open_settings_window(&cfg, path, notify_tx);
+                }
             }
             LRESULT(0)
         }
@@ -239,6 +395,21 @@ unsafe extern "system" fn app_wnd_proc(
             }
             LRESULT(0)
         }
+        WM_DISPLAYCHANGE => {
+            if let Some(tx) = EVENT_TX.get() {
+                let _ = tx.try_send(InputEvent::DisplayChanged);
+            }
+            LRESULT(0)
+        }
+        WM_INPUT => {
+            if let Some(tx) = EVENT_TX.get() {
+                for event in read_raw_mouse_events(lparam) {
+                    let _ = tx.try_send(InputEvent::Mouse(event));
+                }
+            }
+            // WM_INPUT は DefWindowProcW でバッファを解放させる
+            DefWindowProcW(hwnd, msg, wparam, lparam)
+        }
         WM_DESTROY => {
             PostQuitMessage(0);
             LRESULT(0)
@@ -265,6 +436,64 @@ fn should_confirm_exit() -> bool {
         .unwrap_or(false)
 }
 
+/// 多重起動を防止する名前付きミューテックスを取得
+///
+/// 既に起動中なら既存インスタンスへ通知して `None` を返す。
+/// ミューテックス作成自体に失敗した場合は起動を妨げない（無効ハンドルを返す）。
+fn acquire_single_instance() -> Option<HANDLE> {
+    unsafe {
+        let handle = CreateMutexW(None, true, w!("Global\\yStrokeyInstance")).unwrap_or_default();
+        if GetLastError() == ERROR_ALREADY_EXISTS {
+            if let Ok(hwnd) = FindWindowW(w!("yStrokeyOSD"), None) {
+                let _ = PostMessageW(hwnd, WM_SECOND_INSTANCE, WPARAM(0), LPARAM(0));
+            }
+            if !handle.is_invalid() {
+                let _ = CloseHandle(handle);
+            }
+            return None;
+        }
+        Some(handle)
+    }
+}
+
+/// ログファイルを既定のアプリで開く（未作成ならファイルログ無効の旨を表示）
+fn open_log_file() {
+    let Some(path) = logger::log_path().filter(|p| p.exists()) else {
+        let text = match current_tray_status().0 {
+            MenuLanguage::Ja => "ログファイルがありません。ファイルログが無効になっています。",
+            MenuLanguage::En => "No log file found. File logging is disabled.",
+            MenuLanguage::Ko => "로그 파일이 없습니다. 파일 로그가 비활성화되어 있습니다.",
+        };
+        unsafe {
+            MessageBoxW(
+                None,
+                &HSTRING::from(text),
+                &HSTRING::from("yStrokey"),
+                MB_ICONINFORMATION | MB_OK,
+            );
+        }
+        return;
+    };
+
+    let result = unsafe {
+        ShellExecuteW(
+            None,
+            w!("open"),
+            &HSTRING::from(path.as_os_str()),
+            None,
+            None,
+            SW_SHOWNORMAL,
+        )
+    };
+    // 戻り値が 32 以下なら失敗
+    if result.0 as isize <= 32 {
+        logger::log(
+            DiagnosticsLevel::Warn,
+            &format!("Failed to open log file: {}", path.display()),
+        );
+    }
+}
+
 fn exit_confirm_text() -> &'static str {
     let lang = CURRENT_CONFIG
         .get()
@@ -275,6 +504,7 @@ fn exit_confirm_text() -> &'static str {
     match lang {
         MenuLanguage::Ja => "yStrokey を終了しますか？",
         MenuLanguage::En => "Exit yStrokey?",
+        MenuLanguage::Ko => "yStrokey를 종료하시겠습니까?",
     }
 }
 
@@ -283,10 +513,53 @@ fn main() {
         let _ = SetProcessDpiAwarenessContext(DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2);
     }
 
-    let config_path = std::env::current_exe()
-        .ok()
-        .and_then(|p| p.parent().map(|d| d.join("config.json")))
-        .unwrap_or_else(|| PathBuf::from("config.json"));
+    let args = cli::parse_args();
+    if args.help {
+        println!("{}", cli::USAGE);
+        return;
+    }
+    if args.version {
+        println!("{}", build_info::version_string());
+        return;
+    }
+    if args.print_schema {
+        let schema = AppConfig::json_schema();
+        println!("{}", serde_json::to_string_pretty(&schema).unwrap_or_default());
+        return;
+    }
+    // 起動中のインスタンスへ切り替えを依頼して終了（多重起動チェックより前）
+    if args.toggle {
+        match ipc::send_command("toggle") {
+            Ok(response) => println!("{response}"),
+            Err(e) => {
+                eprintln!("yStrokey: failed to reach running instance: {e}");
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let config_path = args.config_path();
+    if args.profile.is_some() {
+        // 初回のプロファイル指定時は profiles ディレクトリが無いので作成
+        if let Some(dir) = config_path.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+    }
+
+    if args.doctor {
+        let passed = doctor::run(&config_path);
+        std::process::exit(if passed { 0 } else { 1 });
+    }
+    if args.reset_config {
+        reset_config(&config_path);
+        return;
+    }
+
+    // ハンドルはプロセス終了まで保持（終了時に OS が解放）
+    let Some(_instance_mutex) = acquire_single_instance() else {
+        return;
+    };
 
     let base_dir = config_path
         .parent()
@@ -299,29 +572,31 @@ fn main() {
     let mut preview_draft_config: Option<AppConfig> = None;
 
     logger::init(&base_dir, &saved_config.diagnostics);
-    logger::log(DiagnosticsLevel::Info, "Application startup");
+    logger::install_panic_hook();
+    event_recorder::init(&base_dir, &saved_config.diagnostics, args.replay.is_some());
+    logger::log(
+        DiagnosticsLevel::Info,
+        &format!(
+            "Application startup: {} built {}",
+            build_info::version_string(),
+            build_info::BUILD_DATE
+        ),
+    );
 
     let _ = CONFIG_PATH.set(config_path.clone());
     let _ = CURRENT_CONFIG.set(Mutex::new(saved_config.clone()));
 
-    let mut window = OsdWindow::create(
-        saved_config.performance.osd_width,
-        saved_config.performance.osd_height,
-        &saved_config.display,
-    )
-    .unwrap_or_else(|e| fatal_error(&format!("OSD window creation failed: {e}")));
-
-    unsafe {
-        SetWindowLongPtrW(window.hwnd(), GWL_WNDPROC, app_wnd_proc as usize as isize);
+    let mut osd = MultiOsdManager::new(&saved_config)
+        .unwrap_or_else(|e| fatal_error(&format!("OSD window creation failed: {e}")));
+    osd.set_wnd_proc(app_wnd_proc);
+    if let Err(e) = osd.refresh_monitors(&saved_config) {
+        logger::log(DiagnosticsLevel::Warn, &format!("per-monitor OSD creation failed: {e}"));
     }
 
-    let mut renderer = D2DRenderer::new(&saved_config.style)
-        .unwrap_or_else(|e| fatal_error(&format!("D2D renderer creation failed: {e}")));
-    renderer.update_dpi(window.dpi);
-
     let mut state = DisplayState::new(&saved_config);
     let mut intervals = RuntimeIntervals {
         frame_duration: Duration::from_millis(saved_config.performance.frame_interval_ms),
+        fade_frame_duration: Duration::from_millis(saved_config.performance.fade_frame_interval_ms),
         ime_poll_interval: Duration::from_millis(saved_config.performance.ime_poll_interval_ms),
         config_reload_interval: Duration::from_millis(saved_config.performance.config_reload_interval_ms),
     };
@@ -330,17 +605,44 @@ fn main() {
         ApplyReason::Startup,
         &saved_config,
         &mut state,
-        &mut renderer,
-        &mut window,
+        &mut osd,
         &mut intervals,
     );
 
-    let (tx, rx) = mpsc::sync_channel::<InputEvent>(256);
+    let (tx, rx, event_relay) = relay::EventRelay::start(256)
+        .unwrap_or_else(|e| fatal_error(&format!("event relay creation failed: {e}")));
     let _ = EVENT_TX.set(tx.clone());
+    // 常時表示するロック状態は、最初のキー操作を待たず起動時点の状態から出す
+    if saved_config.behavior.persist_lock_indicator {
+        let lock_state = get_lock_state_event();
+        if lock_state.caps_lock || lock_state.num_lock {
+            let _ = tx.try_send(InputEvent::LockState(lock_state));
+        }
+    }
 
     let _hook_thread = install_keyboard_hook(tx.clone());
 
-    let _clipboard_listener = match ClipboardListener::new(window.hwnd()) {
+    if let Some(path) = args.replay.clone() {
+        let replay_tx = tx.clone();
+        std::thread::spawn(move || match replay_events(&path, replay_tx) {
+            Ok(count) => logger::log(DiagnosticsLevel::Info, &format!("Replayed {count} events")),
+            Err(e) => logger::log(
+                DiagnosticsLevel::Warn,
+                &format!("Replay failed ({}): {e}", path.display()),
+            ),
+        });
+    }
+
+    let mouse_input_mode = saved_config.behavior.mouse_input_mode;
+    let _mouse_hook_thread = matches!(mouse_input_mode, MouseInputMode::Hook | MouseInputMode::Both)
+        .then(|| install_mouse_hook(tx.clone()));
+    if matches!(mouse_input_mode, MouseInputMode::RawInput | MouseInputMode::Both) {
+        if let Err(e) = register_raw_mouse(osd.host().hwnd()) {
+            logger::log(DiagnosticsLevel::Warn, &format!("raw input registration failed: {e}"));
+        }
+    }
+
+    let _clipboard_listener = match ClipboardListener::new(osd.host().hwnd()) {
         Ok(listener) => Some(listener),
         Err(e) => {
             logger::log(DiagnosticsLevel::Warn, &format!("clipboard listener failed: {e}"));
@@ -348,15 +650,85 @@ fn main() {
         }
     };
 
-    let _tray = tray::TrayIcon::new(window.hwnd())
+    let _ipc_server = match ipc::IpcServer::start(tx.clone(), &OSD_ENABLED) {
+        Ok(server) => Some(server),
+        Err(e) => {
+            logger::log(DiagnosticsLevel::Warn, &format!("IPC server failed: {e}"));
+            None
+        }
+    };
+
+    let http_server = saved_config.performance.http_server_port.and_then(|port| {
+        match http_server::HttpServer::start(port, saved_config.performance.http_allowed_origin.clone()) {
+            Ok(server) => {
+                logger::log(DiagnosticsLevel::Info, &format!("HTTP server listening on port {port}"));
+                Some(server)
+            }
+            Err(e) => {
+                logger::log(DiagnosticsLevel::Warn, &format!("HTTP server failed: {e}"));
+                None
+            }
+        }
+    });
+
+    let ws_server = saved_config.performance.websocket_port.and_then(|port| {
+        match ws_server::WsServer::start(port, saved_config.performance.http_allowed_origin.clone()) {
+            Ok(server) => {
+                logger::log(DiagnosticsLevel::Info, &format!("WebSocket server listening on port {port}"));
+                Some(server)
+            }
+            Err(e) => {
+                logger::log(DiagnosticsLevel::Warn, &format!("WebSocket server failed: {e}"));
+                None
+            }
+        }
+    });
+
+    // ディレクトリ監視が使えない環境ではポーリングにフォールバック
+    let mut dir_watcher = match watcher::DirWatcher::start(
+        &config_path,
+        saved_config.performance.config_reload_interval_ms,
+        tx.clone(),
+    ) {
+        Ok(w) => Some(w),
+        Err(e) => {
+            logger::log(
+                DiagnosticsLevel::Warn,
+                &format!("config watcher failed, falling back to polling: {e}"),
+            );
+            None
+        }
+    };
+
+    // フォーカス変更を即時検知（失敗時は IME ポーリング側の判定のみ）
+    let foreground_hook = ForegroundHook::install();
+    if foreground_hook.is_none() {
+        logger::log(
+            DiagnosticsLevel::Warn,
+            "foreground WinEvent hook failed; privacy check falls back to polling",
+        );
+    }
+
+    let tray = tray::TrayIcon::new(osd.host().hwnd())
         .unwrap_or_else(|e| fatal_error(&format!("Tray icon creation failed: {e}")));
 
     let mut msg = MSG::default();
     let mut last_ime_poll = Instant::now();
     let mut last_config_check = Instant::now();
     let mut privacy_active = false;
+    let mut hold_to_show_active = false;
+    // 有効化されるまで XInput のポーリングは開始しない
+    let mut gamepad_poller = None;
+    let macros_path = config_path.with_file_name("macros.json");
+    let mut macro_steps = macros::load(&macros_path).unwrap_or_else(|e| {
+        logger::log(DiagnosticsLevel::Warn, &format!("macros load failed: {e}"));
+        Vec::new()
+    });
+    let mut macro_player: Option<JoinHandle<()>> = None;
     let mut was_rendering = false;
     let mut last_foreground_hwnd = HWND::default();
+    let mut last_cursor_monitor = HMONITOR::default();
+    let mut perf_stats = PerfStats::new(Instant::now());
 
     loop {
         unsafe {
@@ -369,8 +741,83 @@ fn main() {
             }
         }
 
-        let enabled = OSD_ENABLED.load(Ordering::Relaxed);
-        while let Ok(event) = rx.try_recv() {
+        // 新しいフォアグラウンドへのキー入力を処理する前にプライバシー判定を更新
+        if foreground_hook.is_some() && take_foreground_changed() {
+            let prev_privacy = privacy_active;
+            privacy_active = is_privacy_target(&saved_config.privacy);
+            if privacy_active && !prev_privacy {
+                state.clear();
+            }
+        }
+
+        // HoldToShow: コードが押されている間だけ表示
+        match is_hold_chord_down(&effective_config.hotkey) {
+            Some(down) => {
+                OSD_ENABLED.store(down, Ordering::Relaxed);
+                hold_to_show_active = true;
+            }
+            None if hold_to_show_active => {
+                OSD_ENABLED.store(true, Ordering::Relaxed);
+                hold_to_show_active = false;
+            }
+            None => {}
+        }
+
+        // プライバシーホットキーによる強制は一定時間で解除し、通常の判定に戻す
+        let privacy_forced = match PRIVACY_OVERRIDE.lock() {
+            Ok(mut since) => {
+                let timeout_ms = effective_config.behavior.privacy_override_timeout_ms;
+                if since.is_some_and(|at| {
+                    timeout_ms > 0 && at.elapsed() >= Duration::from_millis(timeout_ms)
+                }) {
+                    *since = None;
+                }
+                since.is_some()
+            }
+            Err(_) => false,
+        };
+
+        let enabled =
+            OSD_ENABLED.load(Ordering::Relaxed) && !PANIC_HIDDEN.load(Ordering::Relaxed);
+        // 設定で無効にされたら（ホットリロード・設定画面とも）ポーリングスレッドを止める
+        match (effective_config.behavior.show_gamepad, gamepad_poller.is_some()) {
+            (true, false) => {
+                gamepad_poller = Some(install_gamepad_poller(tx.clone(), intervals.frame_duration));
+            }
+            (false, true) => gamepad_poller = None,
+            _ => {}
+        }
+
+        // アプリ別キーフィルタ用のプロセス名はフレーム内で1回だけ取得
+        if !effective_config.behavior.app_key_filters.is_empty() {
+            state.set_foreground_app(get_foreground_process_name());
+        }
+        // フレーム内に届いたイベントをまとめて処理し、制御イベントだけを後から扱う
+        let batch: Vec<InputEvent> = rx
+            .try_iter()
+            .filter(|event| {
+                event_recorder::record(event);
+                if event.is_control() {
+                    return true;
+                }
+                if !enabled || privacy_active || privacy_forced {
+                    return false;
+                }
+                if http_server.is_some() || ws_server.is_some() {
+                    // クリップボードは OSD と同じプライバシー設定を通してから流す
+                    if let Some(exported) = state.export_event(event) {
+                        if let Some(server) = &http_server {
+                            server.publish_event(&exported);
+                        }
+                        if let Some(server) = &ws_server {
+                            server.broadcast(&exported);
+                        }
+                    }
+                }
+                true
+            })
+            .collect();
+        for event in state.batch_process_events(batch) {
             match event {
                 InputEvent::DpiChanged { dpi, suggested_rect } => {
                     let rect = RECT {
@@ -379,20 +826,31 @@ fn main() {
                         right: suggested_rect[2],
                         bottom: suggested_rect[3],
                     };
-                    window.update_for_dpi(dpi, &rect);
-                    renderer.update_dpi(dpi);
+                    osd.update_for_dpi(dpi, &rect);
+                    continue;
+                }
+                InputEvent::DisplayChanged => {
+                    if let Err(e) = osd.refresh_monitors(&effective_config) {
+                        logger::log(
+                            DiagnosticsLevel::Warn,
+                            &format!("per-monitor OSD creation failed: {e}"),
+                        );
+                    }
+                    osd.reposition_follow(&effective_config.display);
                     continue;
                 }
                 InputEvent::ConfigChanged => {
                     if let Some(path) = CONFIG_PATH.get() {
-                        match AppConfig::load_strict(path) {
-                            Ok(new_config) => {
+                        // 設定画面の保存通知とディレクトリ監視の通知が重なるため、
+                        // 更新時刻が変わっていなければ読み直さない
+                        match saved_config.check_reload(path) {
+                            Ok(Some(new_config)) => {
+                                log_config_changes(&saved_config, &new_config);
                                 apply_config(
                                     ApplyReason::UiEdit,
                                     &new_config,
                                     &mut state,
-                                    &mut renderer,
-                                    &mut window,
+                                    &mut osd,
                                     &mut intervals,
                                 );
                                 if let Some(cfg_mutex) = CURRENT_CONFIG.get() {
@@ -409,8 +867,7 @@ fn main() {
                                         apply_visual_config(
                                             &effective_config,
                                             &mut state,
-                                            &mut renderer,
-                                            &mut window,
+                                            &mut osd,
                                         );
                                     } else {
                                         effective_config = saved_config.clone();
@@ -419,6 +876,7 @@ fn main() {
                                     effective_config = saved_config.clone();
                                 }
                             }
+                            Ok(None) => {}
                             Err(e) => logger::log(
                                 DiagnosticsLevel::Warn,
                                 &format!("ConfigChanged reload failed: {e}"),
@@ -427,6 +885,44 @@ fn main() {
                     }
                     continue;
                 }
+                InputEvent::ToggleOsd => {
+                    let prev = OSD_ENABLED.load(Ordering::Relaxed);
+                    OSD_ENABLED.store(!prev, Ordering::Relaxed);
+                    continue;
+                }
+                InputEvent::MacroRecord { start } => {
+                    MACRO_RECORDING.store(start, Ordering::Relaxed);
+                    if start {
+                        state.start_macro_recording(Instant::now());
+                    } else if state.is_recording_macro() {
+                        macro_steps = macros::steps_from_recording(&state.stop_macro_recording());
+                        if let Err(e) = macros::save(&macros_path, &macro_steps) {
+                            logger::log(DiagnosticsLevel::Warn, &format!("macros save failed: {e}"));
+                        }
+                    }
+                    continue;
+                }
+                InputEvent::Screenshot => {
+                    match save_screenshot(&osd, &state, &effective_config) {
+                        Ok(path) => tray.show_balloon("yStrokey", &path.display().to_string()),
+                        Err(e) => {
+                            logger::log(DiagnosticsLevel::Warn, &format!("screenshot failed: {e}"))
+                        }
+                    }
+                    continue;
+                }
+                InputEvent::StateSnapshot => {
+                    ipc::publish_snapshot(state.snapshot());
+                    continue;
+                }
+                InputEvent::MacroPlay => {
+                    // 再生中に再度押されても重ねて再生しない
+                    let playing = macro_player.as_ref().is_some_and(|h| !h.is_finished());
+                    if !playing && !state.is_recording_macro() && !macro_steps.is_empty() {
+                        macro_player = macros::play(macro_steps.clone());
+                    }
+                    continue;
+                }
                 InputEvent::PreviewMode { enabled } => {
                     preview_mode_active = enabled;
                     if enabled {
@@ -439,8 +935,7 @@ fn main() {
                             ApplyReason::UiEdit,
                             &saved_config,
                             &mut state,
-                            &mut renderer,
-                            &mut window,
+                            &mut osd,
                             &mut intervals,
                         );
                     }
@@ -454,50 +949,66 @@ fn main() {
                             apply_visual_config(
                                 &effective_config,
                                 &mut state,
-                                &mut renderer,
-                                &mut window,
+                                &mut osd,
                             );
                         }
                     }
                     continue;
                 }
-                other => {
-                    if enabled && !privacy_active {
-                        state.process_event(other);
-                    }
-                }
+                _ => {}
             }
         }
 
         let now = Instant::now();
         if now.duration_since(last_ime_poll) >= intervals.ime_poll_interval {
             let fg = unsafe { GetForegroundWindow() };
-            if fg != last_foreground_hwnd {
-                last_foreground_hwnd = fg;
+            let fg_changed = fg != last_foreground_hwnd;
+            // タイトルは同一ウィンドウ内でも変わる（タブ切替等）ため、指定時は毎回判定
+            if fg_changed || !saved_config.privacy.blocked_title_substrings.is_empty() {
                 let prev_privacy = privacy_active;
                 privacy_active = is_privacy_target(&saved_config.privacy);
                 if privacy_active && !prev_privacy {
                     state.clear();
                 }
-                if !fg.0.is_null() {
-                    window.reposition_to_monitor(fg, &effective_config.display);
+            }
+            if fg_changed {
+                last_foreground_hwnd = fg;
+                if !fg.0.is_null() && effective_config.display.follow == FollowMode::ForegroundWindow {
+                    osd.reposition_to_monitor(fg, &effective_config.display);
+                }
+            }
+            if effective_config.display.follow == FollowMode::MouseCursor {
+                if let Some(monitor) = cursor_monitor() {
+                    if monitor != last_cursor_monitor {
+                        last_cursor_monitor = monitor;
+                        osd.reposition_on_monitor(monitor, &effective_config.display);
+                    }
                 }
             }
-            if enabled && !privacy_active {
+            if enabled && !privacy_active && !privacy_forced {
                 poll_ime_state(&tx);
             }
             last_ime_poll = now;
         }
 
-        if now.duration_since(last_config_check) >= intervals.config_reload_interval {
+        if dir_watcher.as_ref().is_some_and(|w| !w.is_alive()) {
+            logger::log(
+                DiagnosticsLevel::Warn,
+                "config watcher stopped, falling back to polling",
+            );
+            dir_watcher = None;
+        }
+        if let Some(w) = &dir_watcher {
+            w.set_debounce_ms(saved_config.performance.config_reload_interval_ms);
+        } else if now.duration_since(last_config_check) >= intervals.config_reload_interval {
             match saved_config.check_reload(&config_path) {
                 Ok(Some(new_config)) => {
+                    log_config_changes(&saved_config, &new_config);
                     apply_config(
                         ApplyReason::HotReload,
                         &new_config,
                         &mut state,
-                        &mut renderer,
-                        &mut window,
+                        &mut osd,
                         &mut intervals,
                     );
                     if let Some(cfg_mutex) = CURRENT_CONFIG.get() {
@@ -513,8 +1024,7 @@ fn main() {
                             apply_visual_config(
                                 &effective_config,
                                 &mut state,
-                                &mut renderer,
-                                &mut window,
+                                &mut osd,
                             );
                         } else {
                             effective_config = saved_config.clone();
@@ -533,6 +1043,10 @@ fn main() {
         }
 
         state.tick(Instant::now());
+        perf_stats.maybe_report(Instant::now());
+        if let Some(server) = &http_server {
+            server.update_items(state.active_items());
+        }
 
         let has_items = !state.active_items().is_empty();
         let has_any = has_items || state.preview_active();
@@ -540,12 +1054,25 @@ fn main() {
         if has_any || was_rendering {
             let live_items = state.active_items();
             let preview_items = state.preview_items();
-            let ghost_opacity = calculate_ghost_opacity(&window, &effective_config);
+            let (window, renderer) = osd.active_mut();
+            let ghost_opacity = calculate_ghost_opacity(window, &effective_config);
             let interactive = ghost_opacity > 0.0 && is_cursor_in_rect(&window.get_rect());
             GHOST_INTERACTIVE.store(interactive, Ordering::Relaxed);
             window.set_interactive(interactive);
 
-            if let Err(e) = renderer.render(
+            if effective_config.display.auto_size {
+                let (width, height) = renderer.content_size(
+                    live_items,
+                    preview_items,
+                    &effective_config.style,
+                    effective_config.performance.osd_width as u32,
+                    effective_config.performance.osd_height as u32,
+                );
+                window.resize_in_place(width as i32, height as i32, &effective_config.display);
+            }
+
+            let render_start = Instant::now();
+            let render_result = renderer.render(
                 live_items,
                 preview_items,
                 &effective_config.style,
@@ -553,12 +1080,18 @@ fn main() {
                 window.width() as u32,
                 window.height() as u32,
                 ghost_opacity,
-            ) {
-                logger::log(DiagnosticsLevel::Warn, &format!("Render error: {e}"));
-                if let Ok(new_renderer) = D2DRenderer::new(&effective_config.style) {
-                    renderer = new_renderer;
-                    renderer.update_dpi(window.dpi);
+            );
+            perf_stats.record_frame(render_start.elapsed());
+            match render_result {
+                Ok(()) => {}
+                // デバイスロスト時だけレンダラーを作り直す。それ以外はこのフレームを捨てる
+                Err(RenderError::DeviceLost) => {
+                    logger::log(DiagnosticsLevel::Warn, "Render target lost; recreating resources");
+                    if let Err(e) = renderer.recreate(&effective_config.style) {
+                        logger::log(DiagnosticsLevel::Warn, &format!("Renderer recreate failed: {e}"));
+                    }
                 }
+                Err(e) => logger::log(DiagnosticsLevel::Warn, &format!("Render error: {e}")),
             }
             // While settings preview is active, keep the window slightly visible so the user
             // can recover even if they temporarily set opacity to 0.
@@ -570,10 +1103,41 @@ fn main() {
             window.present(present_opacity);
 
             was_rendering = has_any;
-            std::thread::sleep(intervals.frame_duration);
+            if state.is_only_fading() {
+                // フェードだけなら粗い間隔で描画し、新しい入力が届いたら即座に起きる
+                unsafe {
+                    MsgWaitForMultipleObjects(
+                        Some(&[event_relay.wake_handle()]),
+                        false,
+                        intervals.fade_frame_duration.as_millis() as u32,
+                        QS_ALLINPUT,
+                    );
+                }
+            } else {
+                std::thread::sleep(intervals.frame_duration);
+            }
         } else {
+            // 入力（中継イベント）かウィンドウメッセージが来るまで眠る。
+            // 設定ファイルのポーリングが必要な場合だけ、その間隔で起きる。
+            let mut idle_timeout = if dir_watcher.is_some() {
+                IDLE_WAIT_FALLBACK
+            } else {
+                intervals.config_reload_interval.min(IDLE_WAIT_FALLBACK)
+            };
+            // 同じウィンドウ内のタイトル変更やモニタをまたぐカーソル移動では起こされないので、
+            // どちらかを見ている間は IME ポーリング間隔で確認する
+            if !saved_config.privacy.blocked_title_substrings.is_empty()
+                || effective_config.display.follow == FollowMode::MouseCursor
+            {
+                idle_timeout = idle_timeout.min(intervals.ime_poll_interval);
+            }
             unsafe {
-                MsgWaitForMultipleObjects(None, false, 50, QS_ALLINPUT);
+                MsgWaitForMultipleObjects(
+                    Some(&[event_relay.wake_handle()]),
+                    false,
+                    idle_timeout.as_millis() as u32,
+                    QS_ALLINPUT,
+                );
             }
         }
     }
@@ -583,23 +1147,35 @@ fn apply_config(
     reason: ApplyReason,
     config: &AppConfig,
     state: &mut DisplayState,
-    renderer: &mut D2DRenderer,
-    window: &mut OsdWindow,
+    osd: &mut MultiOsdManager,
     intervals: &mut RuntimeIntervals,
 ) {
-    apply_visual_config(config, state, renderer, window);
+    apply_visual_config(config, state, osd);
 
     intervals.frame_duration = Duration::from_millis(config.performance.frame_interval_ms);
+    intervals.fade_frame_duration =
+        Duration::from_millis(config.performance.fade_frame_interval_ms);
     intervals.ime_poll_interval = Duration::from_millis(config.performance.ime_poll_interval_ms);
     intervals.config_reload_interval =
         Duration::from_millis(config.performance.config_reload_interval_ms);
 
+    let host = osd.host().hwnd();
     unsafe {
-        let _ = UnregisterHotKey(window.hwnd(), HOTKEY_TOGGLE_ID);
+        let _ = UnregisterHotKey(host, HOTKEY_TOGGLE_ID);
+        for index in 0..MAX_EXTRA_TOGGLES as i32 {
+            let _ = UnregisterHotKey(host, HOTKEY_EXTRA_TOGGLE_BASE_ID + index);
+        }
+        let _ = UnregisterHotKey(host, HOTKEY_PANIC_ID);
+        let _ = UnregisterHotKey(host, HOTKEY_SCREENSHOT_ID);
+        let _ = UnregisterHotKey(host, HOTKEY_PRIVACY_ID);
     }
-    register_toggle_hotkey(window.hwnd(), &config.hotkey.toggle);
+    register_toggle_hotkey(host, &config.hotkey);
+    register_panic_hotkey(host, &config.hotkey.panic_hide);
+    register_hotkey(host, HOTKEY_SCREENSHOT_ID, &config.hotkey.screenshot);
+    register_hotkey(host, HOTKEY_PRIVACY_ID, &config.hotkey.privacy_toggle);
 
     logger::update_config(&config.diagnostics);
+    event_recorder::update_config(&config.diagnostics);
 
     if autostart::set_autostart(config.startup.autostart_enabled).is_err() {
         logger::log(
@@ -613,27 +1189,33 @@ fn apply_config(
     }
 }
 
-fn apply_visual_config(
-    config: &AppConfig,
-    state: &mut DisplayState,
-    renderer: &mut D2DRenderer,
-    window: &mut OsdWindow,
-) {
-    state.update_config(config);
-    renderer.update_style(&config.style);
-    window.set_display_affinity(config.behavior.exclude_from_capture);
-
-    if window.width() != config.performance.osd_width || window.height() != config.performance.osd_height {
-        window.resize(config.performance.osd_width, config.performance.osd_height);
+/// ホットリロードで何が変わったかを項目ごとにログへ出す
+///
+/// `previous` にはディスク上の保存済み設定を渡す（`CURRENT_CONFIG` は設定画面も書き換える）。
+fn log_config_changes(previous: &AppConfig, new_config: &AppConfig) {
+    for diff in previous.diff(new_config) {
+        logger::log(DiagnosticsLevel::Info, &format!("config reloaded: {diff}"));
     }
+}
 
-    // Display settings should apply immediately (e.g. while tweaking in Settings).
-    unsafe {
-        let fg = GetForegroundWindow();
-        if !fg.0.is_null() {
-            window.reposition_to_monitor(fg, &config.display);
+fn apply_visual_config(config: &AppConfig, state: &mut DisplayState, osd: &mut MultiOsdManager) {
+    state.update_config(config);
+    for (window, renderer) in osd.iter_mut() {
+        renderer.update_style(&config.style);
+        renderer.set_layout(config.display.layout);
+        window.set_display_affinity(config.behavior.exclude_from_capture);
+
+        // auto_size のときは描画ループで中身に合わせる
+        if !config.display.auto_size
+            && (window.width() != config.performance.osd_width
+                || window.height() != config.performance.osd_height)
+        {
+            window.resize(config.performance.osd_width, config.performance.osd_height);
         }
     }
+
+    // Display settings should apply immediately (e.g. while tweaking in Settings).
+    osd.reposition_follow(&config.display);
 }
 
 fn merge_preview_config(base: &AppConfig, draft: &AppConfig) -> AppConfig {
@@ -647,6 +1229,8 @@ fn merge_preview_config(base: &AppConfig, draft: &AppConfig) -> AppConfig {
     cfg.display.max_items = draft.display.max_items;
     cfg.display.display_duration_ms = draft.display.display_duration_ms;
     cfg.display.fade_duration_ms = draft.display.fade_duration_ms;
+    cfg.display.layout = draft.display.layout;
+    cfg.display.auto_size = draft.display.auto_size;
 
     // Style (all visual).
     cfg.style = draft.style.clone();
@@ -657,9 +1241,15 @@ fn merge_preview_config(base: &AppConfig, draft: &AppConfig) -> AppConfig {
     cfg.behavior.distinguish_numpad = draft.behavior.distinguish_numpad;
     cfg.behavior.show_shift_for_typed_symbols = draft.behavior.show_shift_for_typed_symbols;
     cfg.behavior.show_ime_composition = draft.behavior.show_ime_composition;
+    cfg.behavior.show_ime_candidates = draft.behavior.show_ime_candidates;
+    cfg.behavior.ime_candidate_max_items = draft.behavior.ime_candidate_max_items;
+    cfg.behavior.show_ime_commit = draft.behavior.show_ime_commit;
+    cfg.behavior.ime_display_mode = draft.behavior.ime_display_mode;
     cfg.behavior.show_clipboard = draft.behavior.show_clipboard;
+    cfg.behavior.show_clipboard_source = draft.behavior.show_clipboard_source;
     cfg.behavior.clipboard_max_chars = draft.behavior.clipboard_max_chars;
     cfg.behavior.show_lock_indicators = draft.behavior.show_lock_indicators;
+    cfg.behavior.persist_lock_indicator = draft.behavior.persist_lock_indicator;
 
     // Performance (OSD size).
     cfg.performance.osd_width = draft.performance.osd_width;
@@ -701,6 +1291,26 @@ fn load_config_with_recovery(config_path: &Path) -> AppConfig {
     }
 }
 
+/// `--reset-config`: 既存の設定をバックアップしてから既定値で作り直す
+fn reset_config(config_path: &Path) {
+    let had_config = config_path.exists();
+    match backup_invalid_config(config_path) {
+        Ok(backup_path) if had_config => println!("Backed up config to {}", backup_path.display()),
+        Ok(_) => {}
+        Err(e) => {
+            eprintln!("yStrokey: failed to back up {}: {e}", config_path.display());
+            std::process::exit(1);
+        }
+    }
+    match AppConfig::create_default(config_path) {
+        Ok(_) => println!("Wrote default config to {}", config_path.display()),
+        Err(e) => {
+            eprintln!("yStrokey: failed to write default config: {e}");
+            std::process::exit(1);
+        }
+    }
+}
+
 fn default_runtime_config(config_path: &Path) -> AppConfig {
     let mut cfg = AppConfig::default();
     cfg.last_modified = std::fs::metadata(config_path)
@@ -730,6 +1340,28 @@ fn backup_invalid_config(config_path: &Path) -> Result<PathBuf, std::io::Error>
     Ok(backup_path)
 }
 
+/// HoldToShow 時にトグル用のコードが押されているか（それ以外のモードでは None）
+fn is_hold_chord_down(hotkey: &HotkeyConfig) -> Option<bool> {
+    if hotkey.mode != HotkeyMode::HoldToShow {
+        return None;
+    }
+    let (modifiers, vk) = parse_hotkey(&hotkey.toggle)?;
+    let is_down = |vk: VIRTUAL_KEY| unsafe { (GetAsyncKeyState(vk.0 as i32) as u16) & 0x8000 != 0 };
+
+    let required = [
+        (MOD_CONTROL, VK_CONTROL),
+        (MOD_ALT, VK_MENU),
+        (MOD_SHIFT, VK_SHIFT),
+    ];
+    let modifiers_down = required
+        .iter()
+        .filter(|(flag, _)| modifiers.contains(*flag))
+        .all(|(_, key)| is_down(*key));
+    let win_down = !modifiers.contains(MOD_WIN) || is_down(VK_LWIN) || is_down(VK_RWIN);
+
+    Some(modifiers_down && win_down && is_down(VIRTUAL_KEY(vk as u16)))
+}
+
 /// Modifier key + cursor distance determines ghost opacity.
 fn calculate_ghost_opacity(window: &OsdWindow, config: &AppConfig) -> f32 {
     unsafe {
@@ -790,6 +1422,13 @@ fn is_cursor_in_rect(rect: &RECT) -> bool {
     }
 }
 
+fn current_snap_to_edge_px() -> u32 {
+    CURRENT_CONFIG
+        .get()
+        .and_then(|cfg| cfg.lock().ok().map(|cfg| cfg.display.snap_to_edge_px))
+        .unwrap_or(0)
+}
+
 /// Save current window position to config file.
 fn save_current_position(hwnd: HWND) {
     unsafe {
@@ -797,21 +1436,37 @@ fn save_current_position(hwnd: HWND) {
         if GetWindowRect(hwnd, &mut rect).is_err() {
             return;
         }
+        if snap_rect_to_work_area(&mut rect, current_snap_to_edge_px()) {
+            let _ = SetWindowPos(
+                hwnd,
+                None,
+                rect.left,
+                rect.top,
+                0,
+                0,
+                SWP_NOSIZE | SWP_NOZORDER | SWP_NOACTIVATE,
+            );
+        }
 
         let hmon = MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST);
-        if let Some(device_name) = get_monitor_device_name(hmon) {
-            if let Some(cfg_mutex) = CURRENT_CONFIG.get() {
-                if let Ok(mut cfg) = cfg_mutex.lock() {
-                    cfg.display
-                        .monitor_positions
-                        .insert(device_name, [rect.left, rect.top]);
-                    if let Some(path) = CONFIG_PATH.get() {
-                        if let Err(e) = cfg.save_atomic(path) {
-                            logger::log(
-                                DiagnosticsLevel::Warn,
-                                &format!("Failed to save monitor position: {e}"),
-                            );
-                        }
+        // px で持つと DPI の違うモニタへ付け替えたときにずれるので、作業領域に対する割合で保存
+        let (Some(device_name), Some(fraction)) =
+            (get_monitor_device_name(hmon), work_area_fraction(hmon, &rect))
+        else {
+            return;
+        };
+        if let Some(cfg_mutex) = CURRENT_CONFIG.get() {
+            if let Ok(mut cfg) = cfg_mutex.lock() {
+                cfg.display.monitor_positions.remove(&device_name);
+                cfg.display
+                    .monitor_relative_positions
+                    .insert(device_name, fraction);
+                if let Some(path) = CONFIG_PATH.get() {
+                    if let Err(e) = cfg.save_atomic(path) {
+                        logger::log(
+                            DiagnosticsLevel::Warn,
+                            &format!("Failed to save monitor position: {e}"),
+                        );
                     }
                 }
             }
@@ -819,8 +1474,79 @@ fn save_current_position(hwnd: HWND) {
     }
 }
 
+/// ドラッグで保存した位置を消して保存し、設定どおりの既定位置へ配置し直させる
+///
+/// 再配置は `ConfigChanged` を受けたメインループの `apply_config` で行う。
+fn reset_saved_positions() {
+    if let Some(cfg_mutex) = CURRENT_CONFIG.get() {
+        if let Ok(mut cfg) = cfg_mutex.lock() {
+            cfg.display.clear_saved_positions();
+            if let Some(path) = CONFIG_PATH.get() {
+                if let Err(e) = cfg.save_atomic(path) {
+                    logger::log(
+                        DiagnosticsLevel::Warn,
+                        &format!("Failed to reset saved position: {e}"),
+                    );
+                    return;
+                }
+            }
+        }
+    }
+    if let Some(tx) = EVENT_TX.get() {
+        let _ = tx.try_send(InputEvent::ConfigChanged);
+    }
+}
+
+/// 現在の表示を `<exe_dir>/screenshots/<日時>.png` に保存し、保存先を返す
+fn save_screenshot(
+    osd: &MultiOsdManager,
+    state: &DisplayState,
+    config: &AppConfig,
+) -> Result<PathBuf, String> {
+    let (window, renderer) = osd.active();
+    let (width, height) = (window.width() as u32, window.height() as u32);
+    let bits = renderer
+        .render_to_bitmap(state.active_items(), &config.style, width, height)
+        .map_err(|e| e.to_string())?;
+
+    let t = unsafe { GetLocalTime() };
+    let file_name = format!(
+        "{:04}{:02}{:02}-{:02}{:02}{:02}.png",
+        t.wYear, t.wMonth, t.wDay, t.wHour, t.wMinute, t.wSecond
+    );
+    let exe_dir = std::env::current_exe()
+        .ok()
+        .and_then(|p| p.parent().map(Path::to_path_buf))
+        .unwrap_or_else(|| PathBuf::from("."));
+    let path = exe_dir.join("screenshots").join(file_name);
+    save_png(&bits, width, height, &path).map_err(|e| e.to_string())?;
+    Ok(path)
+}
+
 /// Parse hotkey string and register with RegisterHotKey.
-fn register_toggle_hotkey(hwnd: HWND, hotkey_str: &str) {
+///
+/// HoldToShow ではメインループのポーリングで判定するため登録しない。
+fn register_toggle_hotkey(hwnd: HWND, hotkey: &HotkeyConfig) {
+    if hotkey.mode == HotkeyMode::HoldToShow {
+        return;
+    }
+    register_hotkey(hwnd, HOTKEY_TOGGLE_ID, &hotkey.toggle);
+    for (index, extra) in hotkey.extra_toggles.iter().take(MAX_EXTRA_TOGGLES).enumerate() {
+        register_hotkey(hwnd, HOTKEY_EXTRA_TOGGLE_BASE_ID + index as i32, extra);
+    }
+}
+
+fn is_toggle_hotkey_id(id: i32) -> bool {
+    id == HOTKEY_TOGGLE_ID
+        || (HOTKEY_EXTRA_TOGGLE_BASE_ID..HOTKEY_EXTRA_TOGGLE_BASE_ID + MAX_EXTRA_TOGGLES as i32)
+            .contains(&id)
+}
+
+fn register_panic_hotkey(hwnd: HWND, hotkey_str: &str) {
+    register_hotkey(hwnd, HOTKEY_PANIC_ID, hotkey_str);
+}
+
+fn register_hotkey(hwnd: HWND, id: i32, hotkey_str: &str) {
     if hotkey_str.is_empty() {
         return;
     }
@@ -831,7 +1557,7 @@ fn register_toggle_hotkey(hwnd: HWND, hotkey_str: &str) {
     };
 
     unsafe {
-        if RegisterHotKey(hwnd, HOTKEY_TOGGLE_ID, modifiers, vk).is_err() {
+        if RegisterHotKey(hwnd, id, modifiers, vk).is_err() {
             logger::log(
                 DiagnosticsLevel::Warn,
                 &format!("RegisterHotKey failed for: {}", hotkey_str),
@@ -930,3 +1656,30 @@ fn key_name_to_vk(name: &str) -> Option<u32> {
     };
     Some(vk)
 }
+
+/// `key_name_to_vk` の逆変換（ホットキー文字列に使えないキーは None）
+fn vk_to_key_name(vk: u32) -> Option<String> {
+    let name = match vk {
+        0x70..=0x7B => return Some(format!("F{}", vk - 0x6F)),
+        0x30..=0x39 | 0x41..=0x5A => return char::from_u32(vk).map(String::from),
+        0x20 => "Space",
+        0x0D => "Enter",
+        0x09 => "Tab",
+        0x1B => "Esc",
+        0x08 => "BS",
+        0x2E => "Del",
+        0x2D => "Ins",
+        0x24 => "Home",
+        0x23 => "End",
+        0x21 => "PgUp",
+        0x22 => "PgDn",
+        0x25 => "Left",
+        0x26 => "Up",
+        0x27 => "Right",
+        0x28 => "Down",
+        0x13 => "Pause",
+        0x2C => "PrtSc",
+        _ => return None,
+    };
+    Some(name.to_string())
+}
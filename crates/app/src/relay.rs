@@ -0,0 +1,64 @@
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::Arc;
+use std::thread;
+
+use windows::Win32::Foundation::{CloseHandle, HANDLE};
+use windows::Win32::System::Threading::{CreateEventW, SetEvent};
+
+use ystrokey_core::InputEvent;
+
+/// スレッド間で共有する自動リセットイベント
+struct WakeEvent(HANDLE);
+
+// イベントオブジェクトはどのスレッドからでも SetEvent / 待機できる
+unsafe impl Send for WakeEvent {}
+unsafe impl Sync for WakeEvent {}
+
+impl Drop for WakeEvent {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = CloseHandle(self.0);
+        }
+    }
+}
+
+/// 入力スレッドからのイベントをメインスレッドのチャネルへ中継する
+///
+/// 転送のたびにイベントオブジェクトをシグナルするので、メインスレッドは
+/// `MsgWaitForMultipleObjects` でウィンドウメッセージと入力の到着を同時に待てる。
+pub struct EventRelay {
+    wake: Arc<WakeEvent>,
+}
+
+impl EventRelay {
+    /// 戻り値は (入力スレッド用の送信側, メインスレッド用の受信側, 中継)
+    pub fn start(
+        capacity: usize,
+    ) -> std::io::Result<(SyncSender<InputEvent>, Receiver<InputEvent>, Self)> {
+        let handle = unsafe { CreateEventW(None, false, false, None)? };
+        let wake = Arc::new(WakeEvent(handle));
+
+        let (input_tx, input_rx) = mpsc::sync_channel::<InputEvent>(capacity);
+        let (main_tx, main_rx) = mpsc::sync_channel::<InputEvent>(capacity);
+        let thread_wake = Arc::clone(&wake);
+        thread::Builder::new()
+            .name("event-relay".into())
+            .spawn(move || {
+                while let Ok(event) = input_rx.recv() {
+                    if main_tx.send(event).is_err() {
+                        break;
+                    }
+                    unsafe {
+                        let _ = SetEvent(thread_wake.0);
+                    }
+                }
+            })?;
+
+        Ok((input_tx, main_rx, Self { wake }))
+    }
+
+    /// 入力が中継されるとシグナル状態になるハンドル（待機で自動リセット）
+    pub fn wake_handle(&self) -> HANDLE {
+        self.wake.0
+    }
+}
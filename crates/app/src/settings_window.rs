@@ -1,16 +1,21 @@
 
 use std::cell::Cell;
+use std::collections::HashMap;
 use std::path::Path;
 use std::sync::mpsc::SyncSender;
 
 use windows::core::HSTRING;
 use windows::Win32::Foundation::*;
 use windows::Win32::Graphics::Gdi::*;
+use windows::Win32::UI::Controls::Dialogs::{
+    ChooseFontW, CF_INITTOLOGFONTSTRUCT, CF_NOVERTFONTS, CF_SCREENFONTS, CHOOSEFONTW, SCREEN_FONTTYPE,
+};
+use windows::Win32::UI::HiDpi::GetDpiForWindow;
 use windows::Win32::UI::WindowsAndMessaging::*;
 
 use ystrokey_core::{
-    AppConfig, DiagnosticsLevel, FadeOutCurve, GhostModifier, InputEvent, KeyTransitionMode,
-    MenuLanguage, Position, ShortcutDef,
+    AppConfig, DiagnosticsLevel, FadeOutCurve, FollowMode, GhostModifier, HotkeyMode, ImeDisplayMode, InputEvent, KeyTransitionMode,
+    LogFormat, LogTimestamp, MenuLanguage, MouseInputMode, OsdLayout, Position, PrivacyMode, ShortcutDef, TextRenderingMode,
 };
 
 struct SettingsState {
@@ -20,9 +25,104 @@ struct SettingsState {
     notify_tx: Option<SyncSender<InputEvent>>,
     category: Category,
     nav: HWND,
+    search: HWND,
+    /// 検索で絞り込んだ現在のナビゲーション項目（リストの並び順）
+    nav_categories: Vec<Category>,
     status: HWND,
-    dynamic_controls: Vec<HWND>,
+    dynamic_controls: Vec<(HWND, Placement)>,
+    layout: PaneLayout,
     rebuilding: bool,
+    /// 保存前の設定（末尾が直前）。Undo で戻し、戻した分は `redo_stack` へ積む
+    undo_stack: Vec<AppConfig>,
+    redo_stack: Vec<AppConfig>,
+}
+
+/// 外周と列間の余白
+const MARGIN: i32 = 20;
+/// ナビゲーションリストの幅（クライアント幅に対する割合 %）
+const NAV_WIDTH_PERCENT: i32 = 24;
+/// 右ペインのラベル列の幅
+const LABEL_WIDTH: i32 = 220;
+/// 入力欄の右に並べるボタンの幅
+const ROW_BUTTON_WIDTH: i32 = 90;
+/// ボタン行・ステータス行に使う下端の高さ
+const FOOTER_HEIGHT: i32 = 80;
+/// Undo / Redo で遡れる保存回数
+const HISTORY_LIMIT: usize = 20;
+/// ウィンドウの最小サイズ
+const MIN_WINDOW_WIDTH: i32 = 640;
+const MIN_WINDOW_HEIGHT: i32 = 480;
+
+/// 右ペイン内の列（x と幅はウィンドウ幅に合わせて再計算する）
+#[derive(Clone, Copy)]
+enum Column {
+    /// 左側のラベル列
+    Label,
+    /// ラベル右側の入力欄（`max_width` が 0 なら残り幅いっぱい）
+    Field { max_width: i32 },
+    /// 右端にボタンを置く行の入力欄（残り幅からボタン分を除く）
+    FieldBeforeButton,
+    /// 入力欄の右に置くボタン
+    RowButton,
+    /// ペイン全幅
+    Full,
+}
+
+/// 右ペイン基準の配置（y はクライアント上端から）
+#[derive(Clone, Copy)]
+struct Placement {
+    column: Column,
+    y: i32,
+    height: i32,
+}
+
+/// クライアント領域の大きさから各領域の位置を求める
+#[derive(Clone, Copy)]
+struct PaneLayout {
+    client_width: i32,
+    client_height: i32,
+}
+
+impl PaneLayout {
+    fn nav_width(&self) -> i32 {
+        self.client_width * NAV_WIDTH_PERCENT / 100
+    }
+
+    fn pane_left(&self) -> i32 {
+        MARGIN + self.nav_width() + MARGIN
+    }
+
+    fn pane_width(&self) -> i32 {
+        (self.client_width - self.pane_left() - MARGIN * 2).max(LABEL_WIDTH)
+    }
+
+    fn footer_top(&self) -> i32 {
+        self.client_height - FOOTER_HEIGHT
+    }
+
+    /// 配置をクライアント座標の (x, y, w, h) に変換
+    fn rect(&self, placement: Placement) -> (i32, i32, i32, i32) {
+        let left = self.pane_left();
+        let field_left = LABEL_WIDTH + 10;
+        let (x, w) = match placement.column {
+            Column::Label => (0, LABEL_WIDTH),
+            Column::Field { max_width } => {
+                let available = (self.pane_width() - field_left).max(60);
+                let w = if max_width > 0 { available.min(max_width) } else { available };
+                (field_left, w)
+            }
+            Column::FieldBeforeButton => {
+                let available = (self.pane_width() - field_left).max(60 + ROW_BUTTON_WIDTH + 8);
+                (field_left, available - ROW_BUTTON_WIDTH - 8)
+            }
+            Column::RowButton => {
+                let available = (self.pane_width() - field_left).max(60 + ROW_BUTTON_WIDTH + 8);
+                (field_left + available - ROW_BUTTON_WIDTH, ROW_BUTTON_WIDTH)
+            }
+            Column::Full => (0, self.pane_width()),
+        };
+        (left + x, placement.y, w, placement.height)
+    }
 }
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -37,24 +137,171 @@ enum Category {
     Startup,
     Tray,
     Animation,
+    Logs,
 }
 
 impl Category {
-    fn from_index(i: i32) -> Self {
-        match i {
-            0 => Self::General,
-            1 => Self::Display,
-            2 => Self::Style,
-            3 => Self::Input,
-            4 => Self::Privacy,
-            5 => Self::Performance,
-            6 => Self::Diagnostics,
-            7 => Self::Startup,
-            8 => Self::Tray,
-            9 => Self::Animation,
-            _ => Self::General,
-        }
-    }
+    /// ナビゲーションの表示順（`category_search_table` と同じ並び）
+    const ALL: [Category; 11] = [
+        Self::General,
+        Self::Display,
+        Self::Style,
+        Self::Input,
+        Self::Privacy,
+        Self::Performance,
+        Self::Diagnostics,
+        Self::Startup,
+        Self::Tray,
+        Self::Animation,
+        Self::Logs,
+    ];
+}
+
+/// 検索用: カテゴリ名と、そのページにある項目ラベル
+fn category_search_table() -> Vec<(&'static str, &'static [&'static str])> {
+    vec![
+        (
+            "General",
+            &[
+                "Toggle hotkey",
+                "Toggle hotkey mode",
+                "Extra toggle hotkeys",
+                "Panic hide hotkey",
+                "Screenshot hotkey",
+                "Privacy toggle hotkey",
+                "Shortcuts",
+            ],
+        ),
+        (
+            "Display",
+            &[
+                "Position",
+                "X%",
+                "Y%",
+                "Offset X",
+                "Offset Y",
+                "Max items",
+                "Display duration",
+                "Fade duration",
+                "Layout",
+                "Snap to edge",
+                "Follow",
+                "Auto size",
+                "Saved position",
+                "Enabled monitors",
+            ],
+        ),
+        (
+            "Style",
+            &[
+                "Font family",
+                "Custom font file",
+                "Fallback font",
+                "Font size",
+                "Key font weight",
+                "Key italic",
+                "IME font weight",
+                "IME italic",
+                "Clipboard font weight",
+                "Text color",
+                "Background color",
+                "Border radius",
+                "Padding",
+                "Shortcut color",
+                "Key down color",
+                "Opacity",
+                "Keycap style",
+                "Color emoji fonts",
+                "Text rendering",
+            ],
+        ),
+        (
+            "Input",
+            &[
+                "Key transition display",
+                "Show repeat count",
+                "Distinguish numpad",
+                "Distinguish left/right modifiers",
+                "Show gamepad buttons",
+                "Show held modifiers",
+                "Mouse input",
+                "Show mouse drags",
+                "Drag min distance",
+                "Show Shift badge for typed symbols",
+                "Show IME composition",
+                "Show IME candidates",
+                "IME candidate max items",
+                "Flash committed IME text",
+                "IME display mode",
+                "Show clipboard",
+                "Show clipboard source app",
+                "Clipboard max chars",
+                "Clipboard debounce",
+                "Show lock indicators",
+                "Keep lock indicator while Caps/Num Lock is on",
+                "Repeat timeout",
+                "Per-key repeat timeout",
+                "Group timeout",
+                "Max group size",
+                "Exclude from capture",
+                "Ignored keys",
+                "Pinned item kinds",
+            ],
+        ),
+        (
+            "Privacy",
+            &[
+                "Privacy filter enabled",
+                "Mode",
+                "Privacy hotkey auto-resume",
+                "Blocked process names",
+                "Allowed process names",
+                "Blocked window title substrings",
+                "Mask clipboard patterns",
+                "Clipboard redact patterns",
+                "Redact replacement",
+            ],
+        ),
+        (
+            "Performance",
+            &[
+                "OSD width",
+                "OSD height",
+                "IME poll interval",
+                "Frame interval",
+                "Fade-only frame interval",
+                "Config reload debounce",
+                "HTTP server port",
+                "WebSocket port",
+            ],
+        ),
+        (
+            "Diagnostics",
+            &["Level", "Enable file logging", "Max file bytes", "Max files", "Log format", "Log timestamp", "Record input events", "Events file"],
+        ),
+        ("Startup", &["Enable autostart"]),
+        ("Tray", &["OSD enabled on startup", "Menu language", "Confirm on exit"]),
+        (
+            "Animation",
+            &["Ghost modifier", "Ghost threshold", "Ghost max opacity", "Fade out curve"],
+        ),
+        ("Logs", &["Recent log lines"]),
+    ]
+}
+
+/// 検索語（大文字小文字無視の部分一致）に一致する項目を持つカテゴリ。空なら全カテゴリ
+fn matching_categories(term: &str) -> Vec<Category> {
+    let term = term.trim().to_lowercase();
+    Category::ALL
+        .into_iter()
+        .zip(category_search_table())
+        .filter(|(_, (name, labels))| {
+            term.is_empty()
+                || name.to_lowercase().contains(&term)
+                || labels.iter().any(|label| label.to_lowercase().contains(&term))
+        })
+        .map(|(category, _)| category)
+        .collect()
 }
 
 thread_local! {
@@ -65,9 +312,18 @@ const ID_NAV: u16 = 100;
 const ID_BTN_REVERT_SECTION: u16 = 101;
 const ID_BTN_RESET_ALL: u16 = 102;
 const ID_BTN_CLOSE: u16 = 103;
+const ID_NAV_SEARCH: u16 = 104;
+const ID_BTN_UNDO: u16 = 105;
+const ID_BTN_REDO: u16 = 106;
 
 const ID_HOTKEY_TOGGLE: u16 = 1000;
 const ID_SHORTCUTS: u16 = 1001;
+const ID_HOTKEY_PANIC_HIDE: u16 = 1002;
+const ID_HOTKEY_EXTRA_TOGGLES: u16 = 1003;
+const ID_HOTKEY_MODE: u16 = 1004;
+const ID_HOTKEY_SCREENSHOT: u16 = 1005;
+const ID_HOTKEY_RECORD: u16 = 1006;
+const ID_HOTKEY_PRIVACY_TOGGLE: u16 = 1007;
 
 const ID_DISPLAY_POSITION: u16 = 1100;
 const ID_DISPLAY_OFFSET_X: u16 = 1101;
@@ -75,6 +331,14 @@ const ID_DISPLAY_OFFSET_Y: u16 = 1102;
 const ID_DISPLAY_MAX_ITEMS: u16 = 1103;
 const ID_DISPLAY_DURATION: u16 = 1104;
 const ID_DISPLAY_FADE: u16 = 1105;
+const ID_DISPLAY_LAYOUT: u16 = 1106;
+const ID_DISPLAY_SNAP_TO_EDGE: u16 = 1107;
+const ID_DISPLAY_CUSTOM_X: u16 = 1108;
+const ID_DISPLAY_CUSTOM_Y: u16 = 1109;
+const ID_DISPLAY_ENABLED_MONITORS: u16 = 1110;
+const ID_DISPLAY_FOLLOW: u16 = 1111;
+const ID_DISPLAY_AUTO_SIZE: u16 = 1112;
+const ID_DISPLAY_RESET_POSITION: u16 = 1113;
 
 const ID_STYLE_FONT_FAMILY: u16 = 1200;
 const ID_STYLE_FONT_SIZE: u16 = 1201;
@@ -85,6 +349,17 @@ const ID_STYLE_PADDING: u16 = 1205;
 const ID_STYLE_SHORTCUT_COLOR: u16 = 1206;
 const ID_STYLE_KEY_DOWN_COLOR: u16 = 1207;
 const ID_STYLE_OPACITY: u16 = 1208;
+const ID_STYLE_CUSTOM_FONT_PATH: u16 = 1209;
+const ID_STYLE_TEXT_RENDERING: u16 = 1210;
+const ID_STYLE_KEY_FONT_WEIGHT: u16 = 1211;
+const ID_STYLE_IME_FONT_WEIGHT: u16 = 1212;
+const ID_STYLE_CLIPBOARD_FONT_WEIGHT: u16 = 1213;
+const ID_STYLE_IME_FONT_ITALIC: u16 = 1214;
+const ID_STYLE_KEYCAP: u16 = 1215;
+const ID_STYLE_FONT_BROWSE: u16 = 1216;
+const ID_STYLE_COLOR_FONTS: u16 = 1217;
+const ID_STYLE_KEY_FONT_ITALIC: u16 = 1218;
+const ID_STYLE_FALLBACK_FONT: u16 = 1219;
 
 const ID_BEHAVIOR_KEY_TRANSITION_MODE: u16 = 1300;
 const ID_BEHAVIOR_SHOW_REPEAT_COUNT: u16 = 1301;
@@ -99,20 +374,50 @@ const ID_BEHAVIOR_MAX_GROUP_SIZE: u16 = 1309;
 const ID_BEHAVIOR_IGNORED_KEYS: u16 = 1310;
 const ID_BEHAVIOR_EXCLUDE_CAPTURE: u16 = 1311;
 const ID_BEHAVIOR_SHIFT_TYPED_SYMBOLS: u16 = 1312;
+const ID_BEHAVIOR_CLIPBOARD_DEBOUNCE: u16 = 1313;
+const ID_BEHAVIOR_DISTINGUISH_LR_MODIFIERS: u16 = 1314;
+const ID_BEHAVIOR_SHOW_GAMEPAD: u16 = 1315;
+const ID_BEHAVIOR_MOUSE_INPUT_MODE: u16 = 1316;
+const ID_BEHAVIOR_SHOW_MODIFIER_STATE: u16 = 1317;
+const ID_BEHAVIOR_SHOW_MOUSE_DRAG: u16 = 1318;
+const ID_BEHAVIOR_DRAG_MIN_PIXELS: u16 = 1319;
+const ID_BEHAVIOR_PINNED_ITEM_KINDS: u16 = 1320;
+const ID_BEHAVIOR_IME_DISPLAY_MODE: u16 = 1321;
+const ID_BEHAVIOR_PRIVACY_OVERRIDE_TIMEOUT: u16 = 1322;
+const ID_BEHAVIOR_PERSIST_LOCK: u16 = 1323;
+const ID_BEHAVIOR_SHOW_IME_CANDIDATES: u16 = 1324;
+const ID_BEHAVIOR_SHOW_CLIPBOARD_SOURCE: u16 = 1325;
+const ID_BEHAVIOR_SHOW_IME_COMMIT: u16 = 1326;
+const ID_BEHAVIOR_IME_CANDIDATE_MAX: u16 = 1327;
+const ID_BEHAVIOR_PER_KEY_REPEAT_TIMEOUT: u16 = 1328;
 
 const ID_PRIVACY_ENABLED: u16 = 1400;
 const ID_PRIVACY_BLOCKED_APPS: u16 = 1401;
+const ID_PRIVACY_MASK_CLIPBOARD: u16 = 1402;
+const ID_PRIVACY_BLOCKED_TITLES: u16 = 1403;
+const ID_PRIVACY_MODE: u16 = 1404;
+const ID_PRIVACY_ALLOWED_APPS: u16 = 1405;
+const ID_PRIVACY_REDACT_PATTERNS: u16 = 1406;
+const ID_PRIVACY_REDACT_REPLACEMENT: u16 = 1407;
 
 const ID_PERF_OSD_WIDTH: u16 = 1500;
 const ID_PERF_OSD_HEIGHT: u16 = 1501;
 const ID_PERF_IME_POLL: u16 = 1502;
 const ID_PERF_FRAME_INTERVAL: u16 = 1503;
 const ID_PERF_RELOAD_INTERVAL: u16 = 1504;
+const ID_PERF_HTTP_PORT: u16 = 1505;
+const ID_PERF_WS_PORT: u16 = 1506;
+const ID_PERF_FADE_FRAME_INTERVAL: u16 = 1507;
+const ID_PERF_HTTP_ALLOWED_ORIGIN: u16 = 1508;
 
 const ID_DIAG_LEVEL: u16 = 1600;
 const ID_DIAG_FILE_ENABLED: u16 = 1601;
 const ID_DIAG_MAX_BYTES: u16 = 1602;
 const ID_DIAG_MAX_FILES: u16 = 1603;
+const ID_DIAG_LOG_FORMAT: u16 = 1604;
+const ID_DIAG_TIMESTAMP: u16 = 1605;
+const ID_DIAG_RECORD_EVENTS: u16 = 1606;
+const ID_DIAG_EVENTS_FILE: u16 = 1607;
 
 const ID_STARTUP_AUTOSTART: u16 = 1700;
 
@@ -125,16 +430,27 @@ const ID_ANIM_GHOST_THRESHOLD: u16 = 1901;
 const ID_ANIM_GHOST_MAX_OPACITY: u16 = 1902;
 const ID_ANIM_FADE_CURVE: u16 = 1903;
 
+const ID_LOGS_RECENT: u16 = 2000;
+
+/// EM_SETREADONLY（Win32_UI_Controls を有効にせずに使うためローカル定義）
+const EM_SETREADONLY: u32 = 0x00CF;
+/// EM_SETCUEBANNER（同上）
+const EM_SETCUEBANNER: u32 = 0x1501;
+
 fn should_request_preview(changed_control_id: u16) -> bool {
     matches!(
         changed_control_id,
         // Display
         ID_DISPLAY_POSITION
+            | ID_DISPLAY_CUSTOM_X
+            | ID_DISPLAY_CUSTOM_Y
             | ID_DISPLAY_OFFSET_X
             | ID_DISPLAY_OFFSET_Y
             | ID_DISPLAY_MAX_ITEMS
             | ID_DISPLAY_DURATION
             | ID_DISPLAY_FADE
+            | ID_DISPLAY_LAYOUT
+            | ID_DISPLAY_AUTO_SIZE
             // Style
             | ID_STYLE_FONT_FAMILY
             | ID_STYLE_FONT_SIZE
@@ -145,15 +461,31 @@ fn should_request_preview(changed_control_id: u16) -> bool {
             | ID_STYLE_SHORTCUT_COLOR
             | ID_STYLE_KEY_DOWN_COLOR
             | ID_STYLE_OPACITY
+            | ID_STYLE_CUSTOM_FONT_PATH
+            | ID_STYLE_FALLBACK_FONT
+            | ID_STYLE_TEXT_RENDERING
+            | ID_STYLE_KEY_FONT_WEIGHT
+            | ID_STYLE_IME_FONT_WEIGHT
+            | ID_STYLE_CLIPBOARD_FONT_WEIGHT
+            | ID_STYLE_KEY_FONT_ITALIC
+            | ID_STYLE_IME_FONT_ITALIC
+            | ID_STYLE_KEYCAP
+            | ID_STYLE_COLOR_FONTS
             // Behavior (display affects)
             | ID_BEHAVIOR_KEY_TRANSITION_MODE
             | ID_BEHAVIOR_SHOW_REPEAT_COUNT
             | ID_BEHAVIOR_DISTINGUISH_NUMPAD
             | ID_BEHAVIOR_SHIFT_TYPED_SYMBOLS
             | ID_BEHAVIOR_SHOW_IME
+            | ID_BEHAVIOR_SHOW_IME_CANDIDATES
+            | ID_BEHAVIOR_IME_CANDIDATE_MAX
+            | ID_BEHAVIOR_SHOW_IME_COMMIT
+            | ID_BEHAVIOR_IME_DISPLAY_MODE
             | ID_BEHAVIOR_SHOW_CLIPBOARD
+            | ID_BEHAVIOR_SHOW_CLIPBOARD_SOURCE
             | ID_BEHAVIOR_CLIPBOARD_MAX_CHARS
             | ID_BEHAVIOR_SHOW_LOCK
+            | ID_BEHAVIOR_PERSIST_LOCK
             // Performance (OSD size)
             | ID_PERF_OSD_WIDTH
             | ID_PERF_OSD_HEIGHT
@@ -169,7 +501,7 @@ fn should_live_preview_on_change(changed_control_id: u16) -> bool {
     matches!(
         changed_control_id,
         // Display (positioning)
-        ID_DISPLAY_OFFSET_X | ID_DISPLAY_OFFSET_Y
+        ID_DISPLAY_OFFSET_X | ID_DISPLAY_OFFSET_Y | ID_DISPLAY_CUSTOM_X | ID_DISPLAY_CUSTOM_Y
         // Style (most visual edits are safe to reflect immediately)
         | ID_STYLE_FONT_FAMILY
             | ID_STYLE_FONT_SIZE
@@ -240,6 +572,8 @@ unsafe extern "system" fn settings_wnd_proc(
                             Ok(()) => {
                                 state.config = cfg;
                                 state.draft_config = state.config.clone();
+                                state.undo_stack.clear();
+                                state.redo_stack.clear();
                                 rebuild_category(hwnd, state);
                                 set_status(state, "Reset to defaults.");
                             }
@@ -248,10 +582,54 @@ unsafe extern "system" fn settings_wnd_proc(
                     }
                     return LRESULT(0);
                 }
+                ID_BTN_UNDO | ID_BTN_REDO => {
+                    if notify == BN_CLICKED as u16 {
+                        step_history(hwnd, state, cmd_id == ID_BTN_UNDO);
+                    }
+                    return LRESULT(0);
+                }
                 ID_NAV if notify == LBN_SELCHANGE as u16 => {
-                    let idx = SendMessageW(state.nav, LB_GETCURSEL, WPARAM(0), LPARAM(0)).0 as i32;
-                    state.category = Category::from_index(idx);
-                    rebuild_category(hwnd, state);
+                    let idx = SendMessageW(state.nav, LB_GETCURSEL, WPARAM(0), LPARAM(0)).0;
+                    if let Some(&category) = usize::try_from(idx).ok().and_then(|i| state.nav_categories.get(i)) {
+                        state.category = category;
+                        rebuild_category(hwnd, state);
+                    }
+                    return LRESULT(0);
+                }
+                ID_HOTKEY_RECORD => {
+                    if notify == BN_CLICKED as u16 {
+                        if let Some(hotkey) = crate::hotkey_recorder::record_hotkey(hwnd) {
+                            set_control_text(hwnd, ID_HOTKEY_TOGGLE, &hotkey);
+                            save_controls(hwnd, state, &[ID_HOTKEY_TOGGLE]);
+                        }
+                    }
+                    return LRESULT(0);
+                }
+                ID_STYLE_FONT_BROWSE => {
+                    if notify == BN_CLICKED as u16 {
+                        browse_font(hwnd, state);
+                    }
+                    return LRESULT(0);
+                }
+                ID_DISPLAY_RESET_POSITION => {
+                    if notify == BN_CLICKED as u16 {
+                        let mut cfg = state.config.clone();
+                        cfg.display.clear_saved_positions();
+                        match persist_and_notify(state, &mut cfg) {
+                            Ok(()) => {
+                                state.config = cfg;
+                                state.draft_config.display.clear_saved_positions();
+                                set_status(state, "Position reset.");
+                            }
+                            Err(e) => set_status(state, &format!("Reset failed: {e}")),
+                        }
+                    }
+                    return LRESULT(0);
+                }
+                ID_NAV_SEARCH => {
+                    if notify == EN_CHANGE as u16 {
+                        refill_nav(state);
+                    }
                     return LRESULT(0);
                 }
                 _ => {}
@@ -284,6 +662,10 @@ unsafe extern "system" fn settings_wnd_proc(
                             state.config = new_cfg;
                             state.draft_config = state.config.clone();
                             set_status(state, "Saved.");
+                            // custom のときだけ X% / Y% の行を出す
+                            if cmd_id == ID_DISPLAY_POSITION {
+                                rebuild_category(hwnd, state);
+                            }
                             if should_request_preview(cmd_id) {
                                 if let Some(tx) = &state.notify_tx {
                                     let _ = tx.try_send(InputEvent::PreviewConfig {
@@ -306,6 +688,20 @@ unsafe extern "system" fn settings_wnd_proc(
 
             LRESULT(0)
         }
+        WM_SIZE if wparam.0 != SIZE_MINIMIZED as usize => {
+            let width = (lparam.0 & 0xFFFF) as i32;
+            let height = ((lparam.0 >> 16) & 0xFFFF) as i32;
+            relayout(hwnd, width, height);
+            LRESULT(0)
+        }
+        WM_GETMINMAXINFO => {
+            let info = &mut *(lparam.0 as *mut MINMAXINFO);
+            info.ptMinTrackSize = POINT {
+                x: MIN_WINDOW_WIDTH,
+                y: MIN_WINDOW_HEIGHT,
+            };
+            LRESULT(0)
+        }
         WM_DESTROY => {
             let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut SettingsState;
             if !ptr.is_null() {
@@ -324,7 +720,15 @@ unsafe extern "system" fn settings_wnd_proc(
     }
 }
 
-fn persist_and_notify(state: &SettingsState, cfg: &mut AppConfig) -> Result<(), String> {
+/// 保存して本体へ通知し、保存前の設定を Undo 履歴に積む
+fn persist_and_notify(state: &mut SettingsState, cfg: &mut AppConfig) -> Result<(), String> {
+    write_and_notify(state, cfg)?;
+    push_history(&mut state.undo_stack, state.config.clone());
+    state.redo_stack.clear();
+    Ok(())
+}
+
+fn write_and_notify(state: &SettingsState, cfg: &mut AppConfig) -> Result<(), String> {
     cfg.validate().map_err(|e| e.to_string())?;
     cfg.save_atomic(&state.config_path)
         .map_err(|e| e.to_string())?;
@@ -336,6 +740,132 @@ fn persist_and_notify(state: &SettingsState, cfg: &mut AppConfig) -> Result<(),
     Ok(())
 }
 
+fn push_history(stack: &mut Vec<AppConfig>, cfg: AppConfig) {
+    if stack.len() >= HISTORY_LIMIT {
+        stack.remove(0);
+    }
+    stack.push(cfg);
+}
+
+/// Undo（`undo` が false なら Redo）で 1 つ前の設定に戻して保存し直す
+unsafe fn step_history(hwnd: HWND, state: &mut SettingsState, undo: bool) {
+    let popped = if undo {
+        state.undo_stack.pop()
+    } else {
+        state.redo_stack.pop()
+    };
+    let Some(mut cfg) = popped else {
+        set_status(state, if undo { "Nothing to undo." } else { "Nothing to redo." });
+        return;
+    };
+    match write_and_notify(state, &mut cfg) {
+        Ok(()) => {
+            let previous = std::mem::replace(&mut state.config, cfg);
+            if undo {
+                push_history(&mut state.redo_stack, previous);
+            } else {
+                push_history(&mut state.undo_stack, previous);
+            }
+            rebuild_category(hwnd, state);
+            set_status(state, if undo { "Undone." } else { "Redone." });
+            // プレビュー中は下書きが優先されるので、戻した設定を下書きとしても送る
+            if let Some(tx) = &state.notify_tx {
+                let _ = tx.try_send(InputEvent::PreviewConfig {
+                    config: state.draft_config.clone(),
+                });
+            }
+        }
+        Err(e) => {
+            if undo {
+                state.undo_stack.push(cfg);
+            } else {
+                state.redo_stack.push(cfg);
+            }
+            set_status(state, &format!("Save failed: {e}"));
+        }
+    }
+}
+
+/// フォント選択ダイアログで font_family / font_size を選び直して保存する
+unsafe fn browse_font(hwnd: HWND, state: &mut SettingsState) {
+    let family = get_edit_string(hwnd, ID_STYLE_FONT_FAMILY);
+    let size = get_edit_f32(hwnd, ID_STYLE_FONT_SIZE).unwrap_or(state.config.style.font_size);
+    // font_size は 96 DPI 基準のピクセル。ダイアログは画面 DPI で高さを解釈する
+    let dpi = GetDpiForWindow(hwnd).max(96) as f32;
+
+    let mut log_font = LOGFONTW {
+        lfHeight: -(size * dpi / 96.0).round() as i32,
+        ..Default::default()
+    };
+    for (dst, src) in log_font
+        .lfFaceName
+        .iter_mut()
+        .take(LF_FACESIZE as usize - 1)
+        .zip(family.encode_utf16())
+    {
+        *dst = src;
+    }
+
+    let mut choose = CHOOSEFONTW {
+        lStructSize: std::mem::size_of::<CHOOSEFONTW>() as u32,
+        hwndOwner: hwnd,
+        lpLogFont: &mut log_font,
+        Flags: CF_SCREENFONTS | CF_INITTOLOGFONTSTRUCT | CF_NOVERTFONTS,
+        nFontType: SCREEN_FONTTYPE,
+        ..Default::default()
+    };
+    if !ChooseFontW(&mut choose).as_bool() {
+        return;
+    }
+
+    let face_len = log_font
+        .lfFaceName
+        .iter()
+        .position(|&c| c == 0)
+        .unwrap_or(log_font.lfFaceName.len());
+    let face = String::from_utf16_lossy(&log_font.lfFaceName[..face_len]);
+    // iPointSize は 1/10 pt 単位
+    let size_px = (choose.iPointSize as f32 / 10.0 * 96.0 / 72.0 * 10.0).round() / 10.0;
+
+    // 書き戻し中の EN_CHANGE でプレビューを二重に送らない
+    state.rebuilding = true;
+    set_control_text(hwnd, ID_STYLE_FONT_FAMILY, &face);
+    set_control_text(hwnd, ID_STYLE_FONT_SIZE, &size_px.to_string());
+    state.rebuilding = false;
+
+    save_controls(hwnd, state, &[ID_STYLE_FONT_FAMILY, ID_STYLE_FONT_SIZE]);
+}
+
+unsafe fn set_control_text(parent: HWND, id: u16, text: &str) {
+    let w = to_wide(text);
+    let ctrl = GetDlgItem(parent, id as i32).unwrap_or_default();
+    let _ = SetWindowTextW(ctrl, windows::core::PCWSTR(w.as_ptr()));
+}
+
+/// ダイアログなどで書き換えたコントロールの値をまとめて保存する
+unsafe fn save_controls(hwnd: HWND, state: &mut SettingsState, ids: &[u16]) {
+    let mut new_cfg = state.config.clone();
+    let applied = ids
+        .iter()
+        .try_for_each(|&id| apply_control_to_config(hwnd, id, &mut new_cfg));
+    match applied.and_then(|()| persist_and_notify(state, &mut new_cfg)) {
+        Ok(()) => {
+            state.config = new_cfg;
+            state.draft_config = state.config.clone();
+            set_status(state, "Saved.");
+            if let Some(tx) = &state.notify_tx {
+                let _ = tx.try_send(InputEvent::PreviewConfig {
+                    config: state.draft_config.clone(),
+                });
+            }
+        }
+        Err(e) => {
+            set_status(state, &format!("Save failed: {e}"));
+            rebuild_category(hwnd, state);
+        }
+    }
+}
+
 unsafe fn set_status(state: &SettingsState, msg: &str) {
     let w = to_wide(msg);
     let _ = SetWindowTextW(state.status, windows::core::PCWSTR(w.as_ptr()));
@@ -344,7 +874,7 @@ unsafe fn rebuild_category(hwnd: HWND, state: &mut SettingsState) {
     state.rebuilding = true;
     state.draft_config = state.config.clone();
 
-    for ctrl in state.dynamic_controls.drain(..) {
+    for (ctrl, _) in state.dynamic_controls.drain(..) {
         let _ = DestroyWindow(ctrl);
     }
 
@@ -352,12 +882,56 @@ unsafe fn rebuild_category(hwnd: HWND, state: &mut SettingsState) {
     let mut y = 24;
     match state.category {
         Category::General => {
-            add_edit_row(
+            add_edit_button_row(
                 hwnd,
                 state,
                 "Toggle hotkey",
                 ID_HOTKEY_TOGGLE,
                 &cfg.hotkey.toggle,
+                "Record…",
+                ID_HOTKEY_RECORD,
+                &mut y,
+            );
+            add_combo_row(
+                hwnd,
+                state,
+                "Toggle hotkey mode",
+                ID_HOTKEY_MODE,
+                &["toggle", "hold-to-show"],
+                hotkey_mode_index(cfg.hotkey.mode),
+                &mut y,
+            );
+            add_multiline_row(
+                hwnd,
+                state,
+                "Extra toggle hotkeys (one per line)",
+                ID_HOTKEY_EXTRA_TOGGLES,
+                &cfg.hotkey.extra_toggles.join("\r\n"),
+                &mut y,
+                60,
+            );
+            add_edit_row(
+                hwnd,
+                state,
+                "Panic hide hotkey (empty = off)",
+                ID_HOTKEY_PANIC_HIDE,
+                &cfg.hotkey.panic_hide,
+                &mut y,
+            );
+            add_edit_row(
+                hwnd,
+                state,
+                "Screenshot hotkey (empty = off)",
+                ID_HOTKEY_SCREENSHOT,
+                &cfg.hotkey.screenshot,
+                &mut y,
+            );
+            add_edit_row(
+                hwnd,
+                state,
+                "Privacy toggle hotkey (empty = off)",
+                ID_HOTKEY_PRIVACY_TOGGLE,
+                &cfg.hotkey.privacy_toggle,
                 &mut y,
             );
             add_multiline_row(
@@ -376,19 +950,62 @@ unsafe fn rebuild_category(hwnd: HWND, state: &mut SettingsState) {
                 state,
                 "Position",
                 ID_DISPLAY_POSITION,
-                &["top-left", "top-center", "top-right", "bottom-left", "bottom-center", "bottom-right"],
+                &["top-left", "top-center", "top-right", "bottom-left", "bottom-center", "bottom-right", "custom"],
                 position_index(cfg.display.position),
                 &mut y,
             );
+            if let Position::Custom { x_pct, y_pct } = cfg.display.position {
+                add_edit_row(hwnd, state, "X%", ID_DISPLAY_CUSTOM_X, &format_percent(x_pct), &mut y);
+                add_edit_row(hwnd, state, "Y%", ID_DISPLAY_CUSTOM_Y, &format_percent(y_pct), &mut y);
+            }
             add_edit_row(hwnd, state, "Offset X", ID_DISPLAY_OFFSET_X, &cfg.display.offset_x.to_string(), &mut y);
             add_edit_row(hwnd, state, "Offset Y", ID_DISPLAY_OFFSET_Y, &cfg.display.offset_y.to_string(), &mut y);
             add_edit_row(hwnd, state, "Max items", ID_DISPLAY_MAX_ITEMS, &cfg.display.max_items.to_string(), &mut y);
             add_edit_row(hwnd, state, "Display duration (ms)", ID_DISPLAY_DURATION, &cfg.display.display_duration_ms.to_string(), &mut y);
             add_edit_row(hwnd, state, "Fade duration (ms)", ID_DISPLAY_FADE, &cfg.display.fade_duration_ms.to_string(), &mut y);
+            add_combo_row(
+                hwnd,
+                state,
+                "Layout",
+                ID_DISPLAY_LAYOUT,
+                &["vertical", "horizontal"],
+                osd_layout_index(cfg.display.layout),
+                &mut y,
+            );
+            add_check_row(hwnd, state, "Auto size (fit to content)", ID_DISPLAY_AUTO_SIZE, cfg.display.auto_size, &mut y);
+            add_edit_row(hwnd, state, "Snap to edge (px, 0 = off)", ID_DISPLAY_SNAP_TO_EDGE, &cfg.display.snap_to_edge_px.to_string(), &mut y);
+            add_button_row(hwnd, state, "Saved position (dragged)", "Reset", ID_DISPLAY_RESET_POSITION, &mut y);
+            add_combo_row(
+                hwnd,
+                state,
+                "Follow",
+                ID_DISPLAY_FOLLOW,
+                &["foreground-window", "mouse-cursor", "fixed"],
+                follow_mode_index(cfg.display.follow),
+                &mut y,
+            );
+            add_multiline_row(
+                hwnd,
+                state,
+                "Enabled monitors (one per line, empty = all)",
+                ID_DISPLAY_ENABLED_MONITORS,
+                &cfg.display.enabled_monitors.join("\r\n"),
+                &mut y,
+                60,
+            );
+            let connected = ystrokey_render::connected_monitor_names().join(", ");
+            add_row_label(hwnd, state, &format!("Connected: {}", connected), Column::Full, y);
         }
         Category::Style => {
-            add_edit_row(hwnd, state, "Font family", ID_STYLE_FONT_FAMILY, &cfg.style.font_family, &mut y);
+            add_edit_button_row(hwnd, state, "Font family", ID_STYLE_FONT_FAMILY, &cfg.style.font_family, "Browse…", ID_STYLE_FONT_BROWSE, &mut y);
+            add_edit_row(hwnd, state, "Custom font file (.ttf/.otf)", ID_STYLE_CUSTOM_FONT_PATH, cfg.style.custom_font_path.as_deref().unwrap_or(""), &mut y);
+            add_edit_row(hwnd, state, "Fallback font (CJK)", ID_STYLE_FALLBACK_FONT, &cfg.style.fallback_font_family, &mut y);
             add_edit_row(hwnd, state, "Font size", ID_STYLE_FONT_SIZE, &cfg.style.font_size.to_string(), &mut y);
+            add_edit_row(hwnd, state, "Key font weight (1-999)", ID_STYLE_KEY_FONT_WEIGHT, &cfg.style.key_font_weight.to_string(), &mut y);
+            add_check_row(hwnd, state, "Key italic", ID_STYLE_KEY_FONT_ITALIC, cfg.style.key_font_italic, &mut y);
+            add_edit_row(hwnd, state, "IME font weight (1-999)", ID_STYLE_IME_FONT_WEIGHT, &cfg.style.ime_font_weight.to_string(), &mut y);
+            add_check_row(hwnd, state, "IME italic", ID_STYLE_IME_FONT_ITALIC, cfg.style.ime_font_italic, &mut y);
+            add_edit_row(hwnd, state, "Clipboard font weight (1-999)", ID_STYLE_CLIPBOARD_FONT_WEIGHT, &cfg.style.clipboard_font_weight.to_string(), &mut y);
             add_edit_row(hwnd, state, "Text color", ID_STYLE_TEXT_COLOR, &cfg.style.text_color, &mut y);
             add_edit_row(hwnd, state, "Background color", ID_STYLE_BACKGROUND_COLOR, &cfg.style.background_color, &mut y);
             add_edit_row(hwnd, state, "Border radius", ID_STYLE_BORDER_RADIUS, &cfg.style.border_radius.to_string(), &mut y);
@@ -396,6 +1013,17 @@ unsafe fn rebuild_category(hwnd: HWND, state: &mut SettingsState) {
             add_edit_row(hwnd, state, "Shortcut color", ID_STYLE_SHORTCUT_COLOR, &cfg.style.shortcut_color, &mut y);
             add_edit_row(hwnd, state, "Key down color", ID_STYLE_KEY_DOWN_COLOR, &cfg.style.key_down_color, &mut y);
             add_edit_row(hwnd, state, "Opacity (0-1)", ID_STYLE_OPACITY, &cfg.style.opacity.to_string(), &mut y);
+            add_check_row(hwnd, state, "Keycap style", ID_STYLE_KEYCAP, cfg.style.keycap_style, &mut y);
+            add_check_row(hwnd, state, "Color emoji fonts", ID_STYLE_COLOR_FONTS, cfg.style.enable_color_fonts, &mut y);
+            add_combo_row(
+                hwnd,
+                state,
+                "Text rendering",
+                ID_STYLE_TEXT_RENDERING,
+                &["default", "cleartype", "grayscale", "aliased"],
+                text_rendering_mode_index(cfg.style.text_rendering_mode),
+                &mut y,
+            );
         }
         Category::Input => {
             add_combo_row(
@@ -403,18 +1031,70 @@ unsafe fn rebuild_category(hwnd: HWND, state: &mut SettingsState) {
                 state,
                 "Key transition display",
                 ID_BEHAVIOR_KEY_TRANSITION_MODE,
-                &["single-cell", "split-cells"],
+                &["single-cell", "split-cells", "simultaneous-display"],
                 key_transition_mode_index(cfg.behavior.key_transition_mode),
                 &mut y,
             );
             add_check_row(hwnd, state, "Show repeat count", ID_BEHAVIOR_SHOW_REPEAT_COUNT, cfg.behavior.show_repeat_count, &mut y);
             add_check_row(hwnd, state, "Distinguish numpad", ID_BEHAVIOR_DISTINGUISH_NUMPAD, cfg.behavior.distinguish_numpad, &mut y);
+            add_check_row(hwnd, state, "Distinguish left/right modifiers", ID_BEHAVIOR_DISTINGUISH_LR_MODIFIERS, cfg.behavior.distinguish_lr_modifiers, &mut y);
+            add_check_row(hwnd, state, "Show gamepad buttons", ID_BEHAVIOR_SHOW_GAMEPAD, cfg.behavior.show_gamepad, &mut y);
+            add_check_row(
+                hwnd,
+                state,
+                "Show held modifiers",
+                ID_BEHAVIOR_SHOW_MODIFIER_STATE,
+                cfg.behavior.show_modifier_state,
+                &mut y,
+            );
+            add_combo_row(
+                hwnd,
+                state,
+                "Mouse input (restart)",
+                ID_BEHAVIOR_MOUSE_INPUT_MODE,
+                &["off", "hook", "raw-input", "both"],
+                mouse_input_mode_index(cfg.behavior.mouse_input_mode),
+                &mut y,
+            );
+            add_check_row(hwnd, state, "Show mouse drags", ID_BEHAVIOR_SHOW_MOUSE_DRAG, cfg.behavior.show_mouse_drag, &mut y);
+            add_edit_row(hwnd, state, "Drag min distance (px)", ID_BEHAVIOR_DRAG_MIN_PIXELS, &cfg.behavior.drag_min_pixels.to_string(), &mut y);
             add_check_row(hwnd, state, "Show Shift badge for typed symbols (e.g. Shift+\")", ID_BEHAVIOR_SHIFT_TYPED_SYMBOLS, cfg.behavior.show_shift_for_typed_symbols, &mut y);
             add_check_row(hwnd, state, "Show IME composition", ID_BEHAVIOR_SHOW_IME, cfg.behavior.show_ime_composition, &mut y);
+            add_check_row(hwnd, state, "Show IME candidates", ID_BEHAVIOR_SHOW_IME_CANDIDATES, cfg.behavior.show_ime_candidates, &mut y);
+            add_edit_row(hwnd, state, "IME candidate max items", ID_BEHAVIOR_IME_CANDIDATE_MAX, &cfg.behavior.ime_candidate_max_items.to_string(), &mut y);
+            add_check_row(hwnd, state, "Flash committed IME text", ID_BEHAVIOR_SHOW_IME_COMMIT, cfg.behavior.show_ime_commit, &mut y);
+            add_combo_row(
+                hwnd,
+                state,
+                "IME display mode",
+                ID_BEHAVIOR_IME_DISPLAY_MODE,
+                &["auto", "hiragana", "katakana"],
+                ime_display_mode_index(cfg.behavior.ime_display_mode),
+                &mut y,
+            );
             add_check_row(hwnd, state, "Show clipboard", ID_BEHAVIOR_SHOW_CLIPBOARD, cfg.behavior.show_clipboard, &mut y);
+            add_check_row(hwnd, state, "Show clipboard source app", ID_BEHAVIOR_SHOW_CLIPBOARD_SOURCE, cfg.behavior.show_clipboard_source, &mut y);
             add_edit_row(hwnd, state, "Clipboard max chars", ID_BEHAVIOR_CLIPBOARD_MAX_CHARS, &cfg.behavior.clipboard_max_chars.to_string(), &mut y);
+            add_edit_row(hwnd, state, "Clipboard debounce (ms)", ID_BEHAVIOR_CLIPBOARD_DEBOUNCE, &cfg.behavior.clipboard_debounce_ms.to_string(), &mut y);
             add_check_row(hwnd, state, "Show lock indicators", ID_BEHAVIOR_SHOW_LOCK, cfg.behavior.show_lock_indicators, &mut y);
+            add_check_row(
+                hwnd,
+                state,
+                "Keep lock indicator while Caps/Num Lock is on",
+                ID_BEHAVIOR_PERSIST_LOCK,
+                cfg.behavior.persist_lock_indicator,
+                &mut y,
+            );
             add_edit_row(hwnd, state, "Repeat timeout (ms)", ID_BEHAVIOR_REPEAT_TIMEOUT, &cfg.behavior.repeat_timeout_ms.to_string(), &mut y);
+            add_multiline_row(
+                hwnd,
+                state,
+                "Per-key repeat timeout (key=ms, one per line)",
+                ID_BEHAVIOR_PER_KEY_REPEAT_TIMEOUT,
+                &key_timeouts_to_text(&cfg.behavior.per_key_repeat_timeout),
+                &mut y,
+                60,
+            );
             add_edit_row(hwnd, state, "Group timeout (ms)", ID_BEHAVIOR_GROUP_TIMEOUT, &cfg.behavior.group_timeout_ms.to_string(), &mut y);
             add_edit_row(hwnd, state, "Max group size", ID_BEHAVIOR_MAX_GROUP_SIZE, &cfg.behavior.max_group_size.to_string(), &mut y);
             add_check_row(hwnd, state, "Exclude from capture", ID_BEHAVIOR_EXCLUDE_CAPTURE, cfg.behavior.exclude_from_capture, &mut y);
@@ -427,9 +1107,35 @@ unsafe fn rebuild_category(hwnd: HWND, state: &mut SettingsState) {
                 &mut y,
                 100,
             );
+            add_multiline_row(
+                hwnd,
+                state,
+                "Pinned item kinds (e.g. LockIndicator, ModifierState; one per line)",
+                ID_BEHAVIOR_PINNED_ITEM_KINDS,
+                &cfg.behavior.pinned_item_kinds.join("\r\n"),
+                &mut y,
+                60,
+            );
         }
         Category::Privacy => {
             add_check_row(hwnd, state, "Privacy filter enabled", ID_PRIVACY_ENABLED, cfg.privacy.enabled, &mut y);
+            add_combo_row(
+                hwnd,
+                state,
+                "Mode",
+                ID_PRIVACY_MODE,
+                &["blocklist", "allowlist"],
+                privacy_mode_index(cfg.privacy.mode),
+                &mut y,
+            );
+            add_edit_row(
+                hwnd,
+                state,
+                "Privacy hotkey auto-resume (ms, 0 = never)",
+                ID_BEHAVIOR_PRIVACY_OVERRIDE_TIMEOUT,
+                &cfg.behavior.privacy_override_timeout_ms.to_string(),
+                &mut y,
+            );
             add_multiline_row(
                 hwnd,
                 state,
@@ -439,13 +1145,61 @@ unsafe fn rebuild_category(hwnd: HWND, state: &mut SettingsState) {
                 &mut y,
                 200,
             );
+            add_multiline_row(
+                hwnd,
+                state,
+                "Allowed process names (allowlist mode, one .exe per line)",
+                ID_PRIVACY_ALLOWED_APPS,
+                &cfg.privacy.allowed_apps.join("\r\n"),
+                &mut y,
+                100,
+            );
+            add_multiline_row(
+                hwnd,
+                state,
+                "Blocked window title substrings (one per line)",
+                ID_PRIVACY_BLOCKED_TITLES,
+                &cfg.privacy.blocked_title_substrings.join("\r\n"),
+                &mut y,
+                100,
+            );
+            add_multiline_row(
+                hwnd,
+                state,
+                "Mask clipboard patterns (* ? wildcards, @random-token)",
+                ID_PRIVACY_MASK_CLIPBOARD,
+                &cfg.privacy.mask_clipboard_patterns.join("\r\n"),
+                &mut y,
+                100,
+            );
+            add_multiline_row(
+                hwnd,
+                state,
+                "Clipboard redact patterns (regex, one per line)",
+                ID_PRIVACY_REDACT_PATTERNS,
+                &cfg.privacy.clipboard_redact_patterns.join("\r\n"),
+                &mut y,
+                100,
+            );
+            add_edit_row(
+                hwnd,
+                state,
+                "Redact replacement",
+                ID_PRIVACY_REDACT_REPLACEMENT,
+                &cfg.privacy.redact_replacement,
+                &mut y,
+            );
         }
         Category::Performance => {
             add_edit_row(hwnd, state, "OSD width", ID_PERF_OSD_WIDTH, &cfg.performance.osd_width.to_string(), &mut y);
             add_edit_row(hwnd, state, "OSD height", ID_PERF_OSD_HEIGHT, &cfg.performance.osd_height.to_string(), &mut y);
             add_edit_row(hwnd, state, "IME poll interval (ms)", ID_PERF_IME_POLL, &cfg.performance.ime_poll_interval_ms.to_string(), &mut y);
             add_edit_row(hwnd, state, "Frame interval (ms)", ID_PERF_FRAME_INTERVAL, &cfg.performance.frame_interval_ms.to_string(), &mut y);
-            add_edit_row(hwnd, state, "Config reload interval (ms)", ID_PERF_RELOAD_INTERVAL, &cfg.performance.config_reload_interval_ms.to_string(), &mut y);
+            add_edit_row(hwnd, state, "Fade-only frame interval (ms)", ID_PERF_FADE_FRAME_INTERVAL, &cfg.performance.fade_frame_interval_ms.to_string(), &mut y);
+            add_edit_row(hwnd, state, "Config reload debounce (ms)", ID_PERF_RELOAD_INTERVAL, &cfg.performance.config_reload_interval_ms.to_string(), &mut y);
+            add_edit_row(hwnd, state, "HTTP server port (empty = off, restart)", ID_PERF_HTTP_PORT, &optional_to_string(cfg.performance.http_server_port), &mut y);
+            add_edit_row(hwnd, state, "WebSocket port (empty = off, restart)", ID_PERF_WS_PORT, &optional_to_string(cfg.performance.websocket_port), &mut y);
+            add_edit_row(hwnd, state, "Allowed browser origin (HTTP/WebSocket, empty = none, restart)", ID_PERF_HTTP_ALLOWED_ORIGIN, cfg.performance.http_allowed_origin.as_deref().unwrap_or(""), &mut y);
         }
         Category::Diagnostics => {
             add_combo_row(
@@ -453,13 +1207,33 @@ unsafe fn rebuild_category(hwnd: HWND, state: &mut SettingsState) {
                 state,
                 "Level",
                 ID_DIAG_LEVEL,
-                &["error", "warn", "info", "debug"],
+                &["off", "error", "warn", "info", "debug"],
                 diag_level_index(cfg.diagnostics.level),
                 &mut y,
             );
             add_check_row(hwnd, state, "Enable file logging", ID_DIAG_FILE_ENABLED, cfg.diagnostics.file_logging_enabled, &mut y);
             add_edit_row(hwnd, state, "Max file bytes", ID_DIAG_MAX_BYTES, &cfg.diagnostics.max_file_bytes.to_string(), &mut y);
             add_edit_row(hwnd, state, "Max files", ID_DIAG_MAX_FILES, &cfg.diagnostics.max_files.to_string(), &mut y);
+            add_combo_row(
+                hwnd,
+                state,
+                "Log format",
+                ID_DIAG_LOG_FORMAT,
+                &["text", "json"],
+                log_format_index(cfg.diagnostics.log_format),
+                &mut y,
+            );
+            add_combo_row(
+                hwnd,
+                state,
+                "Log timestamp",
+                ID_DIAG_TIMESTAMP,
+                &["epoch", "local-iso"],
+                log_timestamp_index(cfg.diagnostics.timestamp),
+                &mut y,
+            );
+            add_check_row(hwnd, state, "Record input events", ID_DIAG_RECORD_EVENTS, cfg.diagnostics.record_events, &mut y);
+            add_edit_row(hwnd, state, "Events file (empty = logs/events.jsonl)", ID_DIAG_EVENTS_FILE, cfg.diagnostics.events_file.as_deref().unwrap_or(""), &mut y);
         }
         Category::Startup => {
             add_check_row(hwnd, state, "Enable autostart", ID_STARTUP_AUTOSTART, cfg.startup.autostart_enabled, &mut y);
@@ -471,8 +1245,8 @@ unsafe fn rebuild_category(hwnd: HWND, state: &mut SettingsState) {
                 state,
                 "Menu language",
                 ID_TRAY_MENU_LANGUAGE,
-                &["ja", "en"],
-                if cfg.tray.menu_language == MenuLanguage::Ja { 0 } else { 1 },
+                &["ja", "en", "ko"],
+                menu_language_index(cfg.tray.menu_language),
                 &mut y,
             );
             add_check_row(hwnd, state, "Confirm on exit", ID_TRAY_CONFIRM_EXIT, cfg.tray.confirm_on_exit, &mut y);
@@ -499,6 +1273,14 @@ unsafe fn rebuild_category(hwnd: HWND, state: &mut SettingsState) {
                 &mut y,
             );
         }
+        Category::Logs => {
+            // カテゴリを開くたびに最新の内容で作り直す
+            let text = crate::logger::recent_lines().join("\r\n");
+            add_multiline_row(hwnd, state, "Recent log lines (oldest first)", ID_LOGS_RECENT, &text, &mut y, 420);
+            if let Ok(edit) = GetDlgItem(hwnd, ID_LOGS_RECENT as i32) {
+                SendMessageW(edit, EM_SETREADONLY, WPARAM(1), LPARAM(0));
+            }
+        }
     }
 
     state.rebuilding = false;
@@ -529,7 +1311,8 @@ unsafe fn create_label(parent: HWND, text: &str, x: i32, y: i32, w: i32, h: i32)
     .unwrap_or_default()
 }
 
-unsafe fn create_edit(parent: HWND, id: u16, value: &str, x: i32, y: i32, w: i32, h: i32) -> HWND {
+unsafe fn create_edit(parent: HWND, id: u16, value: &str, layout: &PaneLayout, placement: Placement) -> HWND {
+    let (x, y, w, h) = layout.rect(placement);
     let wide = to_wide(value);
     CreateWindowExW(
         WS_EX_CLIENTEDGE,
@@ -552,11 +1335,10 @@ unsafe fn create_multiline_edit(
     parent: HWND,
     id: u16,
     value: &str,
-    x: i32,
-    y: i32,
-    w: i32,
-    h: i32,
+    layout: &PaneLayout,
+    placement: Placement,
 ) -> HWND {
+    let (x, y, w, h) = layout.rect(placement);
     let wide = to_wide(value);
     CreateWindowExW(
         WS_EX_CLIENTEDGE,
@@ -579,7 +1361,15 @@ unsafe fn create_multiline_edit(
     .unwrap_or_default()
 }
 
-unsafe fn create_checkbox(parent: HWND, id: u16, text: &str, checked: bool, x: i32, y: i32, w: i32, h: i32) -> HWND {
+unsafe fn create_checkbox(
+    parent: HWND,
+    id: u16,
+    text: &str,
+    checked: bool,
+    layout: &PaneLayout,
+    placement: Placement,
+) -> HWND {
+    let (x, y, w, h) = layout.rect(placement);
     let wide = to_wide(text);
     let hwnd = CreateWindowExW(
         WINDOW_EX_STYLE::default(),
@@ -607,11 +1397,10 @@ unsafe fn create_combo(
     id: u16,
     options: &[&str],
     selected_idx: i32,
-    x: i32,
-    y: i32,
-    w: i32,
-    h: i32,
+    layout: &PaneLayout,
+    placement: Placement,
 ) -> HWND {
+    let (x, y, w, h) = layout.rect(placement);
     let hwnd = CreateWindowExW(
         WINDOW_EX_STYLE::default(),
         windows::core::w!("COMBOBOX"),
@@ -650,10 +1439,65 @@ unsafe fn add_edit_row(
     value: &str,
     y: &mut i32,
 ) {
-    let l = create_label(hwnd, label, 250, *y, 220, 22);
-    let e = create_edit(hwnd, id, value, 480, *y - 2, 340, 24);
-    state.dynamic_controls.push(l);
-    state.dynamic_controls.push(e);
+    add_row_label(hwnd, state, label, Column::Label, *y);
+    let placement = Placement {
+        column: Column::Field { max_width: 0 },
+        y: *y - 2,
+        height: 24,
+    };
+    let e = create_edit(hwnd, id, value, &state.layout, placement);
+    state.dynamic_controls.push((e, placement));
+    *y += 30;
+}
+
+#[allow(clippy::too_many_arguments)]
+unsafe fn add_edit_button_row(
+    hwnd: HWND,
+    state: &mut SettingsState,
+    label: &str,
+    id: u16,
+    value: &str,
+    button_text: &str,
+    button_id: u16,
+    y: &mut i32,
+) {
+    add_row_label(hwnd, state, label, Column::Label, *y);
+    let placement = Placement {
+        column: Column::FieldBeforeButton,
+        y: *y - 2,
+        height: 24,
+    };
+    let e = create_edit(hwnd, id, value, &state.layout, placement);
+    state.dynamic_controls.push((e, placement));
+    let placement = Placement {
+        column: Column::RowButton,
+        y: *y - 3,
+        height: 26,
+    };
+    let (x, by, w, h) = state.layout.rect(placement);
+    let b = create_button(hwnd, button_text, button_id, x, by, w, h);
+    state.dynamic_controls.push((b, placement));
+    *y += 30;
+}
+
+/// ラベルとボタンだけの行（入力欄なし）
+unsafe fn add_button_row(
+    hwnd: HWND,
+    state: &mut SettingsState,
+    label: &str,
+    button_text: &str,
+    button_id: u16,
+    y: &mut i32,
+) {
+    add_row_label(hwnd, state, label, Column::Label, *y);
+    let placement = Placement {
+        column: Column::RowButton,
+        y: *y - 3,
+        height: 26,
+    };
+    let (x, by, w, h) = state.layout.rect(placement);
+    let b = create_button(hwnd, button_text, button_id, x, by, w, h);
+    state.dynamic_controls.push((b, placement));
     *y += 30;
 }
 
@@ -666,10 +1510,14 @@ unsafe fn add_multiline_row(
     y: &mut i32,
     height: i32,
 ) {
-    let l = create_label(hwnd, label, 250, *y, 560, 22);
-    let e = create_multiline_edit(hwnd, id, value, 250, *y + 22, 570, height);
-    state.dynamic_controls.push(l);
-    state.dynamic_controls.push(e);
+    add_row_label(hwnd, state, label, Column::Full, *y);
+    let placement = Placement {
+        column: Column::Full,
+        y: *y + 22,
+        height,
+    };
+    let e = create_multiline_edit(hwnd, id, value, &state.layout, placement);
+    state.dynamic_controls.push((e, placement));
     *y += height + 36;
 }
 
@@ -681,8 +1529,13 @@ unsafe fn add_check_row(
     checked: bool,
     y: &mut i32,
 ) {
-    let c = create_checkbox(hwnd, id, label, checked, 250, *y, 480, 24);
-    state.dynamic_controls.push(c);
+    let placement = Placement {
+        column: Column::Full,
+        y: *y,
+        height: 24,
+    };
+    let c = create_checkbox(hwnd, id, label, checked, &state.layout, placement);
+    state.dynamic_controls.push((c, placement));
     *y += 30;
 }
 
@@ -695,12 +1548,85 @@ unsafe fn add_combo_row(
     selected_idx: i32,
     y: &mut i32,
 ) {
-    let l = create_label(hwnd, label, 250, *y, 220, 22);
-    let c = create_combo(hwnd, id, options, selected_idx, 480, *y - 2, 220, 300);
-    state.dynamic_controls.push(l);
-    state.dynamic_controls.push(c);
+    add_row_label(hwnd, state, label, Column::Label, *y);
+    let placement = Placement {
+        column: Column::Field { max_width: 220 },
+        y: *y - 2,
+        height: 300,
+    };
+    let c = create_combo(hwnd, id, options, selected_idx, &state.layout, placement);
+    state.dynamic_controls.push((c, placement));
     *y += 30;
 }
+
+unsafe fn add_row_label(hwnd: HWND, state: &mut SettingsState, label: &str, column: Column, y: i32) {
+    let placement = Placement {
+        column,
+        y,
+        height: 22,
+    };
+    let (x, y, w, h) = state.layout.rect(placement);
+    let l = create_label(hwnd, label, x, y, w, h);
+    state.dynamic_controls.push((l, placement));
+}
+
+/// 検索欄の内容でナビゲーションを絞り込み、表示中のカテゴリが残っていれば選択し直す
+unsafe fn refill_nav(state: &mut SettingsState) {
+    state.nav_categories = matching_categories(&get_text(state.search));
+    let _ = SendMessageW(state.nav, LB_RESETCONTENT, WPARAM(0), LPARAM(0));
+    for (category, (name, _)) in Category::ALL.into_iter().zip(category_search_table()) {
+        if state.nav_categories.contains(&category) {
+            let w = to_wide(name);
+            let _ = SendMessageW(state.nav, LB_ADDSTRING, WPARAM(0), LPARAM(w.as_ptr() as isize));
+        }
+    }
+    // 一致しなければ選択なし（LB_SETCURSEL に -1）
+    let selected = state
+        .nav_categories
+        .iter()
+        .position(|&c| c == state.category)
+        .unwrap_or(usize::MAX);
+    let _ = SendMessageW(state.nav, LB_SETCURSEL, WPARAM(selected), LPARAM(0));
+}
+
+/// クライアント領域の大きさに合わせて全コントロールを配置し直す
+unsafe fn relayout(hwnd: HWND, new_width: i32, new_height: i32) {
+    let ptr = GetWindowLongPtrW(hwnd, GWLP_USERDATA) as *mut SettingsState;
+    if ptr.is_null() {
+        return;
+    }
+    let state = &mut *ptr;
+    state.layout = PaneLayout {
+        client_width: new_width,
+        client_height: new_height,
+    };
+    let layout = state.layout;
+    let footer_top = layout.footer_top();
+    let pane_left = layout.pane_left();
+    let pane_width = layout.pane_width();
+
+    let _ = MoveWindow(state.search, MARGIN, MARGIN, layout.nav_width(), 24, true);
+    let nav_top = MARGIN + 30;
+    let _ = MoveWindow(state.nav, MARGIN, nav_top, layout.nav_width(), footer_top - nav_top, true);
+    let buttons = [
+        (ID_BTN_UNDO, MARGIN, 70),
+        (ID_BTN_REDO, MARGIN + 80, 70),
+        (ID_BTN_REVERT_SECTION, pane_left, 140),
+        (ID_BTN_RESET_ALL, pane_left + 150, 140),
+        (ID_BTN_CLOSE, pane_left + pane_width - 80, 80),
+    ];
+    for (id, x, w) in buttons {
+        if let Ok(button) = GetDlgItem(hwnd, id as i32) {
+            let _ = MoveWindow(button, x, footer_top + 10, w, 32, true);
+        }
+    }
+    let _ = MoveWindow(state.status, pane_left, footer_top + 48, pane_width, 20, true);
+
+    for &(ctrl, placement) in &state.dynamic_controls {
+        let (x, y, w, h) = layout.rect(placement);
+        let _ = MoveWindow(ctrl, x, y, w, h, true);
+    }
+}
 unsafe fn get_text(hwnd: HWND) -> String {
     let mut buf = vec![0u16; 4096];
     let len = GetWindowTextW(hwnd, &mut buf) as usize;
@@ -747,6 +1673,22 @@ unsafe fn get_edit_f32(parent: HWND, id: u16) -> Result<f32, String> {
         .map_err(|_| format!("id {} expects f32", id))
 }
 
+unsafe fn get_edit_optional_u16(parent: HWND, id: u16) -> Result<Option<u16>, String> {
+    let hwnd = GetDlgItem(parent, id as i32).unwrap_or_default();
+    let text = get_text(hwnd);
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+    text.parse::<u16>()
+        .map(Some)
+        .map_err(|_| format!("id {} expects u16 or empty", id))
+}
+
+fn optional_to_string<T: ToString>(value: Option<T>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
 unsafe fn get_edit_string(parent: HWND, id: u16) -> String {
     let hwnd = GetDlgItem(parent, id as i32).unwrap_or_default();
     get_text(hwnd).trim().to_string()
@@ -770,6 +1712,20 @@ unsafe fn get_combo_index(parent: HWND, id: u16) -> Result<i32, String> {
 unsafe fn apply_control_to_config(parent: HWND, id: u16, cfg: &mut AppConfig) -> Result<(), String> {
     match id {
         ID_HOTKEY_TOGGLE => cfg.hotkey.toggle = get_edit_string(parent, id),
+        ID_HOTKEY_PANIC_HIDE => cfg.hotkey.panic_hide = get_edit_string(parent, id),
+        ID_HOTKEY_SCREENSHOT => cfg.hotkey.screenshot = get_edit_string(parent, id),
+        ID_HOTKEY_PRIVACY_TOGGLE => cfg.hotkey.privacy_toggle = get_edit_string(parent, id),
+        ID_HOTKEY_MODE => {
+            cfg.hotkey.mode = match get_combo_index(parent, id)? {
+                0 => HotkeyMode::Toggle,
+                1 => HotkeyMode::HoldToShow,
+                _ => return Err("invalid hotkey.mode".into()),
+            }
+        }
+        ID_HOTKEY_EXTRA_TOGGLES => {
+            let text = get_edit_string(parent, id);
+            cfg.hotkey.extra_toggles = split_lines(&text);
+        }
         ID_SHORTCUTS => {
             let text = get_edit_string(parent, id);
             cfg.shortcuts = parse_shortcuts(&text)?;
@@ -783,17 +1739,61 @@ unsafe fn apply_control_to_config(parent: HWND, id: u16, cfg: &mut AppConfig) ->
                 3 => Position::BottomLeft,
                 4 => Position::BottomCenter,
                 5 => Position::BottomRight,
+                6 => match cfg.display.position {
+                    custom @ Position::Custom { .. } => custom,
+                    _ => Position::Custom { x_pct: 0.5, y_pct: 0.5 },
+                },
                 _ => return Err("invalid display.position".into()),
             }
         }
+        ID_DISPLAY_CUSTOM_X | ID_DISPLAY_CUSTOM_Y => {
+            let Position::Custom { x_pct, y_pct } = &mut cfg.display.position else {
+                return Err("display.position is not custom".into());
+            };
+            let target = if id == ID_DISPLAY_CUSTOM_X { x_pct } else { y_pct };
+            *target = get_edit_f32(parent, id)? / 100.0;
+        }
         ID_DISPLAY_OFFSET_X => cfg.display.offset_x = get_edit_i32(parent, id)?,
         ID_DISPLAY_OFFSET_Y => cfg.display.offset_y = get_edit_i32(parent, id)?,
         ID_DISPLAY_MAX_ITEMS => cfg.display.max_items = get_edit_usize(parent, id)?,
         ID_DISPLAY_DURATION => cfg.display.display_duration_ms = get_edit_u64(parent, id)?,
         ID_DISPLAY_FADE => cfg.display.fade_duration_ms = get_edit_u64(parent, id)?,
+        ID_DISPLAY_LAYOUT => {
+            cfg.display.layout = match get_combo_index(parent, id)? {
+                0 => OsdLayout::Vertical,
+                1 => OsdLayout::Horizontal,
+                _ => return Err("invalid display.layout".into()),
+            }
+        }
+        ID_DISPLAY_AUTO_SIZE => cfg.display.auto_size = get_checkbox(parent, id),
+        ID_DISPLAY_SNAP_TO_EDGE => cfg.display.snap_to_edge_px = get_edit_u32(parent, id)?,
+        ID_DISPLAY_FOLLOW => {
+            cfg.display.follow = match get_combo_index(parent, id)? {
+                0 => FollowMode::ForegroundWindow,
+                1 => FollowMode::MouseCursor,
+                2 => FollowMode::Fixed,
+                _ => return Err("invalid display.follow".into()),
+            }
+        }
+        ID_DISPLAY_ENABLED_MONITORS => {
+            let text = get_edit_string(parent, id);
+            cfg.display.enabled_monitors = split_lines(&text);
+        }
 
         ID_STYLE_FONT_FAMILY => cfg.style.font_family = get_edit_string(parent, id),
+        ID_STYLE_FALLBACK_FONT => cfg.style.fallback_font_family = get_edit_string(parent, id),
+        ID_STYLE_CUSTOM_FONT_PATH => {
+            let path = get_edit_string(parent, id);
+            cfg.style.custom_font_path = if path.is_empty() { None } else { Some(path) };
+        }
         ID_STYLE_FONT_SIZE => cfg.style.font_size = get_edit_f32(parent, id)?,
+        ID_STYLE_KEY_FONT_WEIGHT => cfg.style.key_font_weight = get_edit_u32(parent, id)?,
+        ID_STYLE_IME_FONT_WEIGHT => cfg.style.ime_font_weight = get_edit_u32(parent, id)?,
+        ID_STYLE_CLIPBOARD_FONT_WEIGHT => cfg.style.clipboard_font_weight = get_edit_u32(parent, id)?,
+        ID_STYLE_KEY_FONT_ITALIC => cfg.style.key_font_italic = get_checkbox(parent, id),
+        ID_STYLE_IME_FONT_ITALIC => cfg.style.ime_font_italic = get_checkbox(parent, id),
+        ID_STYLE_KEYCAP => cfg.style.keycap_style = get_checkbox(parent, id),
+        ID_STYLE_COLOR_FONTS => cfg.style.enable_color_fonts = get_checkbox(parent, id),
         ID_STYLE_TEXT_COLOR => cfg.style.text_color = get_edit_string(parent, id),
         ID_STYLE_BACKGROUND_COLOR => cfg.style.background_color = get_edit_string(parent, id),
         ID_STYLE_BORDER_RADIUS => cfg.style.border_radius = get_edit_f32(parent, id)?,
@@ -801,54 +1801,156 @@ unsafe fn apply_control_to_config(parent: HWND, id: u16, cfg: &mut AppConfig) ->
         ID_STYLE_SHORTCUT_COLOR => cfg.style.shortcut_color = get_edit_string(parent, id),
         ID_STYLE_KEY_DOWN_COLOR => cfg.style.key_down_color = get_edit_string(parent, id),
         ID_STYLE_OPACITY => cfg.style.opacity = get_edit_f32(parent, id)?,
+        ID_STYLE_TEXT_RENDERING => {
+            cfg.style.text_rendering_mode = match get_combo_index(parent, id)? {
+                0 => TextRenderingMode::Default,
+                1 => TextRenderingMode::ClearType,
+                2 => TextRenderingMode::GrayscaleAA,
+                3 => TextRenderingMode::Aliased,
+                _ => return Err("invalid style.text_rendering_mode".into()),
+            }
+        }
 
         ID_BEHAVIOR_KEY_TRANSITION_MODE => {
             cfg.behavior.key_transition_mode = match get_combo_index(parent, id)? {
                 0 => KeyTransitionMode::SingleCell,
                 1 => KeyTransitionMode::SplitCells,
+                2 => KeyTransitionMode::SimultaneousDisplay,
                 _ => return Err("invalid behavior.key_transition_mode".into()),
             }
         }
         ID_BEHAVIOR_SHOW_REPEAT_COUNT => cfg.behavior.show_repeat_count = get_checkbox(parent, id),
         ID_BEHAVIOR_DISTINGUISH_NUMPAD => cfg.behavior.distinguish_numpad = get_checkbox(parent, id),
+        ID_BEHAVIOR_DISTINGUISH_LR_MODIFIERS => cfg.behavior.distinguish_lr_modifiers = get_checkbox(parent, id),
+        ID_BEHAVIOR_SHOW_GAMEPAD => cfg.behavior.show_gamepad = get_checkbox(parent, id),
+        ID_BEHAVIOR_SHOW_MODIFIER_STATE => {
+            cfg.behavior.show_modifier_state = get_checkbox(parent, id)
+        }
+        ID_BEHAVIOR_MOUSE_INPUT_MODE => {
+            cfg.behavior.mouse_input_mode = match get_combo_index(parent, id)? {
+                0 => MouseInputMode::Off,
+                1 => MouseInputMode::Hook,
+                2 => MouseInputMode::RawInput,
+                3 => MouseInputMode::Both,
+                _ => return Err("invalid behavior.mouse_input_mode".into()),
+            }
+        }
+        ID_BEHAVIOR_SHOW_MOUSE_DRAG => cfg.behavior.show_mouse_drag = get_checkbox(parent, id),
+        ID_BEHAVIOR_DRAG_MIN_PIXELS => cfg.behavior.drag_min_pixels = get_edit_f32(parent, id)?,
         ID_BEHAVIOR_SHIFT_TYPED_SYMBOLS => cfg.behavior.show_shift_for_typed_symbols = get_checkbox(parent, id),
         ID_BEHAVIOR_SHOW_IME => cfg.behavior.show_ime_composition = get_checkbox(parent, id),
+        ID_BEHAVIOR_SHOW_IME_CANDIDATES => cfg.behavior.show_ime_candidates = get_checkbox(parent, id),
+        ID_BEHAVIOR_IME_CANDIDATE_MAX => cfg.behavior.ime_candidate_max_items = get_edit_usize(parent, id)?,
+        ID_BEHAVIOR_SHOW_IME_COMMIT => cfg.behavior.show_ime_commit = get_checkbox(parent, id),
+        ID_BEHAVIOR_IME_DISPLAY_MODE => {
+            cfg.behavior.ime_display_mode = match get_combo_index(parent, id)? {
+                0 => ImeDisplayMode::Auto,
+                1 => ImeDisplayMode::Hiragana,
+                2 => ImeDisplayMode::Katakana,
+                _ => return Err("invalid behavior.ime_display_mode".into()),
+            }
+        }
         ID_BEHAVIOR_SHOW_CLIPBOARD => cfg.behavior.show_clipboard = get_checkbox(parent, id),
+        ID_BEHAVIOR_SHOW_CLIPBOARD_SOURCE => cfg.behavior.show_clipboard_source = get_checkbox(parent, id),
         ID_BEHAVIOR_CLIPBOARD_MAX_CHARS => cfg.behavior.clipboard_max_chars = get_edit_usize(parent, id)?,
+        ID_BEHAVIOR_CLIPBOARD_DEBOUNCE => cfg.behavior.clipboard_debounce_ms = get_edit_u64(parent, id)?,
         ID_BEHAVIOR_SHOW_LOCK => cfg.behavior.show_lock_indicators = get_checkbox(parent, id),
+        ID_BEHAVIOR_PERSIST_LOCK => cfg.behavior.persist_lock_indicator = get_checkbox(parent, id),
         ID_BEHAVIOR_REPEAT_TIMEOUT => cfg.behavior.repeat_timeout_ms = get_edit_u64(parent, id)?,
+        ID_BEHAVIOR_PER_KEY_REPEAT_TIMEOUT => {
+            let text = get_edit_string(parent, id);
+            cfg.behavior.per_key_repeat_timeout = parse_key_timeouts(&text)?;
+        }
         ID_BEHAVIOR_GROUP_TIMEOUT => cfg.behavior.group_timeout_ms = get_edit_u64(parent, id)?,
         ID_BEHAVIOR_MAX_GROUP_SIZE => cfg.behavior.max_group_size = get_edit_usize(parent, id)?,
         ID_BEHAVIOR_IGNORED_KEYS => {
             let text = get_edit_string(parent, id);
             cfg.behavior.ignored_keys = split_lines(&text);
         }
+        ID_BEHAVIOR_PINNED_ITEM_KINDS => {
+            let text = get_edit_string(parent, id);
+            cfg.behavior.pinned_item_kinds = split_lines(&text);
+        }
         ID_BEHAVIOR_EXCLUDE_CAPTURE => cfg.behavior.exclude_from_capture = get_checkbox(parent, id),
+        ID_BEHAVIOR_PRIVACY_OVERRIDE_TIMEOUT => {
+            cfg.behavior.privacy_override_timeout_ms = get_edit_u64(parent, id)?
+        }
 
         ID_PRIVACY_ENABLED => cfg.privacy.enabled = get_checkbox(parent, id),
         ID_PRIVACY_BLOCKED_APPS => {
             let text = get_edit_string(parent, id);
             cfg.privacy.blocked_apps = split_lines(&text);
         }
+        ID_PRIVACY_MODE => {
+            cfg.privacy.mode = match get_combo_index(parent, id)? {
+                0 => PrivacyMode::Blocklist,
+                1 => PrivacyMode::Allowlist,
+                _ => return Err("invalid privacy.mode".into()),
+            }
+        }
+        ID_PRIVACY_ALLOWED_APPS => {
+            let text = get_edit_string(parent, id);
+            cfg.privacy.allowed_apps = split_lines(&text);
+        }
+        ID_PRIVACY_BLOCKED_TITLES => {
+            let text = get_edit_string(parent, id);
+            cfg.privacy.blocked_title_substrings = split_lines(&text);
+        }
+        ID_PRIVACY_MASK_CLIPBOARD => {
+            let text = get_edit_string(parent, id);
+            cfg.privacy.mask_clipboard_patterns = split_lines(&text);
+        }
+        ID_PRIVACY_REDACT_PATTERNS => {
+            let text = get_edit_string(parent, id);
+            cfg.privacy.clipboard_redact_patterns = split_lines(&text);
+        }
+        ID_PRIVACY_REDACT_REPLACEMENT => cfg.privacy.redact_replacement = get_edit_string(parent, id),
 
         ID_PERF_OSD_WIDTH => cfg.performance.osd_width = get_edit_i32(parent, id)?,
         ID_PERF_OSD_HEIGHT => cfg.performance.osd_height = get_edit_i32(parent, id)?,
         ID_PERF_IME_POLL => cfg.performance.ime_poll_interval_ms = get_edit_u64(parent, id)?,
         ID_PERF_FRAME_INTERVAL => cfg.performance.frame_interval_ms = get_edit_u64(parent, id)?,
+        ID_PERF_FADE_FRAME_INTERVAL => cfg.performance.fade_frame_interval_ms = get_edit_u64(parent, id)?,
         ID_PERF_RELOAD_INTERVAL => cfg.performance.config_reload_interval_ms = get_edit_u64(parent, id)?,
+        ID_PERF_HTTP_PORT => cfg.performance.http_server_port = get_edit_optional_u16(parent, id)?,
+        ID_PERF_WS_PORT => cfg.performance.websocket_port = get_edit_optional_u16(parent, id)?,
+        ID_PERF_HTTP_ALLOWED_ORIGIN => {
+            let origin = get_edit_string(parent, id);
+            cfg.performance.http_allowed_origin = if origin.is_empty() { None } else { Some(origin) };
+        }
 
         ID_DIAG_LEVEL => {
             cfg.diagnostics.level = match get_combo_index(parent, id)? {
-                0 => DiagnosticsLevel::Error,
-                1 => DiagnosticsLevel::Warn,
-                2 => DiagnosticsLevel::Info,
-                3 => DiagnosticsLevel::Debug,
+                0 => DiagnosticsLevel::Off,
+                1 => DiagnosticsLevel::Error,
+                2 => DiagnosticsLevel::Warn,
+                3 => DiagnosticsLevel::Info,
+                4 => DiagnosticsLevel::Debug,
                 _ => return Err("invalid diagnostics.level".into()),
             }
         }
         ID_DIAG_FILE_ENABLED => cfg.diagnostics.file_logging_enabled = get_checkbox(parent, id),
         ID_DIAG_MAX_BYTES => cfg.diagnostics.max_file_bytes = get_edit_u64(parent, id)?,
         ID_DIAG_MAX_FILES => cfg.diagnostics.max_files = get_edit_u32(parent, id)?,
+        ID_DIAG_LOG_FORMAT => {
+            cfg.diagnostics.log_format = match get_combo_index(parent, id)? {
+                0 => LogFormat::Text,
+                1 => LogFormat::Json,
+                _ => return Err("invalid diagnostics.log_format".into()),
+            }
+        }
+        ID_DIAG_RECORD_EVENTS => cfg.diagnostics.record_events = get_checkbox(parent, id),
+        ID_DIAG_EVENTS_FILE => {
+            let path = get_edit_string(parent, id);
+            cfg.diagnostics.events_file = if path.is_empty() { None } else { Some(path) };
+        }
+        ID_DIAG_TIMESTAMP => {
+            cfg.diagnostics.timestamp = match get_combo_index(parent, id)? {
+                0 => LogTimestamp::Epoch,
+                1 => LogTimestamp::LocalIso,
+                _ => return Err("invalid diagnostics.timestamp".into()),
+            }
+        }
 
         ID_STARTUP_AUTOSTART => cfg.startup.autostart_enabled = get_checkbox(parent, id),
 
@@ -857,6 +1959,7 @@ unsafe fn apply_control_to_config(parent: HWND, id: u16, cfg: &mut AppConfig) ->
             cfg.tray.menu_language = match get_combo_index(parent, id)? {
                 0 => MenuLanguage::Ja,
                 1 => MenuLanguage::En,
+                2 => MenuLanguage::Ko,
                 _ => return Err("invalid tray.menu_language".into()),
             }
         }
@@ -929,6 +2032,85 @@ fn parse_shortcuts(text: &str) -> Result<Vec<ShortcutDef>, String> {
     Ok(shortcuts)
 }
 
+/// キー名で並べて `key=ms` 行にする（HashMap の順序に依存しないように）
+fn key_timeouts_to_text(timeouts: &HashMap<String, u64>) -> String {
+    let mut lines: Vec<String> = timeouts.iter().map(|(key, ms)| format!("{key}={ms}")).collect();
+    lines.sort();
+    lines.join("\r\n")
+}
+
+fn parse_key_timeouts(text: &str) -> Result<HashMap<String, u64>, String> {
+    let mut timeouts = HashMap::new();
+
+    for (i, line) in text.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        let Some((key, ms)) = trimmed.split_once('=') else {
+            return Err(format!("repeat timeout line {} must be key=ms", i + 1));
+        };
+
+        let key = key.trim();
+        if key.is_empty() {
+            return Err(format!("repeat timeout line {} must not be empty", i + 1));
+        }
+        let ms = ms
+            .trim()
+            .parse::<u64>()
+            .map_err(|_| format!("repeat timeout line {} must be a number of ms", i + 1))?;
+        timeouts.insert(key.to_string(), ms);
+    }
+
+    Ok(timeouts)
+}
+
+fn log_format_index(format: LogFormat) -> i32 {
+    match format {
+        LogFormat::Text => 0,
+        LogFormat::Json => 1,
+    }
+}
+
+fn log_timestamp_index(timestamp: LogTimestamp) -> i32 {
+    match timestamp {
+        LogTimestamp::Epoch => 0,
+        LogTimestamp::LocalIso => 1,
+    }
+}
+
+fn hotkey_mode_index(mode: HotkeyMode) -> i32 {
+    match mode {
+        HotkeyMode::Toggle => 0,
+        HotkeyMode::HoldToShow => 1,
+    }
+}
+
+fn mouse_input_mode_index(mode: MouseInputMode) -> i32 {
+    match mode {
+        MouseInputMode::Off => 0,
+        MouseInputMode::Hook => 1,
+        MouseInputMode::RawInput => 2,
+        MouseInputMode::Both => 3,
+    }
+}
+
+fn ime_display_mode_index(mode: ImeDisplayMode) -> i32 {
+    match mode {
+        ImeDisplayMode::Auto => 0,
+        ImeDisplayMode::Hiragana => 1,
+        ImeDisplayMode::Katakana => 2,
+    }
+}
+
+fn privacy_mode_index(mode: PrivacyMode) -> i32 {
+    match mode {
+        PrivacyMode::Blocklist => 0,
+        PrivacyMode::Allowlist => 1,
+    }
+}
+
 fn position_index(pos: Position) -> i32 {
     match pos {
         Position::TopLeft => 0,
@@ -937,6 +2119,35 @@ fn position_index(pos: Position) -> i32 {
         Position::BottomLeft => 3,
         Position::BottomCenter => 4,
         Position::BottomRight => 5,
+        Position::Custom { .. } => 6,
+    }
+}
+
+/// 0.0〜1.0 の割合を設定画面用のパーセント表記にする（小数第 1 位まで）
+fn format_percent(ratio: f32) -> String {
+    ((ratio * 1000.0).round() / 10.0).to_string()
+}
+
+fn follow_mode_index(follow: FollowMode) -> i32 {
+    match follow {
+        FollowMode::ForegroundWindow => 0,
+        FollowMode::MouseCursor => 1,
+        FollowMode::Fixed => 2,
+    }
+}
+
+fn osd_layout_index(layout: OsdLayout) -> i32 {
+    match layout {
+        OsdLayout::Vertical => 0,
+        OsdLayout::Horizontal => 1,
+    }
+}
+
+fn menu_language_index(lang: MenuLanguage) -> i32 {
+    match lang {
+        MenuLanguage::Ja => 0,
+        MenuLanguage::En => 1,
+        MenuLanguage::Ko => 2,
     }
 }
 
@@ -948,19 +2159,30 @@ fn ghost_modifier_index(m: GhostModifier) -> i32 {
     }
 }
 
+fn text_rendering_mode_index(mode: TextRenderingMode) -> i32 {
+    match mode {
+        TextRenderingMode::Default => 0,
+        TextRenderingMode::ClearType => 1,
+        TextRenderingMode::GrayscaleAA => 2,
+        TextRenderingMode::Aliased => 3,
+    }
+}
+
 fn key_transition_mode_index(mode: KeyTransitionMode) -> i32 {
     match mode {
         KeyTransitionMode::SingleCell => 0,
         KeyTransitionMode::SplitCells => 1,
+        KeyTransitionMode::SimultaneousDisplay => 2,
     }
 }
 
 fn diag_level_index(l: DiagnosticsLevel) -> i32 {
     match l {
-        DiagnosticsLevel::Error => 0,
-        DiagnosticsLevel::Warn => 1,
-        DiagnosticsLevel::Info => 2,
-        DiagnosticsLevel::Debug => 3,
+        DiagnosticsLevel::Off => 0,
+        DiagnosticsLevel::Error => 1,
+        DiagnosticsLevel::Warn => 2,
+        DiagnosticsLevel::Info => 3,
+        DiagnosticsLevel::Debug => 4,
     }
 }
 
@@ -991,7 +2213,7 @@ pub fn open_settings_window(
             WINDOW_EX_STYLE::default(),
             windows::core::PCWSTR(class_name.as_ptr()),
             windows::core::PCWSTR(title.as_ptr()),
-            WS_OVERLAPPED | WS_CAPTION | WS_SYSMENU | WS_MINIMIZEBOX,
+            WS_OVERLAPPEDWINDOW,
             CW_USEDEFAULT,
             CW_USEDEFAULT,
             880,
@@ -1007,15 +2229,23 @@ pub fn open_settings_window(
             return;
         }
 
+        let mut client = RECT::default();
+        let _ = GetClientRect(hwnd, &mut client);
+        let layout = PaneLayout {
+            client_width: client.right - client.left,
+            client_height: client.bottom - client.top,
+        };
+
+        // 位置とサイズは relayout で決める
         let nav = CreateWindowExW(
             WS_EX_CLIENTEDGE,
             windows::core::w!("LISTBOX"),
             None,
             WS_CHILD | WS_VISIBLE | WINDOW_STYLE(LBS_NOTIFY as u32),
-            20,
-            20,
-            210,
-            560,
+            0,
+            0,
+            0,
+            0,
             hwnd,
             HMENU(ID_NAV as usize as *mut _),
             None,
@@ -1023,29 +2253,35 @@ pub fn open_settings_window(
         )
         .unwrap_or_default();
 
-        let categories = [
-            "General",
-            "Display",
-            "Style",
-            "Input",
-            "Privacy",
-            "Performance",
-            "Diagnostics",
-            "Startup",
-            "Tray",
-            "Animation",
-        ];
-        for c in categories {
-            let w = to_wide(c);
-            let _ = SendMessageW(nav, LB_ADDSTRING, WPARAM(0), LPARAM(w.as_ptr() as isize));
-        }
-        let _ = SendMessageW(nav, LB_SETCURSEL, WPARAM(0), LPARAM(0));
+        let search = CreateWindowExW(
+            WS_EX_CLIENTEDGE,
+            windows::core::w!("EDIT"),
+            None,
+            WS_CHILD | WS_VISIBLE | WINDOW_STYLE(ES_AUTOHSCROLL as u32),
+            0,
+            0,
+            0,
+            0,
+            hwnd,
+            HMENU(ID_NAV_SEARCH as usize as *mut _),
+            None,
+            None,
+        )
+        .unwrap_or_default();
+        let _ = SendMessageW(
+            search,
+            EM_SETCUEBANNER,
+            WPARAM(1),
+            LPARAM(windows::core::w!("Search settings").as_ptr() as isize),
+        );
 
-        let _ = create_button(hwnd, "Revert Section", ID_BTN_REVERT_SECTION, 250, 590, 140, 32);
-        let _ = create_button(hwnd, "Reset Defaults", ID_BTN_RESET_ALL, 400, 590, 140, 32);
-        let _ = create_button(hwnd, "Close", ID_BTN_CLOSE, 740, 590, 80, 32);
+        let _ = create_button(hwnd, "Revert Section", ID_BTN_REVERT_SECTION, 0, 0, 0, 0);
+        let _ = create_button(hwnd, "Reset Defaults", ID_BTN_RESET_ALL, 0, 0, 0, 0);
+        let _ = create_button(hwnd, "Close", ID_BTN_CLOSE, 0, 0, 0, 0);
+        let _ = create_button(hwnd, "Undo", ID_BTN_UNDO, 0, 0, 0, 0);
+        let _ = create_button(hwnd, "Redo", ID_BTN_REDO, 0, 0, 0, 0);
 
-        let status = create_label(hwnd, "", 250, 628, 570, 20);
+        let status = create_label(hwnd, "", 0, 0, 0, 0);
 
         let mut state = Box::new(SettingsState {
             config: config.clone(),
@@ -1054,9 +2290,14 @@ pub fn open_settings_window(
             notify_tx,
             category: Category::General,
             nav,
+            search,
+            nav_categories: Vec::new(),
             status,
             dynamic_controls: Vec::new(),
+            layout,
             rebuilding: false,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
         });
 
         // While settings are open, keep preview visible regardless of OSD enabled/privacy state.
@@ -1064,9 +2305,11 @@ pub fn open_settings_window(
             let _ = tx.try_send(InputEvent::PreviewMode { enabled: true });
         }
 
+        refill_nav(&mut state);
         rebuild_category(hwnd, &mut state);
 
         SetWindowLongPtrW(hwnd, GWLP_USERDATA, Box::into_raw(state) as isize);
+        relayout(hwnd, layout.client_width, layout.client_height);
         SETTINGS_OPEN.with(|c| c.set(true));
 
         let _ = ShowWindow(hwnd, SW_SHOW);
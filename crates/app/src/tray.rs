@@ -7,6 +7,8 @@ use windows::Win32::UI::WindowsAndMessaging::*;
 
 use ystrokey_core::MenuLanguage;
 
+use crate::build_info;
+
 pub const WM_TRAYICON: u32 = WM_USER + 1;
 pub const ID_TRAY_TOGGLE: u32 = 1001;
 pub const ID_TRAY_EXIT: u32 = 1002;
@@ -14,6 +16,10 @@ pub const ID_TRAY_AUTOSTART: u32 = 1003;
 pub const ID_TRAY_SETTINGS: u32 = 1004;
 pub const ID_TRAY_EXPORT: u32 = 1005;
 pub const ID_TRAY_IMPORT: u32 = 1006;
+pub const ID_TRAY_VIEWLOG: u32 = 1007;
+pub const ID_TRAY_MACRO_RECORD: u32 = 1008;
+pub const ID_TRAY_MACRO_PLAY: u32 = 1009;
+pub const ID_TRAY_RESET_POSITION: u32 = 1010;
 
 /// システムトレイアイコン
 pub struct TrayIcon {
@@ -36,12 +42,7 @@ impl TrayIcon {
             };
 
             // ツールチップ（szTip: [u16; 128] 固定長配列）
-            let tip: Vec<u16> = "yStrokey"
-                .encode_utf16()
-                .chain(std::iter::once(0))
-                .collect();
-            let len = tip.len().min(nid.szTip.len());
-            nid.szTip[..len].copy_from_slice(&tip[..len]);
+            copy_wide(&mut nid.szTip, &build_info::version_string());
 
             if !Shell_NotifyIconW(NIM_ADD, &nid).as_bool() {
                 return Err(windows::core::Error::from_win32());
@@ -52,6 +53,37 @@ impl TrayIcon {
     }
 }
 
+impl TrayIcon {
+    /// トレイアイコンからバルーン通知を表示
+    pub fn show_balloon(&self, title: &str, text: &str) {
+        let mut nid = NOTIFYICONDATAW {
+            cbSize: mem::size_of::<NOTIFYICONDATAW>() as u32,
+            hWnd: self.hwnd,
+            uID: 1,
+            uFlags: NIF_INFO,
+            dwInfoFlags: NIIF_INFO,
+            ..Default::default()
+        };
+        copy_wide(&mut nid.szInfoTitle, title);
+        copy_wide(&mut nid.szInfo, text);
+        unsafe {
+            let _ = Shell_NotifyIconW(NIM_MODIFY, &nid);
+        }
+    }
+}
+
+/// 固定長の UTF-16 配列へ NUL 終端付きでコピー（収まらない分は切り捨て）
+fn copy_wide(dst: &mut [u16], s: &str) {
+    let wide: Vec<u16> = s.encode_utf16().chain(std::iter::once(0)).collect();
+    let len = wide.len().min(dst.len());
+    dst[..len].copy_from_slice(&wide[..len]);
+    if len == dst.len() {
+        if let Some(last) = dst.last_mut() {
+            *last = 0;
+        }
+    }
+}
+
 impl Drop for TrayIcon {
     fn drop(&mut self) {
         unsafe {
@@ -72,6 +104,7 @@ pub fn show_context_menu(
     menu_language: MenuLanguage,
     osd_enabled: bool,
     autostart_enabled: bool,
+    macro_recording: bool,
 ) {
     unsafe {
         let menu = match CreatePopupMenu() {
@@ -89,6 +122,12 @@ pub fn show_context_menu(
         } else {
             MF_STRING
         };
+        // 記録中は再生できないようにする
+        let (record_flags, play_flags) = if macro_recording {
+            (MF_STRING | MF_CHECKED, MF_STRING | MF_GRAYED)
+        } else {
+            (MF_STRING, MF_STRING)
+        };
 
         let _ = AppendMenuW(
             menu,
@@ -97,6 +136,7 @@ pub fn show_context_menu(
             match menu_language {
                 MenuLanguage::Ja => w!("有効/無効 切替 (&T)"),
                 MenuLanguage::En => w!("Toggle OSD (&T)"),
+                MenuLanguage::Ko => w!("OSD 켜기/끄기 (&T)"),
             },
         );
         let _ = AppendMenuW(menu, MF_SEPARATOR, 0, None);
@@ -107,9 +147,41 @@ pub fn show_context_menu(
             match menu_language {
                 MenuLanguage::Ja => w!("自動起動 (&A)"),
                 MenuLanguage::En => w!("Auto Start (&A)"),
+                MenuLanguage::Ko => w!("자동 시작 (&A)"),
             },
         );
         let _ = AppendMenuW(menu, MF_SEPARATOR, 0, None);
+        let _ = AppendMenuW(
+            menu,
+            record_flags,
+            ID_TRAY_MACRO_RECORD as usize,
+            match menu_language {
+                MenuLanguage::Ja => w!("マクロ記録 (&R)"),
+                MenuLanguage::En => w!("Record Macro (&R)"),
+                MenuLanguage::Ko => w!("매크로 기록 (&R)"),
+            },
+        );
+        let _ = AppendMenuW(
+            menu,
+            play_flags,
+            ID_TRAY_MACRO_PLAY as usize,
+            match menu_language {
+                MenuLanguage::Ja => w!("マクロ再生 (&P)"),
+                MenuLanguage::En => w!("Play Macro (&P)"),
+                MenuLanguage::Ko => w!("매크로 재생 (&P)"),
+            },
+        );
+        let _ = AppendMenuW(menu, MF_SEPARATOR, 0, None);
+        let _ = AppendMenuW(
+            menu,
+            MF_STRING,
+            ID_TRAY_RESET_POSITION as usize,
+            match menu_language {
+                MenuLanguage::Ja => w!("表示位置をリセット (&O)"),
+                MenuLanguage::En => w!("Reset Position (&O)"),
+                MenuLanguage::Ko => w!("위치 초기화 (&O)"),
+            },
+        );
         let _ = AppendMenuW(
             menu,
             MF_STRING,
@@ -117,6 +189,17 @@ pub fn show_context_menu(
             match menu_language {
                 MenuLanguage::Ja => w!("設定 (&S)"),
                 MenuLanguage::En => w!("Settings (&S)"),
+                MenuLanguage::Ko => w!("설정 (&S)"),
+            },
+        );
+        let _ = AppendMenuW(
+            menu,
+            MF_STRING,
+            ID_TRAY_VIEWLOG as usize,
+            match menu_language {
+                MenuLanguage::Ja => w!("ログ表示 (&L)"),
+                MenuLanguage::En => w!("View Log (&L)"),
+                MenuLanguage::Ko => w!("로그 보기 (&L)"),
             },
         );
         let _ = AppendMenuW(
@@ -126,6 +209,7 @@ pub fn show_context_menu(
             match menu_language {
                 MenuLanguage::Ja => w!("エクスポート (&E)"),
                 MenuLanguage::En => w!("Export (&E)"),
+                MenuLanguage::Ko => w!("내보내기 (&E)"),
             },
         );
         let _ = AppendMenuW(
@@ -135,6 +219,7 @@ pub fn show_context_menu(
             match menu_language {
                 MenuLanguage::Ja => w!("インポート (&I)"),
                 MenuLanguage::En => w!("Import (&I)"),
+                MenuLanguage::Ko => w!("가져오기 (&I)"),
             },
         );
         let _ = AppendMenuW(menu, MF_SEPARATOR, 0, None);
@@ -145,6 +230,7 @@ pub fn show_context_menu(
             match menu_language {
                 MenuLanguage::Ja => w!("終了 (&X)"),
                 MenuLanguage::En => w!("Exit (&X)"),
+                MenuLanguage::Ko => w!("종료 (&X)"),
             },
         );
 
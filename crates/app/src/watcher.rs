@@ -0,0 +1,174 @@
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, SyncSender};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use windows::core::HSTRING;
+use windows::Win32::Foundation::{CloseHandle, HANDLE};
+use windows::Win32::Storage::FileSystem::{
+    CreateFileW, ReadDirectoryChangesW, FILE_FLAG_BACKUP_SEMANTICS, FILE_LIST_DIRECTORY,
+    FILE_NOTIFY_CHANGE_FILE_NAME, FILE_NOTIFY_CHANGE_LAST_WRITE, FILE_NOTIFY_CHANGE_SIZE,
+    FILE_NOTIFY_INFORMATION, FILE_SHARE_DELETE, FILE_SHARE_READ, FILE_SHARE_WRITE, OPEN_EXISTING,
+};
+
+use ystrokey_core::{DiagnosticsLevel, InputEvent};
+
+use crate::logger;
+
+/// 変更通知バッファ（FILE_NOTIFY_INFORMATION は DWORD 境界に配置される）
+const NOTIFY_BUFFER_WORDS: usize = 4096;
+
+/// スレッドへ受け渡すためのディレクトリハンドル
+struct DirHandle(HANDLE);
+
+// ディレクトリハンドルは監視スレッドのみが所有して使う
+unsafe impl Send for DirHandle {}
+
+impl Drop for DirHandle {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = CloseHandle(self.0);
+        }
+    }
+}
+
+/// 設定ファイルのあるディレクトリを `ReadDirectoryChangesW` で監視する
+///
+/// 設定ファイル名に一致する変更を検知すると、デバウンス後に
+/// `InputEvent::ConfigChanged` を送る。
+pub struct DirWatcher {
+    debounce_ms: Arc<AtomicU64>,
+    /// 監視スレッドが動いているか（ディレクトリ削除などで止まったら false）
+    alive: Arc<AtomicBool>,
+}
+
+impl DirWatcher {
+    pub fn start(
+        config_path: &Path,
+        debounce_ms: u64,
+        tx: SyncSender<InputEvent>,
+    ) -> std::io::Result<Self> {
+        let dir = config_path
+            .parent()
+            .filter(|p| !p.as_os_str().is_empty())
+            .unwrap_or_else(|| Path::new("."));
+        let file_name = config_path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_lowercase())
+            .ok_or_else(|| {
+                std::io::Error::new(std::io::ErrorKind::InvalidInput, "config path has no file name")
+            })?;
+
+        let handle = unsafe {
+            CreateFileW(
+                &HSTRING::from(dir.as_os_str()),
+                FILE_LIST_DIRECTORY.0,
+                FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE,
+                None,
+                OPEN_EXISTING,
+                FILE_FLAG_BACKUP_SEMANTICS,
+                None,
+            )
+        }
+        .map_err(std::io::Error::other)?;
+        let dir_handle = DirHandle(handle);
+
+        let debounce_ms = Arc::new(AtomicU64::new(debounce_ms));
+        let (change_tx, change_rx) = mpsc::channel::<()>();
+
+        let alive = Arc::new(AtomicBool::new(true));
+        let watching = Arc::clone(&alive);
+        let exit_tx = tx.clone();
+        thread::Builder::new()
+            .name("config-watcher".into())
+            .spawn(move || {
+                watch_loop(dir_handle, &file_name, change_tx);
+                watching.store(false, Ordering::Relaxed);
+                // メインループを起こしてポーリングへ切り替えさせる
+                let _ = exit_tx.try_send(InputEvent::ConfigChanged);
+            })?;
+
+        let debounce = Arc::clone(&debounce_ms);
+        thread::Builder::new()
+            .name("config-debounce".into())
+            .spawn(move || {
+                // 最初の変更を受けたら、静かになるまで待ってから1回だけ通知
+                while change_rx.recv().is_ok() {
+                    loop {
+                        let wait = Duration::from_millis(debounce.load(Ordering::Relaxed));
+                        match change_rx.recv_timeout(wait) {
+                            Ok(()) => continue,
+                            Err(RecvTimeoutError::Timeout) => break,
+                            Err(RecvTimeoutError::Disconnected) => return,
+                        }
+                    }
+                    let _ = tx.try_send(InputEvent::ConfigChanged);
+                }
+            })?;
+
+        Ok(Self { debounce_ms, alive })
+    }
+
+    /// 監視スレッドが終了していないか
+    pub fn is_alive(&self) -> bool {
+        self.alive.load(Ordering::Relaxed)
+    }
+
+    /// デバウンス時間を更新（設定のホットリロード時）
+    pub fn set_debounce_ms(&self, ms: u64) {
+        self.debounce_ms.store(ms, Ordering::Relaxed);
+    }
+}
+
+fn watch_loop(dir: DirHandle, file_name: &str, change_tx: mpsc::Sender<()>) {
+    let mut buf = vec![0u32; NOTIFY_BUFFER_WORDS];
+    loop {
+        let mut bytes = 0u32;
+        let result = unsafe {
+            ReadDirectoryChangesW(
+                dir.0,
+                buf.as_mut_ptr() as *mut _,
+                (buf.len() * std::mem::size_of::<u32>()) as u32,
+                false,
+                FILE_NOTIFY_CHANGE_FILE_NAME | FILE_NOTIFY_CHANGE_LAST_WRITE | FILE_NOTIFY_CHANGE_SIZE,
+                Some(&mut bytes),
+                None,
+                None,
+            )
+        };
+        if let Err(e) = result {
+            logger::log(DiagnosticsLevel::Warn, &format!("Config watcher stopped: {e}"));
+            return;
+        }
+
+        // bytes == 0 はバッファ溢れ（どのファイルか不明なので変更扱い）
+        let matched = bytes == 0 || unsafe { contains_file(&buf, bytes as usize, file_name) };
+        if matched && change_tx.send(()).is_err() {
+            return;
+        }
+    }
+}
+
+/// 通知バッファ内に対象ファイル名のエントリがあるか
+unsafe fn contains_file(buf: &[u32], len: usize, file_name: &str) -> bool {
+    let base = buf.as_ptr() as *const u8;
+    let mut offset = 0usize;
+    loop {
+        if offset + std::mem::size_of::<FILE_NOTIFY_INFORMATION>() > len {
+            return false;
+        }
+        let info = &*(base.add(offset) as *const FILE_NOTIFY_INFORMATION);
+        let name_len = info.FileNameLength as usize / 2;
+        let name_ptr = info.FileName.as_ptr();
+        let name = String::from_utf16_lossy(std::slice::from_raw_parts(name_ptr, name_len));
+        if name.to_lowercase() == file_name {
+            return true;
+        }
+        if info.NextEntryOffset == 0 {
+            return false;
+        }
+        offset += info.NextEntryOffset as usize;
+    }
+}
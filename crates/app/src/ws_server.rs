@@ -0,0 +1,250 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use ystrokey_core::{DiagnosticsLevel, InputEvent};
+
+use crate::event_json::input_event_json;
+use crate::http_server::origin_allowed;
+use crate::logger;
+
+/// RFC 6455 で規定されたハンドシェイク用 GUID
+const WS_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// 入力イベントを WebSocket で push 配信する最小実装のサーバー（localhost のみ）
+///
+/// クライアントからの受信フレームは読まない（配信専用）。
+/// ブラウザは WebSocket に CORS を適用しないため、`Origin` はハンドシェイクで検証する。
+pub struct WsServer {
+    clients: Arc<Mutex<Vec<TcpStream>>>,
+}
+
+impl WsServer {
+    /// 指定ポートで待ち受けを開始
+    ///
+    /// `allowed_origin` 以外の `Origin` を持つハンドシェイクは 403 で拒否する。
+    pub fn start(port: u16, allowed_origin: Option<String>) -> std::io::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+        let clients: Arc<Mutex<Vec<TcpStream>>> = Arc::new(Mutex::new(Vec::new()));
+
+        let accept_clients = Arc::clone(&clients);
+        thread::Builder::new()
+            .name("ws-accept".into())
+            .spawn(move || accept_loop(listener, allowed_origin, accept_clients))?;
+
+        Ok(Self { clients })
+    }
+
+    /// 全クライアントへイベントを送信（書き込めないクライアントは除外）
+    pub fn broadcast(&self, event: &InputEvent) {
+        let Some(value) = input_event_json(event) else {
+            return;
+        };
+        let frame = encode_text_frame(value.to_string().as_bytes());
+        if let Ok(mut clients) = self.clients.lock() {
+            // ノンブロッキング書き込み: 部分書き込みもフレーム破損になるため切断扱い
+            clients.retain_mut(|stream| matches!(stream.write(&frame), Ok(n) if n == frame.len()));
+        }
+    }
+}
+
+fn accept_loop(
+    listener: TcpListener,
+    allowed_origin: Option<String>,
+    clients: Arc<Mutex<Vec<TcpStream>>>,
+) {
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else {
+            continue;
+        };
+        let clients = Arc::clone(&clients);
+        let allowed_origin = allowed_origin.clone();
+        let spawned = thread::Builder::new()
+            .name("ws-handshake".into())
+            .spawn(move || match handshake(stream, allowed_origin.as_deref()) {
+                Ok(stream) => {
+                    if let Ok(mut clients) = clients.lock() {
+                        clients.push(stream);
+                    }
+                }
+                Err(e) => {
+                    logger::log(DiagnosticsLevel::Debug, &format!("WebSocket handshake failed: {e}"));
+                }
+            });
+        if let Err(e) = spawned {
+            logger::log(
+                DiagnosticsLevel::Warn,
+                &format!("WebSocket handshake thread spawn failed: {e}"),
+            );
+        }
+    }
+}
+
+/// HTTP Upgrade を処理し、ノンブロッキングに切り替えたストリームを返す
+fn handshake(mut stream: TcpStream, allowed_origin: Option<&str>) -> std::io::Result<TcpStream> {
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut key: Option<String> = None;
+    let mut origin: Option<String> = None;
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("sec-websocket-key") {
+                key = Some(value.trim().to_string());
+            } else if name.trim().eq_ignore_ascii_case("origin") {
+                origin = Some(value.trim().to_string());
+            }
+        }
+    }
+
+    if !origin_allowed(origin.as_deref(), allowed_origin) {
+        stream.write_all(b"HTTP/1.1 403 Forbidden\r\nConnection: close\r\n\r\n")?;
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            format!("disallowed origin: {}", origin.unwrap_or_default()),
+        ));
+    }
+
+    let Some(key) = key.filter(|_| request_line.starts_with("GET ")) else {
+        stream.write_all(b"HTTP/1.1 400 Bad Request\r\nConnection: close\r\n\r\n")?;
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "missing Sec-WebSocket-Key",
+        ));
+    };
+
+    let accept = accept_key(&key);
+    let response = format!(
+        "HTTP/1.1 101 Switching Protocols\r\n\
+         Upgrade: websocket\r\n\
+         Connection: Upgrade\r\n\
+         Sec-WebSocket-Accept: {accept}\r\n\r\n"
+    );
+    stream.write_all(response.as_bytes())?;
+    stream.flush()?;
+    stream.set_nonblocking(true)?;
+    Ok(stream)
+}
+
+/// `Sec-WebSocket-Key` から `Sec-WebSocket-Accept` を計算
+fn accept_key(key: &str) -> String {
+    base64_encode(&sha1(format!("{key}{WS_GUID}").as_bytes()))
+}
+
+/// サーバー→クライアントのテキストフレーム（FIN, マスクなし）
+fn encode_text_frame(payload: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(payload.len() + 10);
+    frame.push(0x81);
+    match payload.len() {
+        len if len < 126 => frame.push(len as u8),
+        len if len <= u16::MAX as usize => {
+            frame.push(126);
+            frame.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            frame.push(127);
+            frame.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(payload);
+    frame
+}
+
+/// ハンドシェイク用の SHA-1（外部クレートを使わない最小実装）
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+
+    let mut msg = data.to_vec();
+    let bit_len = (data.len() as u64).wrapping_mul(8);
+    msg.push(0x80);
+    while msg.len() % 64 != 56 {
+        msg.push(0);
+    }
+    msg.extend_from_slice(&bit_len.to_be_bytes());
+
+    for block in msg.chunks_exact(64) {
+        let mut w = [0u32; 80];
+        for (i, word) in block.chunks_exact(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, wi) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(*wi);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+
+        h[0] = h[0].wrapping_add(a);
+        h[1] = h[1].wrapping_add(b);
+        h[2] = h[2].wrapping_add(c);
+        h[3] = h[3].wrapping_add(d);
+        h[4] = h[4].wrapping_add(e);
+    }
+
+    let mut out = [0u8; 20];
+    for (i, word) in h.iter().enumerate() {
+        out[i * 4..i * 4 + 4].copy_from_slice(&word.to_be_bytes());
+    }
+    out
+}
+
+fn base64_encode(data: &[u8]) -> String {
+    const TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        out.push(TABLE[(n >> 18) as usize & 63] as char);
+        out.push(TABLE[(n >> 12) as usize & 63] as char);
+        out.push(if chunk.len() > 1 { TABLE[(n >> 6) as usize & 63] as char } else { '=' });
+        out.push(if chunk.len() > 2 { TABLE[n as usize & 63] as char } else { '=' });
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accept_key_matches_rfc6455_example() {
+        // RFC 6455 §1.3 の例
+        assert_eq!(accept_key("dGhlIHNhbXBsZSBub25jZQ=="), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+    }
+
+    #[test]
+    fn sha1_and_base64_match_known_vectors() {
+        let hex: String = sha1(b"abc").iter().map(|b| format!("{b:02x}")).collect();
+        assert_eq!(hex, "a9993e364706816aba3e25717850c26c9cd0d89d");
+        assert_eq!(base64_encode(b""), "");
+        assert_eq!(base64_encode(b"f"), "Zg==");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert_eq!(base64_encode(b"foo"), "Zm9v");
+    }
+}
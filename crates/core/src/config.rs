@@ -4,14 +4,57 @@ use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::error::ConfigError;
+use crate::state::DisplayItemKind;
 
 pub const SCHEMA_VERSION: u32 = 2;
 
+/// `hotkey.extra_toggles` の最大登録数
+pub const MAX_EXTRA_TOGGLES: usize = 16;
+
+/// 設定ファイルの形式（拡張子で判定、`.toml` 以外は JSON）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ConfigFormat {
+    Json,
+    Toml,
+}
+
+impl ConfigFormat {
+    fn from_path(path: &Path) -> Self {
+        match path.extension() {
+            Some(ext) if ext.eq_ignore_ascii_case("toml") => Self::Toml,
+            _ => Self::Json,
+        }
+    }
+
+    fn parse(self, content: &str) -> Result<AppConfig, ConfigError> {
+        Ok(match self {
+            Self::Json => serde_json::from_str(content)?,
+            Self::Toml => toml::from_str(content)?,
+        })
+    }
+
+    /// スキーマ確認用に形式を問わず JSON 値として読む
+    fn parse_value(self, content: &str) -> Result<serde_json::Value, ConfigError> {
+        Ok(match self {
+            Self::Json => serde_json::from_str(content)?,
+            Self::Toml => toml::from_str(content)?,
+        })
+    }
+
+    fn serialize(self, config: &AppConfig) -> Result<String, ConfigError> {
+        Ok(match self {
+            Self::Json => serde_json::to_string_pretty(config)?,
+            Self::Toml => toml::to_string_pretty(config)?,
+        })
+    }
+}
+
 /// Strict configuration schema for the app.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct AppConfig {
     pub schema_version: u32,
@@ -30,19 +73,40 @@ pub struct AppConfig {
     pub animation: AnimationConfig,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct DisplayConfig {
     pub position: Position,
     pub offset_x: i32,
     pub offset_y: i32,
+    /// 旧形式のモニタごとの保存位置（px）。`monitor_relative_positions` があるモニタでは使わない
     pub monitor_positions: HashMap<String, [i32; 2]>,
+    /// ドラッグで決めたモニタごとの位置（作業領域に対する割合、`Position::Custom` と同じ基準）
+    ///
+    /// px ではないので、DPI や作業領域が変わっても同じ相対位置に戻る。
+    #[serde(default)]
+    pub monitor_relative_positions: HashMap<String, [f32; 2]>,
     pub max_items: usize,
     pub display_duration_ms: u64,
     pub fade_duration_ms: u64,
+    /// アイテムの並べ方（縦積み / 横並び）
+    #[serde(default)]
+    pub layout: OsdLayout,
+    /// ゴーストモードでドラッグしたとき、作業領域の端へ吸着させる距離 (px, 0 で無効)
+    #[serde(default = "default_snap_to_edge_px", alias = "snap_distance_px")]
+    pub snap_to_edge_px: u32,
+    /// OSD を出すモニタのデバイス名（例: `\\.\DISPLAY1`）。空なら全モニタ
+    #[serde(default)]
+    pub enabled_monitors: Vec<String>,
+    /// OSD をどのモニタへ追従させるか
+    #[serde(default)]
+    pub follow: FollowMode,
+    /// ウィンドウを表示中アイテムの大きさに合わせて縮める（`osd_width`/`osd_height` が上限）
+    #[serde(default)]
+    pub auto_size: bool,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq)]
 #[serde(rename_all = "kebab-case")]
 pub enum Position {
     TopLeft,
@@ -51,9 +115,34 @@ pub enum Position {
     BottomLeft,
     BottomCenter,
     BottomRight,
+    /// 作業領域に対する割合で指定（0.0/0.0 で左上、1.0/1.0 で右下に OSD が接する）
+    Custom { x_pct: f32, y_pct: f32 },
+}
+
+/// OSD を表示するモニタの決め方
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum FollowMode {
+    /// フォアグラウンドウィンドウのあるモニタ
+    #[default]
+    ForegroundWindow,
+    /// マウスカーソルのあるモニタ
+    MouseCursor,
+    /// 自動では移動しない（保存済みの `monitor_positions` のみ反映）
+    Fixed,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum OsdLayout {
+    /// 新しいアイテムを下に、古いものを上へ積む
+    #[default]
+    Vertical,
+    /// 左から右へ並べ、幅が埋まったら上の行へ折り返す
+    Horizontal,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct StyleConfig {
     pub font_family: String,
@@ -66,9 +155,53 @@ pub struct StyleConfig {
     pub shortcut_color: String,
     pub key_down_color: String,
     pub opacity: f32,
+    /// 同梱フォントファイル (.ttf/.otf) のパス。
+    /// 指定時はそのファイルから構築したフォントコレクションで `font_family` を解決し、
+    /// 読み込みに失敗した場合はシステムフォントにフォールバックする。
+    #[serde(default)]
+    pub custom_font_path: Option<String>,
+    /// テキストのアンチエイリアス方式。`default` は DPI に応じて自動選択。
+    #[serde(default)]
+    pub text_rendering_mode: TextRenderingMode,
+    /// キー入力アイテムのフォントウェイト (1-999, DWRITE_FONT_WEIGHT 相当)
+    #[serde(default = "default_font_weight")]
+    pub key_font_weight: u32,
+    /// IME変換中テキストのフォントウェイト
+    #[serde(default = "default_font_weight")]
+    pub ime_font_weight: u32,
+    /// クリップボードプレビューのフォントウェイト
+    #[serde(default = "default_font_weight")]
+    pub clipboard_font_weight: u32,
+    /// 主フォントにない CJK グリフ（IME 変換中のかな・漢字など）に使うフォント。空ならシステム既定
+    #[serde(default = "default_fallback_font_family")]
+    pub fallback_font_family: String,
+    /// キー入力テキストをイタリック体にするか
+    #[serde(default)]
+    pub key_font_italic: bool,
+    /// IME変換中テキストをイタリック体にするか
+    #[serde(default)]
+    pub ime_font_italic: bool,
+    /// キー入力をキーキャップ風（立体的なハイライト/シャドウ付き）で描画
+    #[serde(default)]
+    pub keycap_style: bool,
+    /// カラーフォント（Segoe UI Emoji など）のグリフをカラーで描画するか
+    #[serde(default = "default_true")]
+    pub enable_color_fonts: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum TextRenderingMode {
+    #[default]
+    Default,
+    #[serde(rename = "cleartype")]
+    ClearType,
+    #[serde(rename = "grayscale")]
+    GrayscaleAA,
+    Aliased,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct BehaviorConfig {
     pub key_transition_mode: KeyTransitionMode,
@@ -89,77 +222,278 @@ pub struct BehaviorConfig {
     /// 既存 config 互換のため serde default。
     #[serde(default)]
     pub show_shift_for_typed_symbols: bool,
+    /// 同一内容のクリップボード更新をこの時間内は無視する (ms, 0 で無効)。
+    /// 既存 config 互換のため serde default。
+    #[serde(default = "default_clipboard_debounce_ms")]
+    pub clipboard_debounce_ms: u64,
+    /// ゲームパッド（XInput）のボタン入力を表示するか
+    #[serde(default)]
+    pub show_gamepad: bool,
+    /// 修飾キーの左右を区別して表示するか（例: "LCtrl" / "RCtrl"）
+    #[serde(default)]
+    pub distinguish_lr_modifiers: bool,
+    /// アプリごとのキーフィルタ（`ignored_keys` の後に適用、最初に一致したものを使う）
+    #[serde(default)]
+    pub app_key_filters: Vec<AppKeyFilter>,
+    /// マウスのボタン入力の取得経路（起動時のみ反映）
+    #[serde(default)]
+    pub mouse_input_mode: MouseInputMode,
+    /// 押下中の修飾キーを最下段に固定表示するか
+    #[serde(default)]
+    pub show_modifier_state: bool,
+    /// マウスのドラッグを方向矢印付きで表示するか（`mouse_input_mode` が off 以外のときのみ）
+    #[serde(default)]
+    pub show_mouse_drag: bool,
+    /// ドラッグとみなす最小移動量 (px)
+    #[serde(default = "default_drag_min_pixels")]
+    pub drag_min_pixels: f32,
+    /// 作成時に固定表示（期限切れなし）にするアイテム種別（例: "LockIndicator", "ModifierState"）
+    #[serde(default)]
+    pub pinned_item_kinds: Vec<String>,
+    /// IME フォールバック表示の仮名種別（旧名 `ime_fallback_kana` も受け付ける）
+    #[serde(default, alias = "ime_fallback_kana")]
+    pub ime_display_mode: ImeDisplayMode,
+    /// `hotkey.privacy_toggle` で強制したプライバシーモードを自動解除するまでの時間 (ms, 0 で解除しない)
+    #[serde(default = "default_privacy_override_timeout_ms")]
+    pub privacy_override_timeout_ms: u64,
+    /// IME フォールバックのローマ字変換で組み込み表より優先する綴り（例: "tu" → "つ"）
+    #[serde(default)]
+    pub romaji_overrides: HashMap<String, String>,
+    /// CapsLock / NumLock が有効な間、ロック表示を消さずに出し続けるか
+    #[serde(default)]
+    pub persist_lock_indicator: bool,
+    /// キーごとの連打判定時間 (ms)。キーは `KeyCode::label` の表記（例: "BS", "Enter"、大文字小文字は区別しない）
+    #[serde(default)]
+    pub per_key_repeat_timeout: HashMap<String, u64>,
+    /// IME 変換中に候補一覧（選択中の候補を強調）を表示するか
+    #[serde(default)]
+    pub show_ime_candidates: bool,
+    /// 候補一覧の表示件数（選択中の候補を含むページ単位で切り出す）
+    #[serde(default = "default_ime_candidate_max_items")]
+    pub ime_candidate_max_items: usize,
+    /// クリップボード表示にコピー元アプリ名を ` (from App)` として付けるか
+    #[serde(default)]
+    pub show_clipboard_source: bool,
+    /// IME 確定時に確定文字列を「確定: ○○」として一時表示するか（`show_ime_composition` 有効時のみ）
+    #[serde(default)]
+    pub show_ime_commit: bool,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+/// 特定アプリがフォアグラウンドのときだけ適用するキーフィルタ
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct AppKeyFilter {
+    /// 対象のプロセス名（例: "game.exe"、大文字小文字無視）
+    pub app_name: String,
+    /// このアプリで表示しないキー
+    #[serde(default)]
+    pub ignored_keys: Vec<String>,
+    /// 指定時はこれらのキーのみ表示（修飾キーは対象外）
+    #[serde(default)]
+    pub show_only_keys: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 #[serde(rename_all = "kebab-case")]
 pub enum KeyTransitionMode {
     SingleCell,
     SplitCells,
+    /// 押下中のキーをすべて個別のアイテムとして同時表示する
+    SimultaneousDisplay,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct ShortcutDef {
     pub keys: String,
     pub label: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct PrivacyConfig {
     pub enabled: bool,
+    /// blocklist: blocked_apps を非表示 / allowlist: allowed_apps 以外を非表示
+    #[serde(default)]
+    pub mode: PrivacyMode,
     pub blocked_apps: Vec<String>,
+    /// allowlist モードで OSD を表示するプロセス名
+    #[serde(default)]
+    pub allowed_apps: Vec<String>,
+    /// クリップボード内容を "[hidden]" に置き換えるパターン。
+    /// `*`/`?` ワイルドカード（大文字小文字無視・全体一致）、
+    /// または `@random-token`（長いランダム文字列らしいもの）を指定できる。
+    #[serde(default)]
+    pub mask_clipboard_patterns: Vec<String>,
+    /// フォアグラウンドウィンドウのタイトルにこれらが含まれる場合も非表示（大文字小文字無視）
+    #[serde(default)]
+    pub blocked_title_substrings: Vec<String>,
+    /// クリップボード表示のうち一致した部分だけを `redact_replacement` に置き換える正規表現。
+    /// コンパイルできないパターンは警告を出して無視する。
+    #[serde(default)]
+    pub clipboard_redact_patterns: Vec<String>,
+    /// `clipboard_redact_patterns` に一致した部分の置換文字列
+    #[serde(default = "default_redact_replacement")]
+    pub redact_replacement: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PrivacyMode {
+    #[default]
+    Blocklist,
+    Allowlist,
+}
+
+/// マウス入力の取得経路
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum MouseInputMode {
+    /// マウス入力を表示しない
+    #[default]
+    Off,
+    /// WH_MOUSE_LL フック
+    Hook,
+    /// Raw Input（WM_INPUT、フックの遅延やタイムアウトの影響を受けない）
+    RawInput,
+    /// 両方を使い、重複したイベントは1つにまとめる
+    Both,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// IME フォールバックで表示する仮名
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ImeDisplayMode {
+    /// IME の入力モードに合わせる（全角カタカナ入力ならカタカナ）
+    #[default]
+    Auto,
+    Hiragana,
+    Katakana,
+}
+
+/// `hotkey.toggle` の動作
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum HotkeyMode {
+    /// 押すたびに表示/非表示を切り替える
+    #[default]
+    Toggle,
+    /// 押している間だけ表示する
+    HoldToShow,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct HotkeyConfig {
     pub toggle: String,
+    #[serde(default)]
+    pub mode: HotkeyMode,
+    /// `toggle` と同じ動作をする追加のホットキー（環境ごとに空いている組み合わせを併用）
+    #[serde(default)]
+    pub extra_toggles: Vec<String>,
+    /// 即座に表示を消去し、再度押すまで OSD を抑止するホットキー（空で無効）
+    #[serde(default)]
+    pub panic_hide: String,
+    /// 現在の OSD 表示を PNG に保存するホットキー（空で無効）
+    #[serde(default)]
+    pub screenshot: String,
+    /// プライバシーモードを一時的に強制するホットキー（空で無効）
+    #[serde(default)]
+    pub privacy_toggle: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct PerformanceConfig {
     pub osd_width: i32,
     pub osd_height: i32,
     pub ime_poll_interval_ms: u64,
     pub frame_interval_ms: u64,
+    /// フェードアウト中のアイテムしかないときの描画間隔 (ms)。
+    /// 入力が届けばすぐに通常の `frame_interval_ms` へ戻る。
+    #[serde(default = "default_fade_frame_interval_ms")]
+    pub fade_frame_interval_ms: u64,
+    /// 設定ファイル変更検知後のデバウンス時間 (ms)。
+    /// ディレクトリ監視が使えない環境ではポーリング間隔として使う。
     pub config_reload_interval_ms: u64,
+    /// OSD状態を配信する HTTP サーバーのポート (None で無効、起動時のみ反映)
+    #[serde(default)]
+    pub http_server_port: Option<u16>,
+    /// 入力イベントを push 配信する WebSocket サーバーのポート (None で無効、起動時のみ反映)
+    #[serde(default)]
+    pub websocket_port: Option<u16>,
+    /// HTTP / WebSocket サーバーへのブラウザからのアクセスを許可する Origin (例: `http://localhost:3000`)。
+    /// 一致した `Origin` だけを受け付け、None なら `Origin` 付きのリクエストはすべて拒否する。
+    #[serde(default)]
+    pub http_allowed_origin: Option<String>,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq, PartialOrd, Ord)]
 #[serde(rename_all = "lowercase")]
 pub enum DiagnosticsLevel {
+    /// ログを一切出力しない（Error より下位に置き、比較で全レベルを抑止する）
+    Off,
     Error,
     Warn,
     Info,
     Debug,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct DiagnosticsConfig {
     pub level: DiagnosticsLevel,
     pub file_logging_enabled: bool,
     pub max_file_bytes: u64,
     pub max_files: u32,
+    /// ログ行の形式 (text: `[ts][LEVEL] msg` / json(jsonl): 1行1オブジェクト)
+    #[serde(default, alias = "format")]
+    pub log_format: LogFormat,
+    /// ログ行の時刻表記 (epoch: Unix 秒 / local-iso: `2024-05-01T13:45:02` 形式のローカル時刻)
+    #[serde(default)]
+    pub timestamp: LogTimestamp,
+    /// 受け取った入力イベントを JSON Lines で記録する（再生による不具合調査用）
+    ///
+    /// 記録先は起動のたびに作り直す。`--replay` で再生している間は記録しない。
+    #[serde(default)]
+    pub record_events: bool,
+    /// イベントの記録先。未指定なら `logs/events.jsonl`
+    #[serde(default)]
+    pub events_file: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    #[default]
+    Text,
+    #[serde(alias = "jsonl")]
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum LogTimestamp {
+    #[default]
+    Epoch,
+    LocalIso,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct StartupConfig {
     pub autostart_enabled: bool,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum MenuLanguage {
     Ja,
     En,
+    Ko,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct TrayConfig {
     pub start_osd_enabled: bool,
@@ -167,7 +501,7 @@ pub struct TrayConfig {
     pub confirm_on_exit: bool,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum GhostModifier {
     Ctrl,
@@ -175,14 +509,14 @@ pub enum GhostModifier {
     Shift,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum FadeOutCurve {
     Linear,
     EaseOut,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct AnimationConfig {
     pub ghost_modifier: GhostModifier,
@@ -211,6 +545,14 @@ impl Default for AppConfig {
     }
 }
 
+impl DisplayConfig {
+    /// ドラッグで保存したモニタごとの位置をすべて消し、`position` どおりの配置に戻す
+    pub fn clear_saved_positions(&mut self) {
+        self.monitor_positions.clear();
+        self.monitor_relative_positions.clear();
+    }
+}
+
 impl Default for DisplayConfig {
     fn default() -> Self {
         Self {
@@ -218,9 +560,15 @@ impl Default for DisplayConfig {
             offset_x: 0,
             offset_y: -48,
             monitor_positions: HashMap::new(),
+            monitor_relative_positions: HashMap::new(),
             max_items: 5,
             display_duration_ms: 2000,
             fade_duration_ms: 300,
+            layout: OsdLayout::default(),
+            snap_to_edge_px: default_snap_to_edge_px(),
+            enabled_monitors: Vec::new(),
+            follow: FollowMode::default(),
+            auto_size: false,
         }
     }
 }
@@ -237,6 +585,16 @@ impl Default for StyleConfig {
             shortcut_color: "#4CAF50".into(),
             key_down_color: "#2196F3".into(),
             opacity: 0.95,
+            custom_font_path: None,
+            text_rendering_mode: TextRenderingMode::Default,
+            key_font_weight: default_font_weight(),
+            ime_font_weight: default_font_weight(),
+            clipboard_font_weight: default_font_weight(),
+            fallback_font_family: default_fallback_font_family(),
+            key_font_italic: false,
+            ime_font_italic: false,
+            keycap_style: false,
+            enable_color_fonts: true,
         }
     }
 }
@@ -257,6 +615,24 @@ impl Default for BehaviorConfig {
             ignored_keys: Vec::new(),
             exclude_from_capture: false,
             show_shift_for_typed_symbols: false,
+            clipboard_debounce_ms: default_clipboard_debounce_ms(),
+            show_gamepad: false,
+            distinguish_lr_modifiers: false,
+            app_key_filters: Vec::new(),
+            mouse_input_mode: MouseInputMode::default(),
+            show_modifier_state: false,
+            show_mouse_drag: false,
+            drag_min_pixels: default_drag_min_pixels(),
+            pinned_item_kinds: Vec::new(),
+            ime_display_mode: ImeDisplayMode::default(),
+            privacy_override_timeout_ms: default_privacy_override_timeout_ms(),
+            romaji_overrides: HashMap::new(),
+            per_key_repeat_timeout: HashMap::new(),
+            persist_lock_indicator: false,
+            show_ime_candidates: false,
+            ime_candidate_max_items: default_ime_candidate_max_items(),
+            show_clipboard_source: false,
+            show_ime_commit: false,
         }
     }
 }
@@ -265,7 +641,13 @@ impl Default for PrivacyConfig {
     fn default() -> Self {
         Self {
             enabled: true,
+            mode: PrivacyMode::Blocklist,
             blocked_apps: vec!["KeePass.exe".into(), "1Password.exe".into()],
+            allowed_apps: Vec::new(),
+            mask_clipboard_patterns: Vec::new(),
+            blocked_title_substrings: Vec::new(),
+            clipboard_redact_patterns: Vec::new(),
+            redact_replacement: default_redact_replacement(),
         }
     }
 }
@@ -274,6 +656,11 @@ impl Default for HotkeyConfig {
     fn default() -> Self {
         Self {
             toggle: "Ctrl+Alt+F12".into(),
+            mode: HotkeyMode::Toggle,
+            extra_toggles: Vec::new(),
+            panic_hide: String::new(),
+            screenshot: String::new(),
+            privacy_toggle: String::new(),
         }
     }
 }
@@ -285,7 +672,11 @@ impl Default for PerformanceConfig {
             osd_height: 300,
             ime_poll_interval_ms: 50,
             frame_interval_ms: 16,
+            fade_frame_interval_ms: default_fade_frame_interval_ms(),
             config_reload_interval_ms: 1000,
+            http_server_port: None,
+            websocket_port: None,
+            http_allowed_origin: None,
         }
     }
 }
@@ -297,6 +688,10 @@ impl Default for DiagnosticsConfig {
             file_logging_enabled: true,
             max_file_bytes: 1024 * 1024,
             max_files: 3,
+            log_format: LogFormat::Text,
+            timestamp: LogTimestamp::Epoch,
+            record_events: false,
+            events_file: None,
         }
     }
 }
@@ -330,6 +725,47 @@ impl Default for AnimationConfig {
     }
 }
 
+fn default_fallback_font_family() -> String {
+    "Yu Gothic UI".to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_clipboard_debounce_ms() -> u64 {
+    500
+}
+
+fn default_fade_frame_interval_ms() -> u64 {
+    33
+}
+
+fn default_snap_to_edge_px() -> u32 {
+    8
+}
+
+fn default_drag_min_pixels() -> f32 {
+    40.0
+}
+
+fn default_ime_candidate_max_items() -> usize {
+    5
+}
+
+fn default_redact_replacement() -> String {
+    "[REDACTED]".into()
+}
+
+fn default_privacy_override_timeout_ms() -> u64 {
+    300_000
+}
+
+/// DWRITE_FONT_WEIGHT_SEMI_BOLD（従来の固定ウェイト）
+fn default_font_weight() -> u32 {
+    600
+}
+
 fn default_shortcuts() -> Vec<ShortcutDef> {
     vec![
         ShortcutDef { keys: "Ctrl+C".into(), label: "Copy".into() },
@@ -353,7 +789,103 @@ fn default_shortcuts() -> Vec<ShortcutDef> {
     ]
 }
 
+/// 旧スキーマの設定値を現行の `AppConfig` に変換
+///
+/// v1 は v2 で追加された項目が欠けているだけなので、既定値で補ってから
+/// バージョンを上げる。未知のキーは従来どおり拒否する。
+pub fn migrate(mut value: serde_json::Value) -> Result<AppConfig, ConfigError> {
+    if value.get("schema_version").and_then(|v| v.as_u64()) == Some(1) {
+        let defaults = serde_json::to_value(AppConfig::default())?;
+        fill_missing_fields(&mut value, &defaults);
+        value["schema_version"] = serde_json::json!(SCHEMA_VERSION);
+    }
+    Ok(serde_json::from_value(value)?)
+}
+
+/// `target` に無いキーを `defaults` から再帰的に補う（既存の値は変更しない）
+fn fill_missing_fields(target: &mut serde_json::Value, defaults: &serde_json::Value) {
+    let (Some(target), Some(defaults)) = (target.as_object_mut(), defaults.as_object()) else {
+        return;
+    };
+    for (key, default) in defaults {
+        match target.get_mut(key) {
+            Some(existing) => fill_missing_fields(existing, default),
+            None => {
+                target.insert(key.clone(), default.clone());
+            }
+        }
+    }
+}
+
+/// 2 つの設定の間で値が変わった項目（ホットリロード時のログ用）
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigDiff {
+    /// `display.max_items` のようなドット区切りのキー
+    pub field: String,
+    /// 変更前の値（項目が無かった場合は `null`）
+    pub from: serde_json::Value,
+    /// 変更後の値（項目が無くなった場合は `null`）
+    pub to: serde_json::Value,
+}
+
+impl std::fmt::Display for ConfigDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {} -> {}", self.field, self.from, self.to)
+    }
+}
+
+/// オブジェクトはキーごとに潜り、それ以外（配列を含む）は値全体で比較する
+fn collect_diffs(
+    path: &str,
+    from: &serde_json::Value,
+    to: &serde_json::Value,
+    diffs: &mut Vec<ConfigDiff>,
+) {
+    if from == to {
+        return;
+    }
+    let (Some(from_obj), Some(to_obj)) = (from.as_object(), to.as_object()) else {
+        diffs.push(ConfigDiff {
+            field: path.to_string(),
+            from: from.clone(),
+            to: to.clone(),
+        });
+        return;
+    };
+    let mut keys: Vec<&String> = from_obj.keys().chain(to_obj.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    let null = serde_json::Value::Null;
+    for key in keys {
+        let field = if path.is_empty() {
+            key.clone()
+        } else {
+            format!("{path}.{key}")
+        };
+        collect_diffs(
+            &field,
+            from_obj.get(key).unwrap_or(&null),
+            to_obj.get(key).unwrap_or(&null),
+            diffs,
+        );
+    }
+}
+
 impl AppConfig {
+    /// 設定ファイルの JSON Schema（外部エディタや CI での検証用）
+    pub fn json_schema() -> serde_json::Value {
+        serde_json::to_value(schemars::schema_for!(AppConfig)).unwrap_or_default()
+    }
+
+    /// `self` から `other` への変更点をキー順に列挙する（`last_modified` は対象外）
+    pub fn diff(&self, other: &AppConfig) -> Vec<ConfigDiff> {
+        let mut diffs = Vec::new();
+        if let (Ok(from), Ok(to)) = (serde_json::to_value(self), serde_json::to_value(other)) {
+            collect_diffs("", &from, &to, &mut diffs);
+        }
+        diffs
+    }
+
     pub fn validate(&self) -> Result<(), ConfigError> {
         if self.schema_version != SCHEMA_VERSION {
             return Err(ConfigError::ValidationError(format!(
@@ -362,6 +894,24 @@ impl AppConfig {
             )));
         }
 
+        if let Position::Custom { x_pct, y_pct } = self.display.position {
+            if !(0.0..=1.0).contains(&x_pct) || !(0.0..=1.0).contains(&y_pct) {
+                return Err(ConfigError::ValidationError(
+                    "display.position custom x_pct/y_pct must be within 0..=1".into(),
+                ));
+            }
+        }
+        if self
+            .display
+            .monitor_relative_positions
+            .values()
+            .flatten()
+            .any(|pct| !(0.0..=1.0).contains(pct))
+        {
+            return Err(ConfigError::ValidationError(
+                "display.monitor_relative_positions must be within 0..=1".into(),
+            ));
+        }
         if self.display.max_items == 0 {
             return Err(ConfigError::ValidationError("display.max_items must be > 0".into()));
         }
@@ -382,6 +932,18 @@ impl AppConfig {
         if !(0.0..=1.0).contains(&self.style.opacity) {
             return Err(ConfigError::ValidationError("style.opacity must be within 0..=1".into()));
         }
+        for (name, weight) in [
+            ("key_font_weight", self.style.key_font_weight),
+            ("ime_font_weight", self.style.ime_font_weight),
+            ("clipboard_font_weight", self.style.clipboard_font_weight),
+        ] {
+            if !(1..=999).contains(&weight) {
+                return Err(ConfigError::ValidationError(format!(
+                    "style.{} must be within 1..=999",
+                    name
+                )));
+            }
+        }
 
         if self.behavior.clipboard_max_chars == 0 {
             return Err(ConfigError::ValidationError(
@@ -393,12 +955,75 @@ impl AppConfig {
                 "behavior.repeat_timeout_ms must be > 0".into(),
             ));
         }
+        if let Some(key) = self
+            .behavior
+            .per_key_repeat_timeout
+            .iter()
+            .find_map(|(key, ms)| (*ms == 0).then_some(key))
+        {
+            return Err(ConfigError::ValidationError(format!(
+                "behavior.per_key_repeat_timeout \"{key}\" must be > 0"
+            )));
+        }
         if self.behavior.max_group_size == 0 {
             return Err(ConfigError::ValidationError(
                 "behavior.max_group_size must be > 0".into(),
             ));
         }
+        if self.behavior.ime_candidate_max_items == 0 {
+            return Err(ConfigError::ValidationError(
+                "behavior.ime_candidate_max_items must be > 0".into(),
+            ));
+        }
+        if self.behavior.app_key_filters.iter().any(|f| f.app_name.trim().is_empty()) {
+            return Err(ConfigError::ValidationError(
+                "behavior.app_key_filters[].app_name must not be empty".into(),
+            ));
+        }
+        if let Some(kind) = self
+            .behavior
+            .pinned_item_kinds
+            .iter()
+            .find(|k| !DisplayItemKind::KIND_NAMES.contains(&k.as_str()))
+        {
+            return Err(ConfigError::ValidationError(format!(
+                "behavior.pinned_item_kinds contains unknown kind \"{kind}\" (expected one of: {})",
+                DisplayItemKind::KIND_NAMES.join(", ")
+            )));
+        }
+
+        if let Some(key) = self
+            .behavior
+            .romaji_overrides
+            .keys()
+            .find(|key| key.is_empty() || !key.chars().all(|c| c.is_ascii_alphabetic()))
+        {
+            return Err(ConfigError::ValidationError(format!(
+                "behavior.romaji_overrides key \"{key}\" must be ASCII letters only"
+            )));
+        }
 
+        if !self.hotkey.panic_hide.is_empty()
+            && self.hotkey.panic_hide.eq_ignore_ascii_case(&self.hotkey.toggle)
+        {
+            return Err(ConfigError::ValidationError(
+                "hotkey.panic_hide must differ from hotkey.toggle".into(),
+            ));
+        }
+        if !self.hotkey.privacy_toggle.is_empty()
+            && (self.hotkey.privacy_toggle.eq_ignore_ascii_case(&self.hotkey.toggle)
+                || self.hotkey.privacy_toggle.eq_ignore_ascii_case(&self.hotkey.panic_hide))
+        {
+            return Err(ConfigError::ValidationError(
+                "hotkey.privacy_toggle must differ from hotkey.toggle and hotkey.panic_hide".into(),
+            ));
+        }
+        if self.hotkey.extra_toggles.len() > MAX_EXTRA_TOGGLES {
+            return Err(ConfigError::ValidationError(format!(
+                "hotkey.extra_toggles must have at most {} entries",
+                MAX_EXTRA_TOGGLES
+            )));
+        }
         if self.performance.osd_width <= 0 || self.performance.osd_height <= 0 {
             return Err(ConfigError::ValidationError(
                 "performance.osd_width/osd_height must be > 0".into(),
@@ -414,11 +1039,41 @@ impl AppConfig {
                 "performance.frame_interval_ms must be > 0".into(),
             ));
         }
+        if self.performance.fade_frame_interval_ms == 0 {
+            return Err(ConfigError::ValidationError(
+                "performance.fade_frame_interval_ms must be > 0".into(),
+            ));
+        }
         if self.performance.config_reload_interval_ms == 0 {
             return Err(ConfigError::ValidationError(
                 "performance.config_reload_interval_ms must be > 0".into(),
             ));
         }
+        if self.performance.http_server_port == Some(0) {
+            return Err(ConfigError::ValidationError(
+                "performance.http_server_port must be > 0".into(),
+            ));
+        }
+        if self.performance.websocket_port == Some(0) {
+            return Err(ConfigError::ValidationError(
+                "performance.websocket_port must be > 0".into(),
+            ));
+        }
+        if self.performance.websocket_port.is_some()
+            && self.performance.websocket_port == self.performance.http_server_port
+        {
+            return Err(ConfigError::ValidationError(
+                "performance.websocket_port must differ from http_server_port".into(),
+            ));
+        }
+        if let Some(origin) = &self.performance.http_allowed_origin {
+            let origin = origin.trim();
+            if origin.is_empty() || origin == "*" {
+                return Err(ConfigError::ValidationError(
+                    "performance.http_allowed_origin must be a single origin, not empty or \"*\"".into(),
+                ));
+            }
+        }
 
         if self.diagnostics.max_file_bytes < 1024 {
             return Err(ConfigError::ValidationError(
@@ -431,6 +1086,12 @@ impl AppConfig {
             ));
         }
 
+        if !(self.behavior.drag_min_pixels > 0.0 && self.behavior.drag_min_pixels.is_finite()) {
+            return Err(ConfigError::ValidationError(
+                "behavior.drag_min_pixels must be > 0".into(),
+            ));
+        }
+
         if self.animation.ghost_threshold_px <= 0.0 {
             return Err(ConfigError::ValidationError(
                 "animation.ghost_threshold_px must be > 0".into(),
@@ -447,8 +1108,20 @@ impl AppConfig {
 
     pub fn load_strict(config_path: &Path) -> Result<Self, ConfigError> {
         let content = std::fs::read_to_string(config_path)?;
-        let mut config: Self = serde_json::from_str(&content)?;
+        let format = ConfigFormat::from_path(config_path);
+        let value = format.parse_value(&content)?;
+        let needs_migration = value.get("schema_version").and_then(|v| v.as_u64()) == Some(1);
+
+        let mut config = if needs_migration {
+            migrate(value)?
+        } else {
+            format.parse(&content)?
+        };
         config.validate()?;
+        if needs_migration {
+            // 移行後の内容で書き戻し、次回以降は通常の読み込みにする
+            config.save_atomic(config_path)?;
+        }
         config.last_modified = std::fs::metadata(config_path)?.modified().ok();
         Ok(config)
     }
@@ -481,12 +1154,12 @@ impl AppConfig {
             std::fs::create_dir_all(parent)?;
         }
 
-        let json = serde_json::to_string_pretty(self)?;
+        let content = ConfigFormat::from_path(config_path).serialize(self)?;
 
         let tmp_path = temp_path_for(config_path);
         {
             let mut file = File::create(&tmp_path)?;
-            file.write_all(json.as_bytes())?;
+            file.write_all(content.as_bytes())?;
             file.sync_all()?;
         }
 
@@ -495,7 +1168,7 @@ impl AppConfig {
                 // Fallback for environments where ReplaceFileW returns ACCESS_DENIED.
                 let direct_write = (|| -> Result<(), std::io::Error> {
                     let mut file = File::create(config_path)?;
-                    file.write_all(json.as_bytes())?;
+                    file.write_all(content.as_bytes())?;
                     file.sync_all()?;
                     Ok(())
                 })();
@@ -597,6 +1270,83 @@ mod tests {
         assert!(parsed.is_err());
     }
 
+    #[test]
+    fn custom_position_roundtrip_and_range_check() {
+        let mut cfg = AppConfig::default();
+        cfg.display.position = Position::Custom { x_pct: 0.25, y_pct: 0.75 };
+        let value = serde_json::to_value(&cfg).unwrap();
+        assert_eq!(
+            value["display"]["position"],
+            serde_json::json!({ "custom": { "x_pct": 0.25, "y_pct": 0.75 } })
+        );
+        let parsed = serde_json::from_value::<AppConfig>(value).unwrap();
+        assert_eq!(parsed.display.position, Position::Custom { x_pct: 0.25, y_pct: 0.75 });
+        assert!(parsed.validate().is_ok());
+
+        cfg.display.position = Position::Custom { x_pct: 1.5, y_pct: 0.0 };
+        assert!(matches!(cfg.validate(), Err(ConfigError::ValidationError(_))));
+
+        cfg.display.position = Position::BottomCenter;
+        cfg.display
+            .monitor_relative_positions
+            .insert("\\\\.\\DISPLAY1".into(), [0.5, 1.0]);
+        assert!(cfg.validate().is_ok());
+        cfg.display
+            .monitor_relative_positions
+            .insert("\\\\.\\DISPLAY2".into(), [-0.1, 0.0]);
+        assert!(matches!(cfg.validate(), Err(ConfigError::ValidationError(_))));
+    }
+
+    #[test]
+    fn ime_display_mode_accepts_old_field_name() {
+        let mut value = serde_json::to_value(AppConfig::default()).unwrap();
+        assert_eq!(value["behavior"]["ime_display_mode"], "auto");
+        let behavior = value["behavior"].as_object_mut().unwrap();
+        behavior.remove("ime_display_mode");
+        behavior.insert("ime_fallback_kana".into(), "katakana".into());
+        let parsed = serde_json::from_value::<AppConfig>(value).unwrap();
+        assert_eq!(parsed.behavior.ime_display_mode, ImeDisplayMode::Katakana);
+    }
+
+    #[test]
+    fn display_follow_defaults_to_foreground_window() {
+        let mut value = serde_json::to_value(AppConfig::default()).unwrap();
+        assert_eq!(value["display"]["follow"], "foreground-window");
+        value["display"]["follow"] = "mouse-cursor".into();
+        let parsed = serde_json::from_value::<AppConfig>(value).unwrap();
+        assert_eq!(parsed.display.follow, FollowMode::MouseCursor);
+    }
+
+    #[test]
+    fn snap_to_edge_accepts_snap_distance_name() {
+        let mut value = serde_json::to_value(AppConfig::default()).unwrap();
+        let display = value["display"].as_object_mut().unwrap();
+        display.remove("snap_to_edge_px");
+        display.insert("snap_distance_px".into(), 0.into());
+        let parsed = serde_json::from_value::<AppConfig>(value).unwrap();
+        assert_eq!(parsed.display.snap_to_edge_px, 0);
+    }
+
+    #[test]
+    fn http_allowed_origin_rejects_wildcard() {
+        let mut cfg = AppConfig::default();
+        cfg.performance.http_allowed_origin = Some("http://localhost:3000".into());
+        assert!(cfg.validate().is_ok());
+
+        cfg.performance.http_allowed_origin = Some("*".into());
+        assert!(matches!(cfg.validate(), Err(ConfigError::ValidationError(_))));
+    }
+
+    #[test]
+    fn romaji_override_keys_must_be_ascii_letters() {
+        let mut cfg = AppConfig::default();
+        cfg.behavior.romaji_overrides.insert("tu".into(), "つ".into());
+        assert!(cfg.validate().is_ok());
+
+        cfg.behavior.romaji_overrides.insert("t-u".into(), "つ".into());
+        assert!(matches!(cfg.validate(), Err(ConfigError::ValidationError(_))));
+    }
+
     #[test]
     fn save_atomic_and_load_strict_roundtrip() {
         let path = temp_config_path("roundtrip");
@@ -612,6 +1362,73 @@ mod tests {
         let _ = std::fs::remove_file(path);
     }
 
+    #[test]
+    fn toml_config_roundtrip_and_rejects_unknown_keys() {
+        let path = temp_config_path("toml-roundtrip").with_extension("toml");
+        let mut cfg = AppConfig::default();
+        cfg.style.font_size = 28.0;
+        cfg.performance.http_server_port = Some(8765);
+        cfg.save_atomic(&path).unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.contains("[style]"));
+        let loaded = AppConfig::load_strict(&path).unwrap();
+        assert_eq!(loaded.style.font_size, 28.0);
+        assert_eq!(loaded.performance.http_server_port, Some(8765));
+
+        std::fs::write(&path, format!("# comment\nunknown = 1\n{content}")).unwrap();
+        assert!(matches!(
+            AppConfig::load_strict(&path),
+            Err(ConfigError::TomlParseError(_))
+        ));
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn load_strict_migrates_v1_config_to_current_schema() {
+        let path = temp_config_path("migrate-v1");
+        let mut value = serde_json::to_value(AppConfig::default()).unwrap();
+        value["schema_version"] = serde_json::json!(1);
+        value["style"]["font_size"] = serde_json::json!(26.0);
+        let obj = value.as_object_mut().unwrap();
+        obj.remove("animation");
+        obj["behavior"].as_object_mut().unwrap().remove("show_shift_for_typed_symbols");
+        std::fs::write(&path, serde_json::to_string_pretty(&value).unwrap()).unwrap();
+
+        let loaded = AppConfig::load_strict(&path).unwrap();
+        assert_eq!(loaded.schema_version, SCHEMA_VERSION);
+        assert_eq!(loaded.style.font_size, 26.0);
+
+        let saved: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved["schema_version"], serde_json::json!(SCHEMA_VERSION));
+        assert!(saved.get("animation").is_some());
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn json_schema_reflects_serde_renames() {
+        let schema = AppConfig::json_schema().to_string();
+        assert!(schema.contains("\"top-left\""));
+        assert!(schema.contains("\"ease_out\""));
+        assert!(schema.contains("\"blocklist\""));
+        assert!(schema.contains("\"additionalProperties\":false"));
+        assert!(!schema.contains("last_modified"));
+    }
+
+    #[test]
+    fn diagnostics_accepts_jsonl_format_alias() {
+        let mut value = serde_json::to_value(AppConfig::default()).unwrap();
+        let diagnostics = value["diagnostics"].as_object_mut().unwrap();
+        diagnostics.remove("log_format");
+        diagnostics.insert("format".to_string(), serde_json::json!("jsonl"));
+
+        let parsed: AppConfig = serde_json::from_value(value).unwrap();
+        assert_eq!(parsed.diagnostics.log_format, LogFormat::Json);
+    }
+
     #[test]
     fn default_uses_single_cell_key_transition_mode() {
         let cfg = AppConfig::default();
@@ -620,4 +1437,35 @@ mod tests {
             KeyTransitionMode::SingleCell
         );
     }
+
+    #[test]
+    fn diff_lists_changed_fields_by_key_path() {
+        let old = AppConfig::default();
+        let mut new = old.clone();
+        assert!(old.diff(&new).is_empty());
+
+        new.last_modified = Some(SystemTime::now());
+        new.display.max_items = old.display.max_items + 1;
+        new.behavior.show_repeat_count = !old.behavior.show_repeat_count;
+        new.behavior
+            .per_key_repeat_timeout
+            .insert("Backspace".into(), 200);
+
+        let diffs = old.diff(&new);
+        let fields: Vec<&str> = diffs.iter().map(|d| d.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            [
+                "behavior.per_key_repeat_timeout.Backspace",
+                "behavior.show_repeat_count",
+                "display.max_items",
+            ]
+        );
+        assert_eq!(diffs[0].from, serde_json::Value::Null);
+        assert_eq!(diffs[0].to, serde_json::json!(200));
+        assert_eq!(
+            diffs[2].to_string(),
+            format!("display.max_items: {} -> {}", old.display.max_items, new.display.max_items)
+        );
+    }
 }
@@ -40,6 +40,12 @@ pub enum ConfigError {
     #[error("parse error: {0}")]
     ParseError(#[from] serde_json::Error),
 
+    #[error("TOML parse error: {0}")]
+    TomlParseError(#[from] toml::de::Error),
+
+    #[error("TOML serialize error: {0}")]
+    TomlSerializeError(#[from] toml::ser::Error),
+
     #[error("validation error: {0}")]
     ValidationError(String),
 }
@@ -1,16 +1,21 @@
 use std::time::Instant;
 
+use serde::{Deserialize, Serialize};
+
 use crate::config::AppConfig;
 use crate::key::KeyCode;
 
 /// 全入力イベントの統合型
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum InputEvent {
     Key(KeyEvent),
     Mouse(MouseEvent),
     Ime(ImeEvent),
     Clipboard(ClipboardEvent),
     LockState(LockStateEvent),
+    Gamepad(GamepadEvent),
+    /// IME の変換候補一覧（候補一覧が閉じたら空）
+    ImeCandidates(Vec<String>),
     /// 設定UI表示中などの「プレビューモード」切替
     PreviewMode { enabled: bool },
     /// 設定UI側のドラフト設定を反映するためのプレビュー用設定更新
@@ -21,12 +26,42 @@ pub enum InputEvent {
         /// suggested rect [left, top, right, bottom]
         suggested_rect: [i32; 4],
     },
+    /// モニタ構成の変更通知 (WM_DISPLAYCHANGE)
+    DisplayChanged,
     /// 設定がインポート等で外部から変更された通知
     ConfigChanged,
+    /// OSD 有効/無効の切替要求（IPC等の外部から）
+    ToggleOsd,
+    /// 表示中アイテムの全消去要求
+    ClearDisplay,
+    /// マクロ記録の開始/停止要求（トレイメニューから）
+    MacroRecord { start: bool },
+    /// 記録済みマクロの再生要求（トレイメニューから）
+    MacroPlay,
+    /// 現在の OSD 表示を PNG に保存する要求（ホットキーから）
+    Screenshot,
+    /// 表示状態のスナップショット要求（IPC の `status` から）
+    StateSnapshot,
+}
+
+impl InputEvent {
+    /// 表示状態ではなくメインループ側で処理する制御イベントか
+    pub fn is_control(&self) -> bool {
+        !matches!(
+            self,
+            InputEvent::Key(_)
+                | InputEvent::Mouse(_)
+                | InputEvent::Ime(_)
+                | InputEvent::Clipboard(_)
+                | InputEvent::LockState(_)
+                | InputEvent::Gamepad(_)
+                | InputEvent::ImeCandidates(_)
+        )
+    }
 }
 
 /// キーイベント
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KeyEvent {
     /// キーコード（VK_*に対応、テンキー区別済み）
     pub key: KeyCode,
@@ -41,41 +76,57 @@ pub struct KeyEvent {
     /// 押下時の実入力文字（Shift/AltGr/レイアウト反映済み、ToUnicodeEx由来）。
     /// OEMキー等で `label()` が `?` を返すケースのフォールバック表示に使う。
     pub text: Option<String>,
-    /// イベント発生時刻
+    /// イベント発生時刻（記録ファイルには残さず、読み込み時刻で埋める）
+    #[serde(skip, default = "Instant::now")]
     pub timestamp: Instant,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum KeyAction {
     Down,
     Up,
 }
 
 /// 修飾キー状態
-#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Modifiers {
     pub ctrl: bool,
     pub shift: bool,
     pub alt: bool,
     pub win: bool,
+    /// AltGr（右Alt + 合成された左Ctrl）。ctrl/alt とは別扱い
+    pub altgr: bool,
 }
 
 impl Modifiers {
     pub fn any(&self) -> bool {
-        self.ctrl || self.shift || self.alt || self.win
+        self.ctrl || self.shift || self.alt || self.win || self.altgr
     }
 }
 
 /// マウスイベント
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MouseEvent {
     pub button: MouseButton,
     pub action: MouseAction,
     pub position: (i32, i32),
+    #[serde(default)]
+    pub source: MouseSource,
+    #[serde(skip, default = "Instant::now")]
     pub timestamp: Instant,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// マウスイベントの取得経路（`MouseInputMode::Both` での重複判定用）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MouseSource {
+    /// WH_MOUSE_LL フック
+    #[default]
+    Hook,
+    /// Raw Input (WM_INPUT)
+    RawInput,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum MouseButton {
     Left,
     Right,
@@ -84,7 +135,7 @@ pub enum MouseButton {
     X2,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum MouseAction {
     Down,
     Up,
@@ -92,13 +143,14 @@ pub enum MouseAction {
 }
 
 /// IMEイベント
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ImeEvent {
     pub kind: ImeEventKind,
+    #[serde(skip, default = "Instant::now")]
     pub timestamp: Instant,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ImeEventKind {
     /// IME ON/OFF切替
     StateChanged { enabled: bool },
@@ -106,27 +158,108 @@ pub enum ImeEventKind {
     CompositionUpdate { text: String },
     /// 変換確定
     CompositionEnd { result: String },
+    /// 候補一覧で選択中の変換候補
+    CandidateUpdate { text: String },
+    /// 入力モード（全角カタカナかどうか）の切替
+    ConversionModeChanged { katakana: bool },
 }
 
 /// クリップボードイベント
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClipboardEvent {
     pub content: ClipboardContent,
+    #[serde(skip, default = "Instant::now")]
     pub timestamp: Instant,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ClipboardContent {
-    Text(String),
+    Text {
+        text: String,
+        /// コピー元アプリの実行ファイル名（クリップボード所有者が取れた場合）
+        source_app: Option<String>,
+        /// テキストを取り出したクリップボード形式
+        format: ClipboardTextFormat,
+    },
+    /// エクスプローラー等からコピーされたファイルのパス一覧
+    Files(Vec<String>),
     Image { width: u32, height: u32 },
     Other,
 }
 
+/// クリップボードテキストの取得元形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ClipboardTextFormat {
+    /// CF_UNICODETEXT
+    #[default]
+    Unicode,
+    /// "HTML Format"（タグを除去したもの）
+    Html,
+    /// "Rich Text Format"（制御語を除去したもの）
+    Rtf,
+}
+
+/// ゲームパッド（XInput）のボタンイベント
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GamepadEvent {
+    /// XInput のユーザーインデックス (0-3)
+    pub controller_id: u8,
+    pub button: GamepadButton,
+    pub action: KeyAction,
+    #[serde(skip, default = "Instant::now")]
+    pub timestamp: Instant,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GamepadButton {
+    A,
+    B,
+    X,
+    Y,
+    LeftShoulder,
+    RightShoulder,
+    LeftTrigger,
+    RightTrigger,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+    Start,
+    Back,
+    LeftThumb,
+    RightThumb,
+}
+
+impl GamepadButton {
+    /// 表示用ラベルを返す
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::A => "A",
+            Self::B => "B",
+            Self::X => "X",
+            Self::Y => "Y",
+            Self::LeftShoulder => "LB",
+            Self::RightShoulder => "RB",
+            Self::LeftTrigger => "LT",
+            Self::RightTrigger => "RT",
+            Self::DPadUp => "Up",
+            Self::DPadDown => "Down",
+            Self::DPadLeft => "Left",
+            Self::DPadRight => "Right",
+            Self::Start => "Start",
+            Self::Back => "Back",
+            Self::LeftThumb => "LS",
+            Self::RightThumb => "RS",
+        }
+    }
+}
+
 /// Lock状態イベント
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LockStateEvent {
     pub caps_lock: bool,
     pub num_lock: bool,
     pub scroll_lock: bool,
+    #[serde(skip, default = "Instant::now")]
     pub timestamp: Instant,
 }
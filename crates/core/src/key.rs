@@ -1,6 +1,8 @@
+use serde::{Deserialize, Serialize};
+
 /// キーコード。テンキーとメインキーを別値として定義。
 /// Win32 VK_*コードをベースに、テンキーEnterを0x200|0x0Dで区別。
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct KeyCode(pub u32);
 
 impl KeyCode {
@@ -236,6 +238,21 @@ impl KeyCode {
         }
     }
 
+    /// 修飾キーの左右を区別したラベルを返す（修飾キー以外は `label()` と同じ）
+    pub fn label_lr(&self) -> &'static str {
+        match *self {
+            Self::L_CTRL => "LCtrl",
+            Self::R_CTRL => "RCtrl",
+            Self::L_ALT => "LAlt",
+            Self::R_ALT => "RAlt",
+            Self::L_SHIFT => "LShift",
+            Self::R_SHIFT => "RShift",
+            Self::L_WIN => "LWin",
+            Self::R_WIN => "RWin",
+            _ => self.label(),
+        }
+    }
+
     /// 修飾キーかどうか
     pub fn is_modifier(&self) -> bool {
         matches!(
@@ -2,17 +2,26 @@ pub mod config;
 pub mod error;
 pub mod event;
 pub mod key;
+pub mod recorder;
+pub mod romaji;
 pub mod state;
 
 pub use config::{
-    AnimationConfig, AppConfig, BehaviorConfig, DiagnosticsConfig, DiagnosticsLevel, DisplayConfig,
-    FadeOutCurve, GhostModifier, HotkeyConfig, KeyTransitionMode, MenuLanguage, PerformanceConfig,
-    Position, PrivacyConfig, SCHEMA_VERSION, ShortcutDef, StartupConfig, StyleConfig, TrayConfig,
+    AnimationConfig, AppConfig, AppKeyFilter, BehaviorConfig, ConfigDiff, DiagnosticsConfig, DiagnosticsLevel, DisplayConfig,
+    FadeOutCurve, FollowMode, GhostModifier, HotkeyConfig, HotkeyMode, ImeDisplayMode, KeyTransitionMode, LogFormat, LogTimestamp, MAX_EXTRA_TOGGLES, MenuLanguage, MouseInputMode,
+    OsdLayout, PerformanceConfig,
+    Position, PrivacyConfig, PrivacyMode, SCHEMA_VERSION, ShortcutDef, StartupConfig, StyleConfig,
+    TextRenderingMode, TrayConfig,
 };
 pub use error::{AppError, ConfigError, HookError, RenderError};
 pub use event::{
-    ClipboardContent, ClipboardEvent, ImeEvent, ImeEventKind, InputEvent, KeyAction, KeyEvent,
-    LockStateEvent, Modifiers, MouseAction, MouseButton, MouseEvent,
+    ClipboardContent, ClipboardEvent, ClipboardTextFormat, GamepadButton, GamepadEvent, ImeEvent, ImeEventKind,
+    InputEvent, KeyAction, KeyEvent, LockStateEvent, Modifiers, MouseAction, MouseButton, MouseEvent,
+    MouseSource,
 };
 pub use key::KeyCode;
-pub use state::{DisplayItem, DisplayItemKind, DisplayPhase, DisplayState, KeyStrokeEntry};
+pub use recorder::{replay_events, EventRecorder};
+pub use romaji::{to_hiragana, to_katakana};
+pub use state::{
+    DisplayItem, DisplayItemKind, DisplayPhase, DisplayState, DisplayStateSnapshot, DisplayStateStats, KeyStrokeEntry,
+};
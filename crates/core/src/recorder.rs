@@ -0,0 +1,156 @@
+//! 入力イベントの記録（JSON Lines）と再生
+//!
+//! 1 行 1 イベントで `{"t_ms": 記録開始からの経過 ms, "event": InputEvent}` を書き出す。
+//! イベント内の `Instant` は記録せず、再生時に送出時刻で埋め直す。
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::SyncSender;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::event::InputEvent;
+
+#[derive(Serialize)]
+struct RecordLine<'a> {
+    t_ms: u64,
+    event: &'a InputEvent,
+}
+
+/// 再生時はイベントを送出直前まで JSON のまま持ち、タイムスタンプを送出時刻にする
+#[derive(Deserialize)]
+struct ReplayLine {
+    t_ms: u64,
+    event: serde_json::Value,
+}
+
+/// イベントを JSON Lines ファイルへ追記する
+pub struct EventRecorder {
+    file: File,
+    path: PathBuf,
+    started: Instant,
+}
+
+impl EventRecorder {
+    /// 記録先を空にして開く（親ディレクトリがなければ作る）
+    ///
+    /// `t_ms` は開いた時点から数え直すので、前回の記録に追記すると再生時に
+    /// 時刻が巻き戻ってしまう。1 ファイル 1 セッションにする。
+    pub fn open(path: &Path) -> io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(path)?;
+        Ok(Self {
+            file,
+            path: path.to_path_buf(),
+            started: Instant::now(),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 1 イベントを 1 行として書き出す
+    pub fn record(&mut self, event: &InputEvent) -> io::Result<()> {
+        let line = RecordLine {
+            t_ms: self.started.elapsed().as_millis() as u64,
+            event,
+        };
+        let mut json = serde_json::to_string(&line)?;
+        json.push('\n');
+        self.file.write_all(json.as_bytes())
+    }
+}
+
+/// 記録ファイルを読み、元の相対時刻どおりに `tx` へイベントを送り直す
+///
+/// 受信側が閉じたら途中で終了する。戻り値は送出したイベント数。
+/// 壊れた行があれば `InvalidData` で中断する。
+pub fn replay_events(path: &Path, tx: SyncSender<InputEvent>) -> io::Result<usize> {
+    let reader = BufReader::new(File::open(path)?);
+    let started = Instant::now();
+    let mut sent = 0;
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let invalid = |e: serde_json::Error| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {e}", index + 1))
+        };
+        let record: ReplayLine = serde_json::from_str(&line).map_err(invalid)?;
+        let due = started + Duration::from_millis(record.t_ms);
+        let now = Instant::now();
+        if due > now {
+            std::thread::sleep(due - now);
+        }
+        let event: InputEvent = serde_json::from_value(record.event).map_err(invalid)?;
+        if tx.send(event).is_err() {
+            break;
+        }
+        sent += 1;
+    }
+    Ok(sent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::{KeyAction, KeyEvent, Modifiers};
+    use crate::key::KeyCode;
+
+    #[test]
+    fn recorded_events_replay_in_order() {
+        let dir = std::env::temp_dir().join(format!("ystrokey-recorder-{}", std::process::id()));
+        let path = dir.join("events.jsonl");
+        let _ = fs::remove_file(&path);
+
+        let mut recorder = EventRecorder::open(&path).unwrap();
+        recorder
+            .record(&InputEvent::Key(KeyEvent {
+                key: KeyCode::KEY_A,
+                action: KeyAction::Down,
+                modifiers: Modifiers {
+                    ctrl: true,
+                    ..Modifiers::default()
+                },
+                is_numpad: false,
+                scan_code: 0x1E,
+                text: Some("a".into()),
+                timestamp: Instant::now(),
+            }))
+            .unwrap();
+        recorder.record(&InputEvent::ImeCandidates(vec!["漢字".into()])).unwrap();
+        recorder.record(&InputEvent::ClearDisplay).unwrap();
+        drop(recorder);
+
+        let (tx, rx) = std::sync::mpsc::sync_channel(8);
+        assert_eq!(replay_events(&path, tx).unwrap(), 3);
+        let events: Vec<InputEvent> = rx.try_iter().collect();
+        assert!(matches!(
+            &events[0],
+            InputEvent::Key(key) if key.key == KeyCode::KEY_A && key.modifiers.ctrl && key.text.as_deref() == Some("a")
+        ));
+        assert!(matches!(&events[1], InputEvent::ImeCandidates(list) if list == &["漢字"]));
+        assert!(matches!(events[2], InputEvent::ClearDisplay));
+
+        // 開き直すと前回の記録は消える
+        EventRecorder::open(&path).unwrap();
+        let (tx, _rx) = std::sync::mpsc::sync_channel(8);
+        assert_eq!(replay_events(&path, tx).unwrap(), 0);
+
+        fs::write(&path, "{\"t_ms\":0,\"event\":\"Bogus\"}\n").unwrap();
+        let (tx, _rx) = std::sync::mpsc::sync_channel(8);
+        let err = replay_events(&path, tx).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
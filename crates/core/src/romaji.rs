@@ -0,0 +1,439 @@
+use std::collections::HashMap;
+
+/// ローマ字をひらがなに変換する（末尾の未確定の綴りは出力しない）
+pub fn to_hiragana(romaji: &str) -> String {
+    to_hiragana_with_overrides(romaji, &HashMap::new())
+}
+
+/// `overrides` は組み込み表・促音・撥音の判定より先に、最長一致で適用する
+pub fn to_hiragana_with_overrides(romaji: &str, overrides: &HashMap<String, String>) -> String {
+    let s: String = romaji
+        .chars()
+        .filter(|c| c.is_ascii_alphabetic() || *c == '\'')
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+
+    let bytes = s.as_bytes();
+    let mut out = String::new();
+    let mut i = 0usize;
+    let max_override_len = overrides.keys().map(String::len).max().unwrap_or(0);
+
+    while i < bytes.len() {
+        let longest = max_override_len.min(bytes.len() - i);
+        if let Some((len, kana)) = (1..=longest)
+            .rev()
+            .find_map(|len| overrides.get(&s[i..i + len]).map(|kana| (len, kana)))
+        {
+            out.push_str(kana);
+            i += len;
+            continue;
+        }
+
+        // 促音（小さい「っ」）: 子音重複（nn除く）
+        if i + 1 < bytes.len()
+            && bytes[i] == bytes[i + 1]
+            && is_romaji_consonant(bytes[i] as char)
+            && bytes[i] != b'n'
+        {
+            out.push('っ');
+            i += 1;
+            continue;
+        }
+
+        // アポストロフィは n の区切り（"n'" で「ん」）。それ以外の位置では読み捨てる
+        if bytes[i] == b'\'' {
+            i += 1;
+            continue;
+        }
+        if bytes[i] == b'n' && bytes.get(i + 1) == Some(&b'\'') {
+            out.push('ん');
+            i += 2;
+            continue;
+        }
+
+        // 「ん」処理
+        if bytes[i] == b'n' {
+            if i + 1 == bytes.len() {
+                break; // 末尾nは確定待ち
+            }
+            let next = bytes[i + 1] as char;
+            if next == 'n' {
+                out.push('ん');
+                // "nn" の後が母音・y なら 2 つ目の n は次の音節（"onna"）、それ以外はまとめて消費（"kannji"）
+                let after = bytes.get(i + 2).map(|&b| b as char);
+                let second_starts_syllable = after.is_some_and(|c| is_romaji_vowel(c) || c == 'y');
+                i += if second_starts_syllable { 1 } else { 2 };
+                continue;
+            }
+            if !is_romaji_vowel(next) && next != 'y' {
+                out.push('ん');
+                i += 1;
+                continue;
+            }
+        }
+
+        if i + 3 <= bytes.len() {
+            let chunk = &s[i..i + 3];
+            if let Some(kana) = romaji_map_3(chunk) {
+                out.push_str(kana);
+                i += 3;
+                continue;
+            }
+        }
+
+        if i + 2 <= bytes.len() {
+            let chunk = &s[i..i + 2];
+            if let Some(kana) = romaji_map_2(chunk) {
+                out.push_str(kana);
+                i += 2;
+                continue;
+            }
+        }
+
+        if i + 1 <= bytes.len() {
+            let chunk = &s[i..i + 1];
+            if let Some(kana) = romaji_map_1(chunk) {
+                out.push_str(kana);
+                i += 1;
+                continue;
+            }
+        }
+
+        // 末尾の未確定1文字は待機し、それ以外の未知綴りは素通しで継続。
+        // 例: "nihogngo" -> "にほgんご"
+        if i + 1 >= bytes.len() {
+            break;
+        }
+        out.push(bytes[i] as char);
+        i += 1;
+    }
+
+    out
+}
+
+/// ローマ字をカタカナに変換する（`to_hiragana` の結果をカタカナへ置き換える）
+pub fn to_katakana(romaji: &str) -> String {
+    to_katakana_with_overrides(romaji, &HashMap::new())
+}
+
+/// `to_hiragana_with_overrides` のカタカナ版（上書き表の値もカタカナへ置き換わる）
+pub fn to_katakana_with_overrides(romaji: &str, overrides: &HashMap<String, String>) -> String {
+    hiragana_to_katakana(&to_hiragana_with_overrides(romaji, overrides))
+}
+
+/// ひらがな（U+3041..U+3096）を対応するカタカナへ置き換える（それ以外はそのまま）
+pub fn hiragana_to_katakana(s: &str) -> String {
+    s.chars()
+        .map(|c| match c {
+            '\u{3041}'..='\u{3096}' => char::from_u32(c as u32 + 0x60).unwrap_or(c),
+            _ => c,
+        })
+        .collect()
+}
+
+/// 確定時、末尾に保留中の n（"nn" の組にならない 1 文字）があれば「ん」になるよう補う
+pub fn flush_trailing_n(romaji: &mut String) {
+    let trailing = romaji.chars().rev().take_while(|&c| c == 'n').count();
+    if trailing % 2 == 1 {
+        romaji.push('n');
+    }
+}
+
+fn is_romaji_vowel(c: char) -> bool {
+    matches!(c, 'a' | 'i' | 'u' | 'e' | 'o')
+}
+
+fn is_romaji_consonant(c: char) -> bool {
+    c.is_ascii_alphabetic() && !is_romaji_vowel(c)
+}
+
+fn romaji_map_3(s: &str) -> Option<&'static str> {
+    let v = match s {
+        "kya" => "きゃ",
+        "kyu" => "きゅ",
+        "kyo" => "きょ",
+        "gya" => "ぎゃ",
+        "gyu" => "ぎゅ",
+        "gyo" => "ぎょ",
+        "sha" | "sya" => "しゃ",
+        "shu" | "syu" => "しゅ",
+        "sho" | "syo" => "しょ",
+        "cha" | "tya" | "cya" => "ちゃ",
+        "chu" | "tyu" | "cyu" => "ちゅ",
+        "cho" | "tyo" | "cyo" => "ちょ",
+        "nya" => "にゃ",
+        "nyu" => "にゅ",
+        "nyo" => "にょ",
+        "hya" => "ひゃ",
+        "hyu" => "ひゅ",
+        "hyo" => "ひょ",
+        "mya" => "みゃ",
+        "myu" => "みゅ",
+        "myo" => "みょ",
+        "rya" => "りゃ",
+        "ryu" => "りゅ",
+        "ryo" => "りょ",
+        "bya" => "びゃ",
+        "byu" => "びゅ",
+        "byo" => "びょ",
+        "pya" => "ぴゃ",
+        "pyu" => "ぴゅ",
+        "pyo" => "ぴょ",
+        "jya" | "zya" => "じゃ",
+        "jyu" | "zyu" => "じゅ",
+        "jyo" | "zyo" => "じょ",
+        "shi" => "し",
+        "chi" => "ち",
+        "tsu" => "つ",
+        "dya" => "ぢゃ",
+        "dyu" => "ぢゅ",
+        "dyo" => "ぢょ",
+        // 外来音の拗音
+        "vya" => "ゔゃ",
+        "vyu" => "ゔゅ",
+        "vyo" => "ゔょ",
+        "thi" => "てぃ",
+        "dhi" => "でぃ",
+        "twu" => "とぅ",
+        "dwu" => "どぅ",
+        "kwa" => "くぁ",
+        "kwi" => "くぃ",
+        "kwe" => "くぇ",
+        "kwo" => "くぉ",
+        // x / l 始まりは小書き仮名を単独で入力する綴り
+        "xtu" | "ltu" => "っ",
+        "xya" | "lya" => "ゃ",
+        "xyu" | "lyu" => "ゅ",
+        "xyo" | "lyo" => "ょ",
+        "xwa" | "lwa" => "ゎ",
+        "xka" | "lka" => "ゕ",
+        "xke" | "lke" => "ゖ",
+        _ => return None,
+    };
+    Some(v)
+}
+
+fn romaji_map_2(s: &str) -> Option<&'static str> {
+    let v = match s {
+        "ka" => "か",
+        "ki" => "き",
+        "ku" => "く",
+        "ke" => "け",
+        "ko" => "こ",
+        "ga" => "が",
+        "gi" => "ぎ",
+        "gu" => "ぐ",
+        "ge" => "げ",
+        "go" => "ご",
+        "sa" => "さ",
+        "su" => "す",
+        "se" => "せ",
+        "so" => "そ",
+        "za" => "ざ",
+        "ja" => "じゃ",
+        "ji" => "じ",
+        "ju" => "じゅ",
+        "jo" => "じょ",
+        "zu" => "ず",
+        "ze" => "ぜ",
+        "zo" => "ぞ",
+        "ta" => "た",
+        "te" => "て",
+        "to" => "と",
+        "da" => "だ",
+        "di" => "ぢ",
+        "du" => "づ",
+        "de" => "で",
+        "do" => "ど",
+        "na" => "な",
+        "ni" => "に",
+        "nu" => "ぬ",
+        "ne" => "ね",
+        "no" => "の",
+        "ha" => "は",
+        "hi" => "ひ",
+        "fu" => "ふ",
+        "he" => "へ",
+        "ho" => "ほ",
+        "ba" => "ば",
+        "bi" => "び",
+        "bu" => "ぶ",
+        "be" => "べ",
+        "bo" => "ぼ",
+        "pa" => "ぱ",
+        "pi" => "ぴ",
+        "pu" => "ぷ",
+        "pe" => "ぺ",
+        "po" => "ぽ",
+        "ma" => "ま",
+        "mi" => "み",
+        "mu" => "む",
+        "me" => "め",
+        "mo" => "も",
+        "ya" => "や",
+        "yu" => "ゆ",
+        "yo" => "よ",
+        "ra" => "ら",
+        "ri" => "り",
+        "ru" => "る",
+        "re" => "れ",
+        "ro" => "ろ",
+        "wa" => "わ",
+        // 歴史的仮名遣いの「ゐ」「ゑ」
+        "wi" => "ゐ",
+        "we" => "ゑ",
+        "wo" => "を",
+        "fa" => "ふぁ",
+        "fi" => "ふぃ",
+        "fe" => "ふぇ",
+        "fo" => "ふぉ",
+        "va" => "ゔぁ",
+        "vi" => "ゔぃ",
+        "vu" => "ゔ",
+        "ve" => "ゔぇ",
+        "vo" => "ゔぉ",
+        "xa" | "la" => "ぁ",
+        "xi" | "li" => "ぃ",
+        "xu" | "lu" => "ぅ",
+        "xe" | "le" => "ぇ",
+        "xo" | "lo" => "ぉ",
+        _ => return None,
+    };
+    Some(v)
+}
+
+fn romaji_map_1(s: &str) -> Option<&'static str> {
+    let v = match s {
+        "a" => "あ",
+        "i" => "い",
+        "u" => "う",
+        "e" => "え",
+        "o" => "お",
+        _ => return None,
+    };
+    Some(v)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_all_map_arities() {
+        let cases = [
+            // 1 文字（母音）
+            ("a", "あ"),
+            ("aiueo", "あいうえお"),
+            // 2 文字
+            ("ka", "か"),
+            ("sushi", "すし"),
+            ("xa", "ぁ"),
+            // 3 文字
+            ("kya", "きゃ"),
+            ("tsuki", "つき"),
+            ("xtu", "っ"),
+            // 組み合わせ
+            ("nihongo", "にほんご"),
+            ("kyouha", "きょうは"),
+        ];
+        for (romaji, expected) in cases {
+            assert_eq!(to_hiragana(romaji), expected, "romaji: {romaji}");
+        }
+    }
+
+    #[test]
+    fn every_map_entry_converts() {
+        let cases = [
+            ("a", "あ"), ("i", "い"), ("u", "う"), ("e", "え"),
+            ("o", "お"), ("ka", "か"), ("ki", "き"), ("ku", "く"),
+            ("ke", "け"), ("ko", "こ"), ("ga", "が"), ("gi", "ぎ"),
+            ("gu", "ぐ"), ("ge", "げ"), ("go", "ご"), ("sa", "さ"),
+            ("su", "す"), ("se", "せ"), ("so", "そ"), ("za", "ざ"),
+            ("ja", "じゃ"), ("ji", "じ"), ("ju", "じゅ"), ("jo", "じょ"),
+            ("zu", "ず"), ("ze", "ぜ"), ("zo", "ぞ"), ("ta", "た"),
+            ("te", "て"), ("to", "と"), ("da", "だ"), ("di", "ぢ"),
+            ("du", "づ"), ("de", "で"), ("do", "ど"), ("na", "な"),
+            ("ni", "に"), ("nu", "ぬ"), ("ne", "ね"), ("no", "の"),
+            ("ha", "は"), ("hi", "ひ"), ("fu", "ふ"), ("he", "へ"),
+            ("ho", "ほ"), ("ba", "ば"), ("bi", "び"), ("bu", "ぶ"),
+            ("be", "べ"), ("bo", "ぼ"), ("pa", "ぱ"), ("pi", "ぴ"),
+            ("pu", "ぷ"), ("pe", "ぺ"), ("po", "ぽ"), ("ma", "ま"),
+            ("mi", "み"), ("mu", "む"), ("me", "め"), ("mo", "も"),
+            ("ya", "や"), ("yu", "ゆ"), ("yo", "よ"), ("ra", "ら"),
+            ("ri", "り"), ("ru", "る"), ("re", "れ"), ("ro", "ろ"),
+            ("wa", "わ"), ("wi", "ゐ"), ("we", "ゑ"), ("wo", "を"),
+            ("fa", "ふぁ"), ("fi", "ふぃ"), ("fe", "ふぇ"), ("fo", "ふぉ"),
+            ("va", "ゔぁ"), ("vi", "ゔぃ"), ("vu", "ゔ"), ("ve", "ゔぇ"),
+            ("vo", "ゔぉ"), ("xa", "ぁ"), ("la", "ぁ"), ("xi", "ぃ"),
+            ("li", "ぃ"), ("xu", "ぅ"), ("lu", "ぅ"), ("xe", "ぇ"),
+            ("le", "ぇ"), ("xo", "ぉ"), ("lo", "ぉ"), ("kya", "きゃ"),
+            ("kyu", "きゅ"), ("kyo", "きょ"), ("gya", "ぎゃ"), ("gyu", "ぎゅ"),
+            ("gyo", "ぎょ"), ("sha", "しゃ"), ("sya", "しゃ"), ("shu", "しゅ"),
+            ("syu", "しゅ"), ("sho", "しょ"), ("syo", "しょ"), ("cha", "ちゃ"),
+            ("tya", "ちゃ"), ("cya", "ちゃ"), ("chu", "ちゅ"), ("tyu", "ちゅ"),
+            ("cyu", "ちゅ"), ("cho", "ちょ"), ("tyo", "ちょ"), ("cyo", "ちょ"),
+            ("nya", "にゃ"), ("nyu", "にゅ"), ("nyo", "にょ"), ("hya", "ひゃ"),
+            ("hyu", "ひゅ"), ("hyo", "ひょ"), ("mya", "みゃ"), ("myu", "みゅ"),
+            ("myo", "みょ"), ("rya", "りゃ"), ("ryu", "りゅ"), ("ryo", "りょ"),
+            ("bya", "びゃ"), ("byu", "びゅ"), ("byo", "びょ"), ("pya", "ぴゃ"),
+            ("pyu", "ぴゅ"), ("pyo", "ぴょ"), ("jya", "じゃ"), ("zya", "じゃ"),
+            ("jyu", "じゅ"), ("zyu", "じゅ"), ("jyo", "じょ"), ("zyo", "じょ"),
+            ("shi", "し"), ("chi", "ち"), ("tsu", "つ"), ("dya", "ぢゃ"),
+            ("dyu", "ぢゅ"), ("dyo", "ぢょ"), ("vya", "ゔゃ"), ("vyu", "ゔゅ"),
+            ("vyo", "ゔょ"), ("thi", "てぃ"), ("dhi", "でぃ"), ("twu", "とぅ"),
+            ("dwu", "どぅ"), ("kwa", "くぁ"), ("kwi", "くぃ"), ("kwe", "くぇ"),
+            ("kwo", "くぉ"), ("xtu", "っ"), ("ltu", "っ"), ("xya", "ゃ"),
+            ("lya", "ゃ"), ("xyu", "ゅ"), ("lyu", "ゅ"), ("xyo", "ょ"),
+            ("lyo", "ょ"), ("xwa", "ゎ"), ("lwa", "ゎ"), ("xka", "ゕ"),
+            ("lka", "ゕ"), ("xke", "ゖ"), ("lke", "ゖ"),
+        ];
+        for (romaji, expected) in cases {
+            assert_eq!(to_hiragana(romaji), expected, "romaji: {romaji}");
+        }
+    }
+
+    #[test]
+    fn trailing_n_waits_until_commit() {
+        assert_eq!(to_hiragana("hon"), "ほ");
+        let mut romaji = "hon".to_string();
+        flush_trailing_n(&mut romaji);
+        assert_eq!(to_hiragana(&romaji), "ほん");
+
+        // "nn" は既に「ん」なので補わない
+        let mut romaji = "honn".to_string();
+        flush_trailing_n(&mut romaji);
+        assert_eq!(to_hiragana(&romaji), "ほん");
+    }
+
+    #[test]
+    fn apostrophe_separates_n() {
+        assert_eq!(to_hiragana("shinyou"), "しにょう");
+        assert_eq!(to_hiragana("shin'you"), "しんよう");
+        assert_eq!(to_hiragana("kannji"), "かんじ");
+    }
+
+    #[test]
+    fn small_kana_forms() {
+        assert_eq!(to_hiragana("matte"), "まって");
+        assert_eq!(to_hiragana("xtu"), "っ");
+        assert_eq!(to_hiragana("ltu"), "っ");
+        assert_eq!(to_hiragana("kixya"), "きゃ");
+        assert_eq!(to_hiragana("fala"), "ふぁぁ");
+        assert_eq!(to_hiragana("uxi"), "うぃ");
+    }
+
+    #[test]
+    fn overrides_take_precedence() {
+        let overrides: HashMap<String, String> =
+            [("la", "ら"), ("wi", "ゐ")].into_iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        assert_eq!(to_hiragana_with_overrides("lamen", &overrides), "らめ");
+        assert_eq!(to_hiragana_with_overrides("wiski", &overrides), "ゐsき");
+    }
+
+    #[test]
+    fn katakana_shifts_hiragana_only() {
+        assert_eq!(hiragana_to_katakana("すし!"), "スシ!");
+        assert_eq!(hiragana_to_katakana("ぁゔゖ"), "ァヴヶ");
+        assert_eq!(to_katakana("kamera"), "カメラ");
+    }
+}
@@ -1,9 +1,15 @@
 use std::collections::{HashMap, HashSet};
 use std::time::{Duration, Instant};
 
-use crate::config::{AppConfig, FadeOutCurve, KeyTransitionMode, ShortcutDef};
+use regex::Regex;
+use serde::Serialize;
+
+use crate::config::{
+    AppConfig, FadeOutCurve, ImeDisplayMode, KeyTransitionMode, MouseInputMode, ShortcutDef,
+};
 use crate::event::*;
 use crate::key::KeyCode;
+use crate::romaji;
 
 /// アプリケーション全体の表示状態
 pub struct DisplayState {
@@ -21,29 +27,62 @@ pub struct DisplayState {
     next_id: u64,
     /// 単一セルモードでのDown/Up対応付け
     active_presses: HashMap<PressKey, PressTarget>,
+    /// `batch_process_events` の処理中は `active_presses` の掃除を末尾の1回にまとめる
+    batching: bool,
+    prune_pending: bool,
     /// IME変換中文字列がアクティブか
     ime_composing: bool,
     /// OSのIME APIから取得したネイティブ変換中表示か
     ime_native_composing: bool,
     /// IME ON/OFF のフォールバック状態
     ime_fallback_enabled: bool,
+    /// OS の IME が全角カタカナ入力モードか（`ImeDisplayMode::Auto` で参照）
+    ime_katakana_mode: bool,
+    /// 直近に受け取った IME 変換候補の一覧と選択中の位置
+    ime_candidates: Vec<String>,
+    ime_candidate_selected: usize,
     /// IMEフォールバック用のローマ字バッファ
     ime_fallback_romaji: String,
+    /// 組み込み表より優先するローマ字綴り（キーは小文字化済み）
+    romaji_overrides: HashMap<String, String>,
+    /// `privacy.clipboard_redact_patterns` のコンパイル結果（初回使用時に作成、設定変更で破棄）
+    clipboard_redactors: Option<Vec<Regex>>,
+    /// 直前に表示したクリップボード内容と時刻（デバウンス用）
+    last_clipboard: Option<(ClipboardContent, Instant)>,
+    /// アプリ別キーフィルタ用のフォアグラウンドプロセス名（フレームごとに更新）
+    foreground_app: Option<String>,
+    /// 直前のマウスイベント（フックと Raw Input 併用時の重複除去用）
+    last_mouse: Option<(MouseButton, MouseAction, MouseSource, Instant)>,
+    /// 修飾キーがすべて離された時刻（ModifierState のカウントダウン開始点）
+    modifier_release_at: Option<Instant>,
+    /// マウスドラッグ判定
+    drag_tracker: DragTracker,
+    /// マクロ記録中なら記録開始時刻と、開始からの経過時間付きのキーイベント
+    macro_recording: Option<(Instant, Vec<(Duration, KeyEvent)>)>,
 }
 
+/// 修飾キーを離してから ModifierState を消すまでの時間
+const MODIFIER_STATE_LINGER: Duration = Duration::from_millis(500);
+
+/// フックと Raw Input で同じマウス入力が届いたとみなす時間幅
+const MOUSE_DEDUP_WINDOW: Duration = Duration::from_millis(30);
+
 /// 表示アイテム（OSD上の1つの表示要素）
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct DisplayItem {
     pub id: u64,
     pub kind: DisplayItemKind,
+    #[serde(skip)]
     pub created_at: Instant,
     /// 0.0（透明）〜 1.0（不透明）
     pub opacity: f32,
     /// フェーズ
     pub phase: DisplayPhase,
+    /// 固定表示（期限切れにならず、`max_items` にも数えない）
+    pub pinned: bool,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub enum DisplayItemKind {
     /// 通常キー入力
     KeyStroke {
@@ -63,6 +102,8 @@ pub enum DisplayItemKind {
     },
     /// IME変換中テキスト
     ImeComposition { text: String },
+    /// IME変換候補の一覧（`selected` は `entries` 内の選択中の位置）
+    ImeCandidateList { entries: Vec<String>, selected: usize },
     /// クリップボード内容
     ClipboardPreview { text: String },
     /// Lock状態変更通知
@@ -71,10 +112,48 @@ pub enum DisplayItemKind {
         num: bool,
         scroll: bool,
     },
+    /// ゲームパッドのボタン入力
+    GamepadInput { label: String },
+    /// 押下中の修飾キー（常に最下段に固定表示）
+    ModifierState {
+        ctrl: bool,
+        shift: bool,
+        alt: bool,
+        win: bool,
+    },
+}
+
+impl DisplayItemKind {
+    /// `behavior.pinned_item_kinds` で指定できる種別名
+    pub const KIND_NAMES: &'static [&'static str] = &[
+        "KeyStroke",
+        "KeyStrokeGroup",
+        "Shortcut",
+        "ImeComposition",
+        "ImeCandidateList",
+        "ClipboardPreview",
+        "LockIndicator",
+        "GamepadInput",
+        "ModifierState",
+    ];
+
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            DisplayItemKind::KeyStroke { .. } => "KeyStroke",
+            DisplayItemKind::KeyStrokeGroup { .. } => "KeyStrokeGroup",
+            DisplayItemKind::Shortcut { .. } => "Shortcut",
+            DisplayItemKind::ImeComposition { .. } => "ImeComposition",
+            DisplayItemKind::ImeCandidateList { .. } => "ImeCandidateList",
+            DisplayItemKind::ClipboardPreview { .. } => "ClipboardPreview",
+            DisplayItemKind::LockIndicator { .. } => "LockIndicator",
+            DisplayItemKind::GamepadInput { .. } => "GamepadInput",
+            DisplayItemKind::ModifierState { .. } => "ModifierState",
+        }
+    }
 }
 
 /// グループ内の個別キーストローク
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct KeyStrokeEntry {
     pub label: String,
     pub modifiers: Modifiers,
@@ -82,7 +161,7 @@ pub struct KeyStrokeEntry {
     pub repeat_count: u32,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub enum DisplayPhase {
     /// 表示中（フルopacity）
     Active,
@@ -92,6 +171,60 @@ pub enum DisplayPhase {
     Expired,
 }
 
+/// 他スレッドへ渡せる表示状態の複製（IPC の `status` 応答用）
+#[derive(Debug, Clone, Serialize)]
+pub struct DisplayStateSnapshot {
+    /// 表示中のアイテム（フェードアウト中を含む）
+    pub items: Vec<DisplayItem>,
+    pub ime_composing: bool,
+    /// 変換中の文字列（表示中の `ImeComposition` アイテムの内容）
+    pub ime_text: Option<String>,
+    pub stats: DisplayStateStats,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DisplayStateStats {
+    /// 起動してから作ったアイテムの数
+    pub items_created: u64,
+    pub pinned_items: usize,
+    pub preview_active: bool,
+    pub recording_macro: bool,
+}
+
+/// ボタン押下位置を覚えておき、離したときの移動量からドラッグを判定する
+#[derive(Default)]
+struct DragTracker {
+    start: Option<(MouseButton, (i32, i32))>,
+}
+
+impl DragTracker {
+    fn press(&mut self, button: MouseButton, position: (i32, i32)) {
+        self.start = Some((button, position));
+    }
+
+    /// 押下と同じボタンが `min_pixels` 以上動いて離されたら方向矢印を返す
+    fn release(&mut self, button: MouseButton, position: (i32, i32), min_pixels: f32) -> Option<&'static str> {
+        let (start_button, start) = self.start.take()?;
+        if start_button != button {
+            return None;
+        }
+        let dx = (position.0 - start.0) as f32;
+        let dy = (position.1 - start.1) as f32;
+        if dx.hypot(dy) < min_pixels {
+            return None;
+        }
+        Some(drag_arrow(dx, dy))
+    }
+}
+
+/// 移動量から8方向の矢印を選ぶ（画面座標は y が下向き）
+fn drag_arrow(dx: f32, dy: f32) -> &'static str {
+    const ARROWS: [&str; 8] = ["→", "↗", "↑", "↖", "←", "↙", "↓", "↘"];
+    let angle = (-dy).atan2(dx).to_degrees();
+    let sector = ((angle / 45.0).round() as i32).rem_euclid(8);
+    ARROWS[sector as usize]
+}
+
 /// 連打検出
 struct RepeatTracker {
     last_key: Option<KeyCode>,
@@ -149,10 +282,12 @@ impl RepeatTracker {
         }
     }
 
-    fn track(&mut self, key: KeyCode, modifiers: Modifiers, now: Instant) -> u32 {
+    /// `override_ms` があれば共通の `timeout` の代わりにその時間で連打を判定する
+    fn track(&mut self, key: KeyCode, modifiers: Modifiers, now: Instant, override_ms: Option<u64>) -> u32 {
+        let timeout = override_ms.map_or(self.timeout, Duration::from_millis);
         if Some(key) == self.last_key
             && modifiers == self.last_modifiers
-            && now.duration_since(self.last_time) < self.timeout
+            && now.duration_since(self.last_time) < timeout
         {
             self.count += 1;
         } else {
@@ -176,10 +311,23 @@ impl DisplayState {
             config: config.clone(),
             next_id: 0,
             active_presses: HashMap::new(),
+            batching: false,
+            prune_pending: false,
             ime_composing: false,
             ime_native_composing: false,
+            ime_katakana_mode: false,
+            ime_candidates: Vec::new(),
+            ime_candidate_selected: 0,
             ime_fallback_enabled: false,
             ime_fallback_romaji: String::new(),
+            romaji_overrides: normalize_romaji_overrides(&config.behavior.romaji_overrides),
+            clipboard_redactors: None,
+            last_clipboard: None,
+            foreground_app: None,
+            last_mouse: None,
+            modifier_release_at: None,
+            macro_recording: None,
+            drag_tracker: DragTracker::default(),
         }
     }
 
@@ -190,11 +338,58 @@ impl DisplayState {
             InputEvent::Ime(ie) => self.process_ime_event(ie),
             InputEvent::Clipboard(ce) => self.process_clipboard_event(ce),
             InputEvent::LockState(ls) => self.process_lock_event(ls),
+            InputEvent::Gamepad(ge) => self.process_gamepad_event(ge),
+            InputEvent::ImeCandidates(entries) => self.process_ime_candidates(entries, Instant::now()),
             InputEvent::PreviewMode { .. }
             | InputEvent::PreviewConfig { .. }
             | InputEvent::DpiChanged { .. }
-            | InputEvent::ConfigChanged => {} // main loopで処理
+            | InputEvent::DisplayChanged
+            | InputEvent::ConfigChanged
+            | InputEvent::ToggleOsd
+            | InputEvent::ClearDisplay
+            | InputEvent::MacroRecord { .. }
+            | InputEvent::MacroPlay
+            | InputEvent::Screenshot
+            | InputEvent::StateSnapshot => {} // main loopで処理
+        }
+    }
+
+    /// フレーム内にまとめて取り出したイベントを到着順に処理する
+    ///
+    /// 入力イベントと `ClearDisplay` はその場で反映し、残りの制御イベントは到着順に返す。
+    /// `ConfigChanged` は何件あっても末尾の1件にまとめ、バッチ境界で適用させる。
+    pub fn process_events<I>(&mut self, events: I) -> Vec<InputEvent>
+    where
+        I: IntoIterator<Item = InputEvent>,
+    {
+        let mut deferred = Vec::new();
+        let mut config_changed = false;
+        for event in events {
+            match event {
+                InputEvent::ConfigChanged => config_changed = true,
+                InputEvent::ClearDisplay => self.clear(),
+                event if event.is_control() => deferred.push(event),
+                event => self.process_event(event),
+            }
+        }
+        if config_changed {
+            deferred.push(InputEvent::ConfigChanged);
+        }
+        deferred
+    }
+
+    /// `process_events` と同じ処理を1パスで行い、`active_presses` の掃除を最後の1回にまとめる
+    ///
+    /// 高速タイピングで1フレームに多数のイベントが届いても、イベントごとに
+    /// 表示中アイテムの ID 集合を作り直さずに済む。
+    pub fn batch_process_events(&mut self, events: Vec<InputEvent>) -> Vec<InputEvent> {
+        self.batching = true;
+        let deferred = self.process_events(events);
+        self.batching = false;
+        if std::mem::take(&mut self.prune_pending) {
+            self.prune_active_press_targets();
         }
+        deferred
     }
 
     pub fn preview_active(&self) -> bool {
@@ -221,9 +416,15 @@ impl DisplayState {
     }
 
     fn process_key_event(&mut self, ke: KeyEvent) {
+        // 表示用フィルタより前に記録し、無視キーも含めて再生できるようにする
+        if let Some((started_at, events)) = self.macro_recording.as_mut() {
+            events.push((ke.timestamp.saturating_duration_since(*started_at), ke.clone()));
+        }
+        self.update_modifier_state(&ke);
+
         // Key filter: skip ignored keys (case-insensitive, always use full label)
         let full_label = ke.key.label();
-        if self.config.behavior.ignored_keys.iter().any(|k| k.eq_ignore_ascii_case(full_label)) {
+        if self.is_key_ignored(&ke) {
             return;
         }
 
@@ -244,8 +445,11 @@ impl DisplayState {
 
         let now = ke.timestamp;
 
-        // 表示ラベル: distinguish_numpad に応じてテンキー区別を制御
-        let base_label = if self.config.behavior.distinguish_numpad {
+        // 表示ラベル: distinguish_lr_modifiers / distinguish_numpad に応じて区別を制御
+        // （フィルタや修飾キー判定は上の full_label / VK ベースのまま）
+        let base_label = if self.config.behavior.distinguish_lr_modifiers && ke.key.is_modifier() {
+            ke.key.label_lr()
+        } else if self.config.behavior.distinguish_numpad {
             ke.key.label()
         } else {
             ke.key.label_plain()
@@ -303,8 +507,21 @@ impl DisplayState {
                     return;
                 }
 
+                if self.config.behavior.key_transition_mode == KeyTransitionMode::SimultaneousDisplay {
+                    self.add_simultaneous_press(&ke, display_label, display_modifiers, now);
+                    return;
+                }
+
                 let target = if self.config.behavior.show_repeat_count {
-                    let count = self.repeat_tracker.track(ke.key, ke.modifiers, now);
+                    // ignored_keys と同じく表記の大文字小文字は区別しない
+                    let override_ms = self
+                        .config
+                        .behavior
+                        .per_key_repeat_timeout
+                        .iter()
+                        .find(|(key, _)| key.eq_ignore_ascii_case(full_label))
+                        .map(|(_, &ms)| ms);
+                    let count = self.repeat_tracker.track(ke.key, ke.modifiers, now, override_ms);
                     if count > 1 {
                         let force_down =
                             self.config.behavior.key_transition_mode == KeyTransitionMode::SingleCell;
@@ -346,7 +563,7 @@ impl DisplayState {
                 }
 
                 match self.config.behavior.key_transition_mode {
-                    KeyTransitionMode::SingleCell => {
+                    KeyTransitionMode::SingleCell | KeyTransitionMode::SimultaneousDisplay => {
                         self.apply_key_up_to_existing(&ke, now);
                     }
                     KeyTransitionMode::SplitCells => {
@@ -364,6 +581,45 @@ impl DisplayState {
     }
 
     fn process_mouse_event(&mut self, me: MouseEvent) {
+        // フックと Raw Input の併用時は同じ入力が2経路から届くため、もう一方の経路から
+        // 直後に届いた同一イベントを捨てる（同じ経路の素早い連続入力は残す）
+        if self.config.behavior.mouse_input_mode == MouseInputMode::Both {
+            let duplicate = self.last_mouse.is_some_and(|(button, action, source, at)| {
+                button == me.button
+                    && action == me.action
+                    && source != me.source
+                    && me.timestamp.saturating_duration_since(at) < MOUSE_DEDUP_WINDOW
+            });
+            if duplicate {
+                return;
+            }
+            self.last_mouse = Some((me.button, me.action, me.source, me.timestamp));
+        }
+
+        match me.action {
+            MouseAction::Down => self.drag_tracker.press(me.button, me.position),
+            MouseAction::Up => {
+                let arrow = self.drag_tracker.release(
+                    me.button,
+                    me.position,
+                    self.config.behavior.drag_min_pixels,
+                );
+                if let Some(arrow) = arrow.filter(|_| self.config.behavior.show_mouse_drag) {
+                    let _ = self.add_item(
+                        DisplayItemKind::KeyStroke {
+                            label: format!("Drag{arrow}"),
+                            modifiers: Modifiers::default(),
+                            action: KeyAction::Down,
+                            repeat_count: 1,
+                        },
+                        me.timestamp,
+                    );
+                }
+                return;
+            }
+            MouseAction::Wheel(_) => {}
+        }
+
         let label = match me.button {
             MouseButton::Left => "LClick",
             MouseButton::Right => "RClick",
@@ -408,8 +664,12 @@ impl DisplayState {
                     self.ime_composing = false;
                     self.ime_native_composing = false;
                     self.ime_fallback_romaji.clear();
+                    self.ime_candidates.clear();
                     self.items.retain(|item| {
-                        !matches!(item.kind, DisplayItemKind::ImeComposition { .. })
+                        !matches!(
+                            item.kind,
+                            DisplayItemKind::ImeComposition { .. } | DisplayItemKind::ImeCandidateList { .. }
+                        )
                     });
                     self.prune_active_press_targets();
                 }
@@ -433,15 +693,20 @@ impl DisplayState {
                     let _ = self.add_item(DisplayItemKind::ImeComposition { text }, ie.timestamp);
                 }
             }
-            ImeEventKind::CompositionEnd { .. } => {
+            ImeEventKind::CompositionEnd { result } => {
                 // ネイティブIME由来の変換終了のみ確定的に終了扱いにする。
                 // フォールバック入力中に空文字イベントが届いてもバッファを壊さない。
                 if self.ime_native_composing {
                     self.ime_composing = false;
                     self.ime_native_composing = false;
                     self.ime_fallback_romaji.clear();
-                    self.items
-                        .retain(|item| !matches!(item.kind, DisplayItemKind::ImeComposition { .. }));
+                    self.ime_candidates.clear();
+                    self.items.retain(|item| {
+                        !matches!(
+                            item.kind,
+                            DisplayItemKind::ImeComposition { .. } | DisplayItemKind::ImeCandidateList { .. }
+                        )
+                    });
                     self.prune_active_press_targets();
                 } else if !self.ime_fallback_enabled {
                     self.ime_composing = false;
@@ -449,26 +714,121 @@ impl DisplayState {
                         .retain(|item| !matches!(item.kind, DisplayItemKind::ImeComposition { .. }));
                     self.prune_active_press_targets();
                 }
+                if self.config.behavior.show_ime_commit && !result.is_empty() {
+                    let _ = self.add_item(
+                        DisplayItemKind::Shortcut {
+                            keys_label: "確定".to_string(),
+                            action_label: result,
+                        },
+                        ie.timestamp,
+                    );
+                }
+            }
+            ImeEventKind::ConversionModeChanged { katakana } => {
+                self.ime_katakana_mode = katakana;
+            }
+            ImeEventKind::CandidateUpdate { text } => {
+                if !self.config.behavior.show_ime_candidates {
+                    return;
+                }
+                // 一覧が取れていない IME では選択中の候補だけの一覧として扱う
+                match self.ime_candidates.iter().position(|c| *c == text) {
+                    Some(index) => self.ime_candidate_selected = index,
+                    None => {
+                        self.ime_candidates = vec![text];
+                        self.ime_candidate_selected = 0;
+                    }
+                }
+                self.refresh_ime_candidate_item(ie.timestamp);
             }
         }
     }
 
+    fn process_ime_candidates(&mut self, entries: Vec<String>, now: Instant) {
+        if !self.config.behavior.show_ime_composition || !self.config.behavior.show_ime_candidates {
+            return;
+        }
+        // 一覧が差し替わっても同じ候補が選ばれていれば位置を引き継ぐ
+        let selected = self.ime_candidates.get(self.ime_candidate_selected);
+        self.ime_candidate_selected = selected
+            .and_then(|text| entries.iter().position(|c| c == text))
+            .unwrap_or(0);
+        self.ime_candidates = entries;
+        self.refresh_ime_candidate_item(now);
+    }
+
+    /// 選択中の候補を含むページを切り出して候補一覧アイテムを更新（一覧が空なら消す）
+    fn refresh_ime_candidate_item(&mut self, now: Instant) {
+        if self.ime_candidates.is_empty() {
+            self.items
+                .retain(|item| !matches!(item.kind, DisplayItemKind::ImeCandidateList { .. }));
+            self.prune_active_press_targets();
+            return;
+        }
+        let page = self.config.behavior.ime_candidate_max_items.max(1);
+        let selected = self.ime_candidate_selected.min(self.ime_candidates.len() - 1);
+        let start = selected / page * page;
+        let end = (start + page).min(self.ime_candidates.len());
+        let entries = self.ime_candidates[start..end].to_vec();
+        let selected = selected - start;
+
+        let updated = self.items.iter_mut().any(|item| {
+            if let DisplayItemKind::ImeCandidateList {
+                entries: ref mut e,
+                selected: ref mut s,
+            } = item.kind
+            {
+                *e = entries.clone();
+                *s = selected;
+                item.phase = DisplayPhase::Active;
+                item.opacity = 1.0;
+                true
+            } else {
+                false
+            }
+        });
+        if !updated {
+            let _ = self.add_item(DisplayItemKind::ImeCandidateList { entries, selected }, now);
+        }
+    }
+
     fn process_clipboard_event(&mut self, ce: ClipboardEvent) {
         if !self.config.behavior.show_clipboard {
             return;
         }
 
+        let debounce = Duration::from_millis(self.config.behavior.clipboard_debounce_ms);
+        if let Some((prev, at)) = &self.last_clipboard {
+            if *prev == ce.content && ce.timestamp.saturating_duration_since(*at) < debounce {
+                return;
+            }
+        }
+        self.last_clipboard = Some((ce.content.clone(), ce.timestamp));
+
         let text = match ce.content {
-            ClipboardContent::Text(ref s) => {
-                let max = self.config.behavior.clipboard_max_chars;
-                let char_count = s.chars().count();
-                if char_count > max {
-                    let truncated: String = s.chars().take(max).collect();
-                    format!("{}...", truncated)
-                } else {
-                    s.clone()
+            ClipboardContent::Text {
+                text: ref s,
+                ref source_app,
+                ..
+            } => {
+                let preview = self.clipboard_preview_text(s);
+                match source_app {
+                    Some(app) if self.config.behavior.show_clipboard_source => {
+                        format!("{} (from {})", preview, app)
+                    }
+                    _ => preview,
                 }
             }
+            ClipboardContent::Files(ref files) => match files.as_slice() {
+                [single] => {
+                    let name = std::path::Path::new(single)
+                        .file_name()
+                        .map(|n| n.to_string_lossy().into_owned())
+                        .unwrap_or_else(|| single.clone());
+                    format!("[File] {}", name)
+                }
+                _ => format!("[{} files]", files.len()),
+            },
             ClipboardContent::Image { width, height } => {
                 format!("[Image {}x{}]", width, height)
             }
@@ -483,14 +843,102 @@ impl DisplayState {
             return;
         }
 
-        let _ = self.add_item(
-            DisplayItemKind::LockIndicator {
-                caps: ls.caps_lock,
-                num: ls.num_lock,
-                scroll: ls.scroll_lock,
-            },
-            ls.timestamp,
-        );
+        let kind = DisplayItemKind::LockIndicator {
+            caps: ls.caps_lock,
+            num: ls.num_lock,
+            scroll: ls.scroll_lock,
+        };
+        // persist_lock_indicator では CapsLock / NumLock が有効な間だけ固定表示にする
+        let persist = self.config.behavior.persist_lock_indicator && (ls.caps_lock || ls.num_lock);
+        let keep_pinned = persist || self.is_auto_pinned(&kind);
+        // 固定表示中のロック表示は増やさずに状態だけ更新（解除されたら通常どおりフェードさせる）
+        if let Some(item) = self.items.iter_mut().find(|item| {
+            item.pinned && matches!(item.kind, DisplayItemKind::LockIndicator { .. })
+        }) {
+            item.kind = kind;
+            item.pinned = keep_pinned;
+            Self::refresh_item(item, ls.timestamp);
+            return;
+        }
+        let item_id = self.add_item(kind, ls.timestamp);
+        if persist {
+            if let Some(item) = self.items.iter_mut().find(|item| item.id == item_id) {
+                item.pinned = true;
+            }
+        }
+    }
+
+    /// クリップボードの文字列を表示用に整える（マスク・伏せ字・`clipboard_max_chars` での切り詰め）
+    fn clipboard_preview_text(&mut self, text: &str) -> String {
+        let max = self.config.behavior.clipboard_max_chars;
+        let normalized = normalize_clipboard_text(text);
+        if should_mask_clipboard(&normalized, &self.config.privacy.mask_clipboard_patterns) {
+            return "[hidden]".to_string();
+        }
+        let normalized = self.redact_clipboard_text(normalized);
+        if normalized.chars().count() > max {
+            let truncated: String = normalized.chars().take(max).collect();
+            format!("{}...", truncated)
+        } else {
+            normalized
+        }
+    }
+
+    /// 外部（WebSocket / SSE）へ流すイベントを返す
+    ///
+    /// OSD で非表示になるもの（`ignored_keys` / `app_key_filters` で除外したキー、
+    /// 表示設定が無効な IME・ロック・ゲームパッド・クリップボード）は流さない。
+    /// クリップボードの文字列は OSD と同じくマスク・伏せ字・切り詰めを済ませたものに差し替える。
+    pub fn export_event(&mut self, event: &InputEvent) -> Option<InputEvent> {
+        let behavior = &self.config.behavior;
+        let ce = match event {
+            InputEvent::Key(ke) if self.is_key_ignored(ke) => return None,
+            InputEvent::Ime(_) if !behavior.show_ime_composition => return None,
+            InputEvent::ImeCandidates(_)
+                if !behavior.show_ime_composition || !behavior.show_ime_candidates =>
+            {
+                return None
+            }
+            InputEvent::LockState(_) if !behavior.show_lock_indicators => return None,
+            InputEvent::Gamepad(_) if !behavior.show_gamepad => return None,
+            InputEvent::Clipboard(_) if !behavior.show_clipboard => return None,
+            InputEvent::Clipboard(ce) => ce,
+            _ => return Some(event.clone()),
+        };
+        let mut ce = ce.clone();
+        if let ClipboardContent::Text { text, .. } = &mut ce.content {
+            *text = self.clipboard_preview_text(text);
+        }
+        Some(InputEvent::Clipboard(ce))
+    }
+
+    /// `privacy.clipboard_redact_patterns` に一致した部分を置換文字列に差し替える
+    fn redact_clipboard_text(&mut self, text: String) -> String {
+        let privacy = &self.config.privacy;
+        if privacy.clipboard_redact_patterns.is_empty() {
+            return text;
+        }
+        let redactors = self
+            .clipboard_redactors
+            .get_or_insert_with(|| compile_redact_patterns(&privacy.clipboard_redact_patterns));
+        redactors.iter().fold(text, |text, re| {
+            re.replace_all(&text, regex::NoExpand(&privacy.redact_replacement))
+                .into_owned()
+        })
+    }
+
+    fn process_gamepad_event(&mut self, ge: GamepadEvent) {
+        if !self.config.behavior.show_gamepad || ge.action != KeyAction::Down {
+            return;
+        }
+
+        // 2台目以降はコントローラー番号を付けて区別
+        let label = if ge.controller_id == 0 {
+            format!("Pad {}", ge.button.label())
+        } else {
+            format!("Pad{} {}", ge.controller_id + 1, ge.button.label())
+        };
+        let _ = self.add_item(DisplayItemKind::GamepadInput { label }, ge.timestamp);
     }
 
     /// 時間経過処理（毎フレーム呼び出し）
@@ -499,7 +947,32 @@ impl DisplayState {
             Duration::from_millis(self.config.display.display_duration_ms);
         let fade_dur = Duration::from_millis(self.config.display.fade_duration_ms);
 
+        let modifier_state_expired = self
+            .modifier_release_at
+            .is_some_and(|t| now.duration_since(t) >= MODIFIER_STATE_LINGER);
+        // 同時押し表示では押下中のキー（active_presses の参照先）を期限なしで表示し続ける
+        let held_ids: HashSet<u64> =
+            if self.config.behavior.key_transition_mode == KeyTransitionMode::SimultaneousDisplay {
+                self.active_presses.values().map(|target| target.item_id).collect()
+            } else {
+                HashSet::new()
+            };
+
         for item in &mut self.items {
+            if item.pinned {
+                continue;
+            }
+            if held_ids.contains(&item.id) {
+                Self::refresh_item(item, now);
+                continue;
+            }
+            // 修飾キー表示は押下中は期限なし、全解放後のカウントダウンでのみ消える
+            if matches!(item.kind, DisplayItemKind::ModifierState { .. }) {
+                if modifier_state_expired {
+                    item.phase = DisplayPhase::Expired;
+                }
+                continue;
+            }
             match item.phase {
                 DisplayPhase::Active => {
                     if now.duration_since(item.created_at) >= display_dur {
@@ -526,6 +999,9 @@ impl DisplayState {
             }
         }
 
+        if modifier_state_expired {
+            self.modifier_release_at = None;
+        }
         self.items.retain(|item| item.phase != DisplayPhase::Expired);
         self.prune_active_press_targets();
     }
@@ -534,10 +1010,47 @@ impl DisplayState {
         &self.items
     }
 
+    /// 現在の表示状態を所有権付きで複製する（`active_items` と違いスレッドをまたいで渡せる）
+    pub fn snapshot(&self) -> DisplayStateSnapshot {
+        let ime_text = self.items.iter().find_map(|item| match &item.kind {
+            DisplayItemKind::ImeComposition { text } => Some(text.clone()),
+            _ => None,
+        });
+        DisplayStateSnapshot {
+            items: self.items.clone(),
+            ime_composing: self.ime_composing,
+            ime_text,
+            stats: DisplayStateStats {
+                items_created: self.next_id,
+                pinned_items: self.items.iter().filter(|item| item.pinned).count(),
+                preview_active: self.preview_active,
+                recording_macro: self.is_recording_macro(),
+            },
+        }
+    }
+
+    /// マクロ記録を開始（記録中なら破棄してやり直す）
+    pub fn start_macro_recording(&mut self, now: Instant) {
+        self.macro_recording = Some((now, Vec::new()));
+    }
+
+    /// マクロ記録を停止し、記録したキーイベントを返す（未記録なら空）
+    pub fn stop_macro_recording(&mut self) -> Vec<(Duration, KeyEvent)> {
+        self.macro_recording
+            .take()
+            .map(|(_, events)| events)
+            .unwrap_or_default()
+    }
+
+    pub fn is_recording_macro(&self) -> bool {
+        self.macro_recording.is_some()
+    }
+
     /// 全アイテムをクリア（privacy遷移時等）
     pub fn clear(&mut self) {
         self.items.clear();
         self.active_presses.clear();
+        self.modifier_release_at = None;
         self.ime_composing = false;
         self.ime_native_composing = false;
         self.ime_fallback_romaji.clear();
@@ -549,13 +1062,83 @@ impl DisplayState {
             .any(|i| i.phase == DisplayPhase::FadingOut)
     }
 
+    /// フェードアウト中のアイテムだけが残っているか（固定表示とプレビューは除く）
+    ///
+    /// 入力が途切れて消えていくだけの状態なので、描画間隔を粗くしてよい。
+    pub fn is_only_fading(&self) -> bool {
+        self.has_animations()
+            && !self.preview_active
+            && self
+                .items
+                .iter()
+                .all(|item| item.pinned || item.phase != DisplayPhase::Active)
+    }
+
+    /// フォアグラウンドのプロセス名を設定（`app_key_filters` の判定に使う）
+    pub fn set_foreground_app(&mut self, name: Option<String>) {
+        self.foreground_app = name;
+    }
+
+    /// `ignored_keys` か `app_key_filters` で表示から除外されるキーか
+    fn is_key_ignored(&self, ke: &KeyEvent) -> bool {
+        let full_label = ke.key.label();
+        self.config.behavior.ignored_keys.iter().any(|k| k.eq_ignore_ascii_case(full_label))
+            || self.is_filtered_by_app(full_label, ke.key.is_modifier())
+    }
+
+    /// フォアグラウンドアプリに一致する最初の `AppKeyFilter` でキーを除外するか
+    fn is_filtered_by_app(&self, label: &str, is_modifier: bool) -> bool {
+        let Some(app) = self.foreground_app.as_deref() else {
+            return false;
+        };
+        let Some(filter) = self
+            .config
+            .behavior
+            .app_key_filters
+            .iter()
+            .find(|f| f.app_name.eq_ignore_ascii_case(app))
+        else {
+            return false;
+        };
+
+        let matches = |keys: &[String]| keys.iter().any(|k| k.eq_ignore_ascii_case(label));
+        if matches(&filter.ignored_keys) {
+            return true;
+        }
+        match &filter.show_only_keys {
+            Some(keys) => !is_modifier && !matches(keys),
+            None => false,
+        }
+    }
+
     /// 設定を更新（ホットリロード用）
     pub fn update_config(&mut self, config: &AppConfig) {
         let now = Instant::now();
         if self.config.behavior.key_transition_mode != config.behavior.key_transition_mode {
             self.active_presses.clear();
         }
+        if !config.behavior.show_modifier_state {
+            self.items
+                .retain(|item| !matches!(item.kind, DisplayItemKind::ModifierState { .. }));
+            self.modifier_release_at = None;
+        }
         self.config = config.clone();
+        if !config.behavior.persist_lock_indicator {
+            // 常時表示をやめたロック表示は、自動固定の対象でなければ通常の寿命に戻す
+            let lock_auto_pinned = self.is_auto_pinned(&DisplayItemKind::LockIndicator {
+                caps: false,
+                num: false,
+                scroll: false,
+            });
+            for item in &mut self.items {
+                if item.pinned && !lock_auto_pinned && matches!(item.kind, DisplayItemKind::LockIndicator { .. }) {
+                    item.pinned = false;
+                    Self::refresh_item(item, now);
+                }
+            }
+        }
+        self.romaji_overrides = normalize_romaji_overrides(&config.behavior.romaji_overrides);
+        self.clipboard_redactors = None;
         self.repeat_tracker.timeout = Duration::from_millis(config.behavior.repeat_timeout_ms);
         self.prune_active_press_targets();
         self.rebuild_preview_items(now);
@@ -565,19 +1148,30 @@ impl DisplayState {
 
     fn add_item(&mut self, kind: DisplayItemKind, now: Instant) -> u64 {
         let item_id = self.next_id;
+        let pinned = self.is_auto_pinned(&kind);
         let item = DisplayItem {
             id: item_id,
             kind,
             created_at: now,
             opacity: 1.0,
             phase: DisplayPhase::Active,
+            pinned,
         };
 
         self.next_id += 1;
         self.items.push(item);
 
-        while self.items.len() > self.config.display.max_items {
-            self.items.remove(0);
+        // 固定表示（ModifierState・pinned）は max_items に数えず、それ以外の最古から削除
+        let is_fixed = |item: &DisplayItem| {
+            item.pinned || matches!(item.kind, DisplayItemKind::ModifierState { .. })
+        };
+        let mut unpinned = self.items.iter().filter(|item| !is_fixed(item)).count();
+        while unpinned > self.config.display.max_items {
+            let Some(oldest) = self.items.iter().position(|item| !is_fixed(item)) else {
+                break;
+            };
+            self.items.remove(oldest);
+            unpinned -= 1;
         }
 
         self.prune_active_press_targets();
@@ -765,11 +1359,48 @@ impl DisplayState {
             _ => false,
         };
 
-        if updated {
+        if !updated {
+            return;
+        }
+        if self.config.behavior.key_transition_mode == KeyTransitionMode::SimultaneousDisplay {
+            // 離したキーはその場でフェードを開始する
+            let display_dur = Duration::from_millis(self.config.display.display_duration_ms);
+            item.created_at = now.checked_sub(display_dur).unwrap_or(now);
+            item.phase = DisplayPhase::FadingOut;
+        } else {
             Self::refresh_item(item, now);
         }
     }
 
+    /// 同時押し表示: 押下中のキーごとに1アイテムを割り当てる（キーリピートは既存を維持）
+    fn add_simultaneous_press(
+        &mut self,
+        ke: &KeyEvent,
+        label: &str,
+        modifiers: Modifiers,
+        now: Instant,
+    ) {
+        let press_key = PressKey::from_key_event(ke);
+        // バッチ処理中は消えたアイテムを指す対応付けが残っているので、参照先の有無で判定する
+        if self
+            .active_presses
+            .get(&press_key)
+            .is_some_and(|target| self.items.iter().any(|item| item.id == target.item_id))
+        {
+            return;
+        }
+        let item_id = self.add_item(
+            DisplayItemKind::KeyStroke {
+                label: label.to_string(),
+                modifiers,
+                action: KeyAction::Down,
+                repeat_count: 1,
+            },
+            now,
+        );
+        self.active_presses.insert(press_key, PressTarget::item(item_id));
+    }
+
     fn remap_item_target_to_group_first(&mut self, item_id: u64) {
         for target in self.active_presses.values_mut() {
             if target.item_id == item_id && target.group_index.is_none() {
@@ -779,11 +1410,74 @@ impl DisplayState {
     }
 
     fn prune_active_press_targets(&mut self) {
+        if self.batching {
+            self.prune_pending = true;
+            return;
+        }
         let live_ids: HashSet<u64> = self.items.iter().map(|item| item.id).collect();
         self.active_presses
             .retain(|_, target| live_ids.contains(&target.item_id));
     }
 
+    /// ModifierState アイテムの位置（存在すれば常に先頭 = 最下段）
+    fn modifier_state_index(&self) -> Option<usize> {
+        self.items
+            .first()
+            .filter(|item| matches!(item.kind, DisplayItemKind::ModifierState { .. }))
+            .map(|_| 0)
+    }
+
+    /// キーイベントごとに ModifierState を押下中の修飾キーへ更新
+    fn update_modifier_state(&mut self, ke: &KeyEvent) {
+        if !self.config.behavior.show_modifier_state {
+            return;
+        }
+
+        let held = held_modifiers(ke);
+        let now = ke.timestamp;
+        if !(held.ctrl || held.shift || held.alt || held.win) {
+            if self.modifier_state_index().is_some() && self.modifier_release_at.is_none() {
+                self.modifier_release_at = Some(now);
+            }
+            return;
+        }
+
+        self.modifier_release_at = None;
+        let kind = DisplayItemKind::ModifierState {
+            ctrl: held.ctrl,
+            shift: held.shift,
+            alt: held.alt,
+            win: held.win,
+        };
+        if let Some(index) = self.modifier_state_index() {
+            let item = &mut self.items[index];
+            item.kind = kind;
+            Self::refresh_item(item, now);
+            return;
+        }
+
+        let item = DisplayItem {
+            id: self.next_id,
+            pinned: self.is_auto_pinned(&kind),
+            kind,
+            created_at: now,
+            opacity: 1.0,
+            phase: DisplayPhase::Active,
+        };
+        self.next_id += 1;
+        self.items.insert(0, item);
+    }
+
+    /// `behavior.pinned_item_kinds` に含まれる種別なら作成時に固定する
+    fn is_auto_pinned(&self, kind: &DisplayItemKind) -> bool {
+        let name = kind.kind_name();
+        self.config
+            .behavior
+            .pinned_item_kinds
+            .iter()
+            .any(|k| k == name)
+    }
+
     fn refresh_item(item: &mut DisplayItem, now: Instant) {
         item.created_at = now;
         item.opacity = 1.0;
@@ -847,13 +1541,19 @@ impl DisplayState {
         let vk = ke.key.0 & 0xFF;
         let is_letter = (0x41..=0x5A).contains(&vk);
         let is_control_key = matches!(vk, 0x08 | 0x0D | 0x1B | 0x09 | 0x20); // BS/Enter/Esc/Tab/Space
+        // "shin'you" の区切り。配列によってキーが違うので実文字で判定する
+        let is_apostrophe = !self.ime_fallback_romaji.is_empty() && ke.text.as_deref() == Some("'");
 
         if ke.action == KeyAction::Up {
-            return is_letter || is_control_key;
+            return is_letter || is_control_key || is_apostrophe;
         }
 
-        if is_letter {
-            let c = (vk as u8 as char).to_ascii_lowercase();
+        if is_letter || is_apostrophe {
+            let c = if is_letter {
+                (vk as u8 as char).to_ascii_lowercase()
+            } else {
+                '\''
+            };
             self.ime_fallback_romaji.push(c);
             self.apply_ime_fallback_text(ke.timestamp);
             return true;
@@ -865,6 +1565,15 @@ impl DisplayState {
             return true;
         }
 
+        if matches!(vk, 0x0D | 0x20) && !self.ime_fallback_romaji.is_empty() {
+            // 確定: 保留中の末尾 n を「ん」にして、確定後の表示は通常どおりフェードさせる
+            romaji::flush_trailing_n(&mut self.ime_fallback_romaji);
+            self.apply_ime_fallback_text(ke.timestamp);
+            self.ime_fallback_romaji.clear();
+            self.ime_composing = false;
+            return true;
+        }
+
         if matches!(vk, 0x0D | 0x1B | 0x09 | 0x20) {
             self.ime_fallback_romaji.clear();
             self.ime_composing = false;
@@ -877,8 +1586,21 @@ impl DisplayState {
         false
     }
 
+    /// フォールバック表示をカタカナにするか（Auto は IME の入力モードに従う）
+    fn is_katakana_ime_mode(&self) -> bool {
+        match self.config.behavior.ime_display_mode {
+            ImeDisplayMode::Auto => self.ime_katakana_mode,
+            ImeDisplayMode::Hiragana => false,
+            ImeDisplayMode::Katakana => true,
+        }
+    }
+
     fn apply_ime_fallback_text(&mut self, now: Instant) {
-        let text = romaji_to_hiragana(&self.ime_fallback_romaji);
+        let text = if self.is_katakana_ime_mode() {
+            romaji::to_katakana_with_overrides(&self.ime_fallback_romaji, &self.romaji_overrides)
+        } else {
+            romaji::to_hiragana_with_overrides(&self.ime_fallback_romaji, &self.romaji_overrides)
+        };
         if text.is_empty() {
             self.ime_composing = false;
             self.ime_native_composing = false;
@@ -919,6 +1641,7 @@ fn shortcut_matches(keys_str: &str, ke: &KeyEvent) -> bool {
     let mut need_shift = false;
     let mut need_alt = false;
     let mut need_win = false;
+    let mut need_altgr = false;
     let mut key_part = None;
 
     for part in &parts {
@@ -927,6 +1650,7 @@ fn shortcut_matches(keys_str: &str, ke: &KeyEvent) -> bool {
             "Shift" => need_shift = true,
             "Alt" => need_alt = true,
             "Win" => need_win = true,
+            "AltGr" => need_altgr = true,
             other => key_part = Some(other),
         }
     }
@@ -935,6 +1659,7 @@ fn shortcut_matches(keys_str: &str, ke: &KeyEvent) -> bool {
         || ke.modifiers.shift != need_shift
         || ke.modifiers.alt != need_alt
         || ke.modifiers.win != need_win
+        || ke.modifiers.altgr != need_altgr
     {
         return false;
     }
@@ -946,6 +1671,23 @@ fn shortcut_matches(keys_str: &str, ke: &KeyEvent) -> bool {
     ke.key.label() == expected_key
 }
 
+/// キーイベント時点で押下中の修飾キー
+///
+/// フック時点の修飾状態には当該キー自身の押下/解放がまだ反映されていないため補正する。
+fn held_modifiers(ke: &KeyEvent) -> Modifiers {
+    let mut held = ke.modifiers;
+    held.alt |= held.altgr;
+    let pressed = ke.action == KeyAction::Down;
+    match ke.key {
+        KeyCode::L_CTRL | KeyCode::R_CTRL => held.ctrl = pressed,
+        KeyCode::L_SHIFT | KeyCode::R_SHIFT => held.shift = pressed,
+        KeyCode::L_ALT | KeyCode::R_ALT => held.alt = pressed,
+        KeyCode::L_WIN | KeyCode::R_WIN => held.win = pressed,
+        _ => {}
+    }
+    held
+}
+
 fn should_suppress_during_ime_composition(ke: &KeyEvent) -> bool {
     if ke.modifiers.ctrl || ke.modifiers.alt || ke.modifiers.win {
         return false;
@@ -956,6 +1698,100 @@ fn should_suppress_during_ime_composition(ke: &KeyEvent) -> bool {
     (0x30..=0x5A).contains(&vk) || (0xBA..=0xE2).contains(&vk)
 }
 
+/// クリップボード文字列をOSD表示用に1行へ正規化
+///
+/// 先頭/末尾のBOMを除去し、制御文字・空白の連続を半角スペース1つにまとめる。
+fn normalize_clipboard_text(text: &str) -> String {
+    let text = text.trim_start_matches('\u{FEFF}').trim_end_matches('\u{FEFF}');
+    let mut out = String::with_capacity(text.len());
+    let mut pending_space = false;
+    for c in text.chars() {
+        if c.is_whitespace() || c.is_control() {
+            pending_space = true;
+        } else {
+            if pending_space && !out.is_empty() {
+                out.push(' ');
+            }
+            pending_space = false;
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// 置換用の正規表現をコンパイルする（失敗したパターンは警告して読み飛ばす）
+fn compile_redact_patterns(patterns: &[String]) -> Vec<Regex> {
+    patterns
+        .iter()
+        .filter(|pattern| !pattern.is_empty())
+        .filter_map(|pattern| match Regex::new(pattern) {
+            Ok(re) => Some(re),
+            Err(e) => {
+                log::warn!("privacy.clipboard_redact_patterns: skipping \"{pattern}\": {e}");
+                None
+            }
+        })
+        .collect()
+}
+
+/// `privacy.mask_clipboard_patterns` で長いランダム文字列の判定を有効にする予約語
+const RANDOM_TOKEN_PATTERN: &str = "@random-token";
+
+/// クリップボード文字列を隠すべきか判定
+fn should_mask_clipboard(text: &str, patterns: &[String]) -> bool {
+    if patterns.is_empty() || text.is_empty() {
+        return false;
+    }
+    let text_lower: Vec<char> = text.to_lowercase().chars().collect();
+    patterns.iter().any(|pattern| {
+        let pattern = pattern.trim();
+        if pattern.eq_ignore_ascii_case(RANDOM_TOKEN_PATTERN) {
+            looks_like_random_token(text)
+        } else if pattern.is_empty() {
+            false
+        } else {
+            let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+            wildcard_match(&pattern, &text_lower)
+        }
+    })
+}
+
+/// `*`（0文字以上）と `?`（1文字）のみ対応するワイルドカード全体一致
+fn wildcard_match(pattern: &[char], text: &[char]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((sp, st)) = star {
+            p = sp + 1;
+            t = st + 1;
+            star = Some((sp, st + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// 空白を含まない16文字以上で、英大文字・英小文字・数字・記号のうち3種以上を含む文字列
+fn looks_like_random_token(text: &str) -> bool {
+    if text.chars().count() < 16 || text.chars().any(char::is_whitespace) {
+        return false;
+    }
+    let classes = [
+        text.chars().any(|c| c.is_ascii_lowercase()),
+        text.chars().any(|c| c.is_ascii_uppercase()),
+        text.chars().any(|c| c.is_ascii_digit()),
+        text.chars().any(|c| c.is_ascii_punctuation()),
+    ];
+    classes.iter().filter(|&&b| b).count() >= 3
+}
+
 fn build_preview_items(config: &AppConfig, now: Instant) -> Vec<DisplayItem> {
     // Preview items are always "active" and do not fade; they are rendered separately
     // from live OSD items.
@@ -1004,6 +1840,7 @@ fn build_preview_items(config: &AppConfig, now: Instant) -> Vec<DisplayItem> {
         created_at: now,
         opacity,
         phase: DisplayPhase::Active,
+        pinned: false,
     });
     id += 1;
 
@@ -1021,6 +1858,7 @@ fn build_preview_items(config: &AppConfig, now: Instant) -> Vec<DisplayItem> {
         created_at: now,
         opacity,
         phase: DisplayPhase::Active,
+        pinned: false,
     });
     id += 1;
 
@@ -1033,8 +1871,24 @@ fn build_preview_items(config: &AppConfig, now: Instant) -> Vec<DisplayItem> {
             created_at: now,
             opacity,
             phase: DisplayPhase::Active,
+            pinned: false,
         });
         id += 1;
+
+        if config.behavior.show_ime_candidates {
+            items.push(DisplayItem {
+                id,
+                kind: DisplayItemKind::ImeCandidateList {
+                    entries: vec!["仮名漢字".to_string(), "かな漢字".to_string(), "カナ漢字".to_string()],
+                    selected: 1,
+                },
+                created_at: now,
+                opacity,
+                phase: DisplayPhase::Active,
+                pinned: false,
+            });
+            id += 1;
+        }
     }
 
     if config.behavior.show_clipboard {
@@ -1054,232 +1908,827 @@ fn build_preview_items(config: &AppConfig, now: Instant) -> Vec<DisplayItem> {
             created_at: now,
             opacity,
             phase: DisplayPhase::Active,
+            pinned: false,
         });
     }
 
     items
 }
 
-fn romaji_to_hiragana(romaji: &str) -> String {
-    let s: String = romaji
-        .chars()
-        .filter(|c| c.is_ascii_alphabetic())
-        .map(|c| c.to_ascii_lowercase())
-        .collect();
-
-    let bytes = s.as_bytes();
-    let mut out = String::new();
-    let mut i = 0usize;
-
-    while i < bytes.len() {
-        // 促音（小さい「っ」）: 子音重複（nn除く）
-        if i + 1 < bytes.len()
-            && bytes[i] == bytes[i + 1]
-            && is_romaji_consonant(bytes[i] as char)
-            && bytes[i] != b'n'
-        {
-            out.push('っ');
-            i += 1;
-            continue;
-        }
+/// 設定のローマ字上書き表をキー小文字化して取り込む
+fn normalize_romaji_overrides(overrides: &HashMap<String, String>) -> HashMap<String, String> {
+    overrides
+        .iter()
+        .filter(|(key, _)| !key.is_empty())
+        .map(|(key, kana)| (key.to_ascii_lowercase(), kana.clone()))
+        .collect()
+}
 
-        // 「ん」処理
-        if bytes[i] == b'n' {
-            if i + 1 == bytes.len() {
-                break; // 末尾nは確定待ち
-            }
-            let next = bytes[i + 1] as char;
-            if next == 'n' {
-                out.push('ん');
-                i += 1;
-                continue;
-            }
-            if !is_romaji_vowel(next) && next != 'y' {
-                out.push('ん');
-                i += 1;
-                continue;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AppKeyFilter;
+
+    fn clipboard_event(text: &str, timestamp: Instant) -> InputEvent {
+        InputEvent::Clipboard(ClipboardEvent {
+            content: ClipboardContent::Text {
+                text: text.to_string(),
+                source_app: None,
+                format: ClipboardTextFormat::Unicode,
+            },
+            timestamp,
+        })
+    }
+
+    #[test]
+    fn clipboard_debounce_drops_identical_content_within_window() {
+        let mut cfg = AppConfig::default();
+        cfg.behavior.clipboard_debounce_ms = 500;
+        let mut state = DisplayState::new(&cfg);
+        let t0 = Instant::now();
+
+        state.process_event(clipboard_event("hello", t0));
+        state.process_event(clipboard_event("hello", t0 + Duration::from_millis(100)));
+        assert_eq!(state.active_items().len(), 1);
+
+        state.process_event(clipboard_event("world", t0 + Duration::from_millis(200)));
+        assert_eq!(state.active_items().len(), 2);
+
+        state.process_event(clipboard_event("world", t0 + Duration::from_millis(800)));
+        assert_eq!(state.active_items().len(), 3);
+    }
+
+    fn key_down(key: KeyCode, timestamp: Instant) -> InputEvent {
+        InputEvent::Key(KeyEvent {
+            key,
+            action: KeyAction::Down,
+            modifiers: Modifiers::default(),
+            is_numpad: false,
+            scan_code: 0,
+            text: None,
+            timestamp,
+        })
+    }
+
+    /// 修飾なしのキーイベント（同時押しの対応付けで区別できるよう scan_code も VK にする）
+    fn key_event(vk: u32, action: KeyAction, timestamp: Instant) -> InputEvent {
+        InputEvent::Key(KeyEvent {
+            key: KeyCode(vk),
+            action,
+            modifiers: Modifiers::default(),
+            is_numpad: false,
+            scan_code: vk,
+            text: None,
+            timestamp,
+        })
+    }
+
+    #[test]
+    fn per_key_repeat_timeout_overrides_global_timeout() {
+        let mut cfg = AppConfig::default();
+        cfg.behavior.repeat_timeout_ms = 500;
+        cfg.behavior.per_key_repeat_timeout.insert("bs".into(), 1000);
+        cfg.behavior.per_key_repeat_timeout.insert("ENTER".into(), 100);
+        let mut state = DisplayState::new(&cfg);
+        let t0 = Instant::now();
+        let repeat_counts = |state: &DisplayState| -> Vec<u32> {
+            state
+                .active_items()
+                .iter()
+                .filter_map(|item| match &item.kind {
+                    DisplayItemKind::KeyStroke { repeat_count, .. } => Some(*repeat_count),
+                    _ => None,
+                })
+                .collect()
+        };
+
+        state.process_event(key_down(KeyCode::BACKSPACE, t0));
+        state.process_event(key_down(KeyCode::BACKSPACE, t0 + Duration::from_millis(800)));
+        assert_eq!(repeat_counts(&state), vec![2]);
+
+        state.clear();
+        state.process_event(key_down(KeyCode::ENTER, t0));
+        state.process_event(key_down(KeyCode::ENTER, t0 + Duration::from_millis(300)));
+        assert_eq!(repeat_counts(&state), vec![1, 1]);
+    }
+
+    #[test]
+    fn app_key_filters_apply_only_to_matching_foreground_app() {
+        let mut cfg = AppConfig::default();
+        cfg.behavior.app_key_filters = vec![
+            AppKeyFilter {
+                app_name: "Game.exe".into(),
+                ignored_keys: vec!["Space".into()],
+                show_only_keys: None,
+            },
+            AppKeyFilter {
+                app_name: "slides.exe".into(),
+                ignored_keys: Vec::new(),
+                show_only_keys: Some(vec!["A".into()]),
+            },
+        ];
+        let mut state = DisplayState::new(&cfg);
+        let t0 = Instant::now();
+
+        state.set_foreground_app(Some("game.exe".into()));
+        state.process_event(key_down(KeyCode::SPACE, t0));
+        assert!(state.active_items().is_empty());
+
+        state.set_foreground_app(Some("slides.exe".into()));
+        state.process_event(key_down(KeyCode::SPACE, t0));
+        assert!(state.active_items().is_empty());
+        state.process_event(key_down(KeyCode(0x41), t0));
+        assert_eq!(state.active_items().len(), 1);
+
+        state.set_foreground_app(Some("notepad.exe".into()));
+        state.process_event(key_down(KeyCode::SPACE, t0 + Duration::from_secs(1)));
+        assert_eq!(state.active_items().len(), 2);
+    }
+
+    #[test]
+    fn altgr_is_matched_separately_from_ctrl_alt() {
+        let mut ke = match key_down(KeyCode(0x45), Instant::now()) {
+            InputEvent::Key(ke) => ke,
+            _ => unreachable!(),
+        };
+        ke.modifiers.altgr = true;
+        assert!(shortcut_matches("AltGr+E", &ke));
+        assert!(!shortcut_matches("Ctrl+Alt+E", &ke));
+        assert!(!shortcut_matches("Alt+E", &ke));
+    }
+
+    #[test]
+    fn gamepad_events_are_shown_only_when_enabled() {
+        let event = |controller_id, action| {
+            InputEvent::Gamepad(GamepadEvent {
+                controller_id,
+                button: GamepadButton::A,
+                action,
+                timestamp: Instant::now(),
+            })
+        };
+        let mut cfg = AppConfig::default();
+        let mut state = DisplayState::new(&cfg);
+        state.process_event(event(0, KeyAction::Down));
+        assert!(state.active_items().is_empty());
+
+        cfg.behavior.show_gamepad = true;
+        state.update_config(&cfg);
+        state.process_event(event(1, KeyAction::Down));
+        state.process_event(event(1, KeyAction::Up));
+        let items = state.active_items();
+        assert_eq!(items.len(), 1);
+        assert!(matches!(&items[0].kind, DisplayItemKind::GamepadInput { label } if label == "Pad2 A"));
+    }
+
+    #[test]
+    fn modifier_state_is_pinned_until_release_countdown_ends() {
+        let start = Instant::now();
+        let key = |key: KeyCode, action, ctrl, ms| {
+            match key_event(key.0, action, start + Duration::from_millis(ms)) {
+                InputEvent::Key(ke) => InputEvent::Key(KeyEvent {
+                    modifiers: Modifiers {
+                        ctrl,
+                        ..Modifiers::default()
+                    },
+                    ..ke
+                }),
+                other => other,
             }
-        }
+        };
+        let mut cfg = AppConfig::default();
+        cfg.behavior.show_modifier_state = true;
+        cfg.display.max_items = 1;
+        let mut state = DisplayState::new(&cfg);
+
+        state.process_event(key(KeyCode::L_CTRL, KeyAction::Down, false, 0));
+        state.process_event(key(KeyCode(0x43), KeyAction::Down, true, 10));
+        state.process_event(key(KeyCode(0x56), KeyAction::Down, true, 20));
+        let items = state.active_items();
+        assert_eq!(items.len(), 2);
+        assert!(matches!(items[0].kind, DisplayItemKind::ModifierState { ctrl: true, shift: false, .. }));
+
+        // 押下中は display_duration を過ぎても残る
+        state.tick(start + Duration::from_secs(60));
+        assert!(matches!(state.active_items()[0].kind, DisplayItemKind::ModifierState { .. }));
+
+        state.process_event(key(KeyCode::L_CTRL, KeyAction::Up, true, 60_000));
+        state.tick(start + Duration::from_millis(60_400));
+        assert_eq!(state.active_items().len(), 1);
+        state.tick(start + Duration::from_millis(60_500));
+        assert!(state.active_items().is_empty());
+    }
 
-        if i + 3 <= bytes.len() {
-            let chunk = &s[i..i + 3];
-            if let Some(kana) = romaji_map_3(chunk) {
-                out.push_str(kana);
-                i += 3;
-                continue;
+    #[test]
+    fn only_fading_once_every_item_has_started_fading_out() {
+        let start = Instant::now();
+        let mut state = DisplayState::new(&AppConfig::default());
+        state.process_event(clipboard_event("hello", start));
+        assert!(!state.is_only_fading());
+
+        state.tick(start + Duration::from_secs(60));
+        assert!(state.is_only_fading());
+
+        state.process_event(clipboard_event("world", start + Duration::from_secs(60)));
+        assert!(!state.is_only_fading());
+    }
+
+    #[test]
+    fn process_events_defers_config_changes_to_batch_end() {
+        let start = Instant::now();
+        let key = |vk, ms| key_event(vk, KeyAction::Down, start + Duration::from_millis(ms));
+        let mut cfg = AppConfig::default();
+        cfg.behavior.group_timeout_ms = 0;
+        let mut state = DisplayState::new(&cfg);
+
+        let deferred = state.process_events(vec![
+            key(0x41, 0),
+            InputEvent::ConfigChanged,
+            InputEvent::ClearDisplay,
+            key(0x42, 10),
+            InputEvent::DpiChanged {
+                dpi: 144,
+                suggested_rect: [0, 0, 100, 100],
+            },
+            InputEvent::ConfigChanged,
+            key(0x43, 20),
+        ]);
+
+        assert_eq!(deferred.len(), 2);
+        assert!(matches!(deferred[0], InputEvent::DpiChanged { dpi: 144, .. }));
+        assert!(matches!(deferred[1], InputEvent::ConfigChanged));
+        let labels: Vec<_> = state
+            .active_items()
+            .iter()
+            .map(|item| match &item.kind {
+                DisplayItemKind::KeyStroke { label, .. } => label.as_str(),
+                _ => "",
+            })
+            .collect();
+        assert_eq!(labels, ["B", "C"]);
+    }
+
+    #[test]
+    fn persist_lock_indicator_stays_while_caps_lock_is_on() {
+        let start = Instant::now();
+        let lock = |caps, ms| {
+            InputEvent::LockState(LockStateEvent {
+                caps_lock: caps,
+                num_lock: false,
+                scroll_lock: false,
+                timestamp: start + Duration::from_millis(ms),
+            })
+        };
+        let mut cfg = AppConfig::default();
+        cfg.behavior.persist_lock_indicator = true;
+        let mut state = DisplayState::new(&cfg);
+
+        state.process_event(lock(true, 0));
+        state.tick(start + Duration::from_secs(60));
+        assert_eq!(state.active_items().len(), 1);
+        assert!(state.active_items()[0].pinned);
+
+        // 解除後は通常の表示時間 + フェードで消える
+        state.process_event(lock(false, 60_000));
+        assert!(!state.active_items()[0].pinned);
+        let display_ms = cfg.display.display_duration_ms;
+        state.tick(start + Duration::from_millis(60_000 + display_ms + 1));
+        assert_eq!(state.active_items()[0].phase, DisplayPhase::FadingOut);
+        state.tick(start + Duration::from_millis(60_000 + display_ms + cfg.display.fade_duration_ms + 1));
+        assert!(state.active_items().is_empty());
+    }
+
+    #[test]
+    fn pinned_item_kinds_never_expire_and_are_not_evicted() {
+        let start = Instant::now();
+        let lock = |caps, ms| {
+            InputEvent::LockState(LockStateEvent {
+                caps_lock: caps,
+                num_lock: false,
+                scroll_lock: false,
+                timestamp: start + Duration::from_millis(ms),
+            })
+        };
+        let mut cfg = AppConfig::default();
+        cfg.behavior.pinned_item_kinds = vec!["LockIndicator".into()];
+        cfg.display.max_items = 1;
+        let mut state = DisplayState::new(&cfg);
+
+        state.process_event(lock(true, 0));
+        let key = |vk, ms| key_event(vk, KeyAction::Down, start + Duration::from_millis(ms));
+        state.process_event(key(0x41, 10));
+        state.process_event(key(0x42, 20));
+        let items = state.active_items();
+        assert_eq!(items.len(), 2);
+        assert!(items[0].pinned);
+
+        // 2回目のロック変更は固定中のアイテムを更新する
+        state.process_event(lock(false, 30));
+        state.tick(start + Duration::from_secs(60));
+        state.tick(start + Duration::from_secs(61));
+        let items = state.active_items();
+        assert_eq!(items.len(), 1);
+        assert!(matches!(items[0].kind, DisplayItemKind::LockIndicator { caps: false, .. }));
+
+        state.clear();
+        assert!(state.active_items().is_empty());
+
+        cfg.behavior.pinned_item_kinds = vec!["Unknown".into()];
+        assert!(cfg.validate().is_err());
+    }
+
+    #[test]
+    fn simultaneous_display_keeps_each_held_key_until_release() {
+        let start = Instant::now();
+        let key = |vk, action, ms| key_event(vk, action, start + Duration::from_millis(ms));
+        let mut cfg = AppConfig::default();
+        cfg.behavior.key_transition_mode = KeyTransitionMode::SimultaneousDisplay;
+        let mut state = DisplayState::new(&cfg);
+
+        state.process_event(key(0x41, KeyAction::Down, 0));
+        state.process_event(key(0x53, KeyAction::Down, 10));
+        // キーリピートでは増えない
+        state.process_event(key(0x41, KeyAction::Down, 40));
+        assert_eq!(state.active_items().len(), 2);
+
+        state.process_event(key(0x53, KeyAction::Up, 50));
+        state.tick(start + Duration::from_secs(60));
+        let items = state.active_items();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].opacity, 1.0);
+        assert!(matches!(&items[0].kind, DisplayItemKind::KeyStroke { label, .. } if label == "A"));
+    }
+
+    #[test]
+    fn batch_processing_matches_per_event_processing() {
+        let start = Instant::now();
+        let key = |vk, action, ms| key_event(vk, action, start + Duration::from_millis(ms));
+        let events = vec![
+            key(0x41, KeyAction::Down, 0),
+            key(0x53, KeyAction::Down, 10),
+            key(0x44, KeyAction::Down, 20),
+            // max_items で押し出された A を押し直すと、新しいアイテムになる
+            key(0x41, KeyAction::Down, 30),
+            key(0x44, KeyAction::Up, 40),
+            InputEvent::ToggleOsd,
+        ];
+        let labels = |state: &DisplayState| -> Vec<(String, KeyAction)> {
+            state
+                .active_items()
+                .iter()
+                .filter_map(|item| match &item.kind {
+                    DisplayItemKind::KeyStroke { label, action, .. } => Some((label.clone(), *action)),
+                    _ => None,
+                })
+                .collect()
+        };
+
+        for mode in [KeyTransitionMode::SingleCell, KeyTransitionMode::SimultaneousDisplay] {
+            let mut cfg = AppConfig::default();
+            cfg.behavior.key_transition_mode = mode;
+            cfg.display.max_items = 2;
+
+            let mut one_by_one = DisplayState::new(&cfg);
+            for event in events.clone() {
+                one_by_one.process_event(event);
             }
+            let mut batched = DisplayState::new(&cfg);
+            let deferred = batched.batch_process_events(events.clone());
+
+            assert!(matches!(deferred.as_slice(), [InputEvent::ToggleOsd]));
+            assert_eq!(labels(&batched), labels(&one_by_one));
+            assert_eq!(batched.active_presses.len(), one_by_one.active_presses.len());
         }
+    }
 
-        if i + 2 <= bytes.len() {
-            let chunk = &s[i..i + 2];
-            if let Some(kana) = romaji_map_2(chunk) {
-                out.push_str(kana);
-                i += 2;
-                continue;
-            }
+    #[test]
+    fn macro_recording_buffers_key_events_with_relative_time() {
+        let start = Instant::now();
+        let mut cfg = AppConfig::default();
+        cfg.behavior.ignored_keys = vec!["A".to_string()];
+        let mut state = DisplayState::new(&cfg);
+
+        state.process_event(key_down(KeyCode(0x41), start));
+        state.start_macro_recording(start);
+        assert!(state.is_recording_macro());
+        state.process_event(key_down(KeyCode(0x41), start + Duration::from_millis(120)));
+        state.process_event(key_down(KeyCode(0x42), start + Duration::from_millis(300)));
+
+        let recorded = state.stop_macro_recording();
+        assert!(!state.is_recording_macro());
+        let offsets: Vec<u128> = recorded.iter().map(|(d, _)| d.as_millis()).collect();
+        assert_eq!(offsets, vec![120, 300]);
+        assert_eq!(recorded[0].1.key, KeyCode(0x41));
+        assert!(state.stop_macro_recording().is_empty());
+    }
+
+    #[test]
+    fn mouse_drag_shows_direction_arrow() {
+        let start = Instant::now();
+        let mouse = |action, position, ms| {
+            InputEvent::Mouse(MouseEvent {
+                button: MouseButton::Left,
+                action,
+                position,
+                source: MouseSource::Hook,
+                timestamp: start + Duration::from_millis(ms),
+            })
+        };
+        let mut cfg = AppConfig::default();
+        cfg.behavior.show_mouse_drag = true;
+        let mut state = DisplayState::new(&cfg);
+
+        state.process_event(mouse(MouseAction::Down, (100, 100), 0));
+        state.process_event(mouse(MouseAction::Up, (110, 95), 100));
+        assert_eq!(state.active_items().len(), 1);
+
+        state.process_event(mouse(MouseAction::Down, (100, 100), 200));
+        state.process_event(mouse(MouseAction::Up, (200, 0), 300));
+        let items = state.active_items();
+        assert_eq!(items.len(), 3);
+        assert!(matches!(&items[2].kind, DisplayItemKind::KeyStroke { label, .. } if label == "Drag↗"));
+
+        assert_eq!(drag_arrow(0.0, 50.0), "↓");
+        assert_eq!(drag_arrow(-50.0, 0.0), "←");
+    }
+
+    #[test]
+    fn duplicate_mouse_events_from_both_paths_are_merged() {
+        let now = Instant::now();
+        let click = |source, ms| {
+            InputEvent::Mouse(MouseEvent {
+                button: MouseButton::Left,
+                action: MouseAction::Down,
+                position: (0, 0),
+                source,
+                timestamp: now + Duration::from_millis(ms),
+            })
+        };
+        let mut cfg = AppConfig::default();
+        cfg.behavior.mouse_input_mode = MouseInputMode::Both;
+        let mut state = DisplayState::new(&cfg);
+        state.process_event(click(MouseSource::Hook, 0));
+        state.process_event(click(MouseSource::RawInput, 5));
+        assert_eq!(state.active_items().len(), 1);
+
+        state.process_event(click(MouseSource::RawInput, 200));
+        assert_eq!(state.active_items().len(), 2);
+    }
+
+    #[test]
+    fn fast_mouse_repeats_from_one_source_are_kept() {
+        let now = Instant::now();
+        let wheel = |ms| {
+            InputEvent::Mouse(MouseEvent {
+                button: MouseButton::Middle,
+                action: MouseAction::Wheel(120),
+                position: (0, 0),
+                source: MouseSource::Hook,
+                timestamp: now + Duration::from_millis(ms),
+            })
+        };
+        for mode in [MouseInputMode::Hook, MouseInputMode::Both] {
+            let mut cfg = AppConfig::default();
+            cfg.behavior.mouse_input_mode = mode;
+            cfg.behavior.show_repeat_count = false;
+            let mut state = DisplayState::new(&cfg);
+            state.process_event(wheel(0));
+            state.process_event(wheel(10));
+            state.process_event(wheel(20));
+            assert_eq!(state.active_items().len(), 3, "{mode:?}");
         }
+    }
 
-        if i + 1 <= bytes.len() {
-            let chunk = &s[i..i + 1];
-            if let Some(kana) = romaji_map_1(chunk) {
-                out.push_str(kana);
-                i += 1;
-                continue;
+    #[test]
+    fn clipboard_redact_patterns_replace_matches_and_skip_invalid() {
+        let mut cfg = AppConfig::default();
+        cfg.privacy.clipboard_redact_patterns = vec![r"\d{4}-\d{4}".into(), "(".into()];
+        let mut state = DisplayState::new(&cfg);
+
+        state.process_event(clipboard_event("card 1234-5678 ok", Instant::now()));
+        let items = state.active_items();
+        assert!(matches!(
+            &items[0].kind,
+            DisplayItemKind::ClipboardPreview { text } if text == "card [REDACTED] ok"
+        ));
+    }
+
+    #[test]
+    fn clipboard_redact_patterns_follow_update_config() {
+        let mut cfg = AppConfig::default();
+        cfg.privacy.clipboard_redact_patterns = vec!["alpha".into()];
+        let mut state = DisplayState::new(&cfg);
+        let t0 = Instant::now();
+        state.process_event(clipboard_event("alpha beta", t0));
+
+        cfg.privacy.clipboard_redact_patterns = vec!["beta".into()];
+        state.update_config(&cfg);
+        state.process_event(clipboard_event("alpha beta!", t0 + Duration::from_secs(1)));
+
+        let texts: Vec<_> = state
+            .active_items()
+            .iter()
+            .filter_map(|item| match &item.kind {
+                DisplayItemKind::ClipboardPreview { text } => Some(text.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(texts, vec!["[REDACTED] beta", "alpha [REDACTED]!"]);
+    }
+
+    #[test]
+    fn export_event_applies_clipboard_privacy_settings() {
+        let mut cfg = AppConfig::default();
+        cfg.behavior.clipboard_max_chars = 12;
+        cfg.privacy.mask_clipboard_patterns = vec!["*password*".into()];
+        cfg.privacy.clipboard_redact_patterns = vec![r"\d{4}-\d{4}".into()];
+        let mut state = DisplayState::new(&cfg);
+        let exported = |state: &mut DisplayState, text: &str| {
+            match state.export_event(&clipboard_event(text, Instant::now())) {
+                Some(InputEvent::Clipboard(ClipboardEvent {
+                    content: ClipboardContent::Text { text, .. },
+                    ..
+                })) => Some(text),
+                _ => None,
             }
-        }
+        };
 
-        // 末尾の未確定1文字は待機し、それ以外の未知綴りは素通しで継続。
-        // 例: "nihogngo" -> "にほgんご"
-        if i + 1 >= bytes.len() {
-            break;
-        }
-        out.push(bytes[i] as char);
-        i += 1;
+        assert_eq!(exported(&mut state, "my password").as_deref(), Some("[hidden]"));
+        assert_eq!(exported(&mut state, "1234-5678").as_deref(), Some("[REDACTED]"));
+        assert_eq!(
+            exported(&mut state, "abcdefghijklmnop").as_deref(),
+            Some("abcdefghijkl...")
+        );
+        assert!(state.export_event(&InputEvent::ClearDisplay).is_some());
+
+        cfg.behavior.show_clipboard = false;
+        state.update_config(&cfg);
+        assert_eq!(exported(&mut state, "hello"), None);
     }
 
-    out
-}
+    #[test]
+    fn export_event_drops_events_hidden_from_the_osd() {
+        let mut cfg = AppConfig::default();
+        cfg.behavior.ignored_keys = vec!["a".into()];
+        cfg.behavior.show_ime_composition = false;
+        cfg.behavior.app_key_filters = vec![AppKeyFilter {
+            app_name: "Game.exe".into(),
+            ignored_keys: vec!["Space".into()],
+            show_only_keys: None,
+        }];
+        let mut state = DisplayState::new(&cfg);
+        let t0 = Instant::now();
+
+        assert!(state.export_event(&key_down(KeyCode(0x41), t0)).is_none());
+        assert!(state.export_event(&key_down(KeyCode(0x42), t0)).is_some());
+        assert!(state.export_event(&key_down(KeyCode::SPACE, t0)).is_some());
+        state.set_foreground_app(Some("game.exe".into()));
+        assert!(state.export_event(&key_down(KeyCode::SPACE, t0)).is_none());
+
+        let composition = ime_event(
+            ImeEventKind::CompositionUpdate { text: "にほんご".into() },
+            t0,
+        );
+        assert!(state.export_event(&composition).is_none());
+        cfg.behavior.show_ime_composition = true;
+        state.update_config(&cfg);
+        assert!(state.export_event(&composition).is_some());
+    }
 
-fn is_romaji_vowel(c: char) -> bool {
-    matches!(c, 'a' | 'i' | 'u' | 'e' | 'o')
-}
+    #[test]
+    fn clipboard_source_app_appended_when_enabled() {
+        let mut cfg = AppConfig::default();
+        let mut state = DisplayState::new(&cfg);
+        let copied = |text: &str, timestamp| {
+            InputEvent::Clipboard(ClipboardEvent {
+                content: ClipboardContent::Text {
+                    text: text.to_string(),
+                    source_app: Some("notepad.exe".into()),
+                    format: ClipboardTextFormat::Unicode,
+                },
+                timestamp,
+            })
+        };
+        let t0 = Instant::now();
 
-fn is_romaji_consonant(c: char) -> bool {
-    c.is_ascii_alphabetic() && !is_romaji_vowel(c)
-}
+        state.process_event(copied("hello", t0));
+        cfg.behavior.show_clipboard_source = true;
+        state.update_config(&cfg);
+        state.process_event(copied("world", t0 + Duration::from_secs(1)));
 
-fn romaji_map_3(s: &str) -> Option<&'static str> {
-    let v = match s {
-        "kya" => "きゃ",
-        "kyu" => "きゅ",
-        "kyo" => "きょ",
-        "gya" => "ぎゃ",
-        "gyu" => "ぎゅ",
-        "gyo" => "ぎょ",
-        "sha" | "sya" => "しゃ",
-        "shu" | "syu" => "しゅ",
-        "sho" | "syo" => "しょ",
-        "cha" | "tya" | "cya" => "ちゃ",
-        "chu" | "tyu" | "cyu" => "ちゅ",
-        "cho" | "tyo" | "cyo" => "ちょ",
-        "nya" => "にゃ",
-        "nyu" => "にゅ",
-        "nyo" => "にょ",
-        "hya" => "ひゃ",
-        "hyu" => "ひゅ",
-        "hyo" => "ひょ",
-        "mya" => "みゃ",
-        "myu" => "みゅ",
-        "myo" => "みょ",
-        "rya" => "りゃ",
-        "ryu" => "りゅ",
-        "ryo" => "りょ",
-        "bya" => "びゃ",
-        "byu" => "びゅ",
-        "byo" => "びょ",
-        "pya" => "ぴゃ",
-        "pyu" => "ぴゅ",
-        "pyo" => "ぴょ",
-        "ja" | "jya" | "zya" => "じゃ",
-        "ju" | "jyu" | "zyu" => "じゅ",
-        "jo" | "jyo" | "zyo" => "じょ",
-        "shi" => "し",
-        "chi" => "ち",
-        "tsu" => "つ",
-        "dya" => "ぢゃ",
-        "dyu" => "ぢゅ",
-        "dyo" => "ぢょ",
-        _ => return None,
-    };
-    Some(v)
-}
+        let texts: Vec<_> = state
+            .active_items()
+            .iter()
+            .filter_map(|item| match &item.kind {
+                DisplayItemKind::ClipboardPreview { text } => Some(text.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(texts, vec!["hello".to_string(), "world (from notepad.exe)".to_string()]);
+    }
 
-fn romaji_map_2(s: &str) -> Option<&'static str> {
-    let v = match s {
-        "ka" => "か",
-        "ki" => "き",
-        "ku" => "く",
-        "ke" => "け",
-        "ko" => "こ",
-        "ga" => "が",
-        "gi" => "ぎ",
-        "gu" => "ぐ",
-        "ge" => "げ",
-        "go" => "ご",
-        "sa" => "さ",
-        "su" => "す",
-        "se" => "せ",
-        "so" => "そ",
-        "za" => "ざ",
-        "ji" => "じ",
-        "zu" => "ず",
-        "ze" => "ぜ",
-        "zo" => "ぞ",
-        "ta" => "た",
-        "te" => "て",
-        "to" => "と",
-        "da" => "だ",
-        "di" => "ぢ",
-        "du" => "づ",
-        "de" => "で",
-        "do" => "ど",
-        "na" => "な",
-        "ni" => "に",
-        "nu" => "ぬ",
-        "ne" => "ね",
-        "no" => "の",
-        "ha" => "は",
-        "hi" => "ひ",
-        "fu" => "ふ",
-        "he" => "へ",
-        "ho" => "ほ",
-        "ba" => "ば",
-        "bi" => "び",
-        "bu" => "ぶ",
-        "be" => "べ",
-        "bo" => "ぼ",
-        "pa" => "ぱ",
-        "pi" => "ぴ",
-        "pu" => "ぷ",
-        "pe" => "ぺ",
-        "po" => "ぽ",
-        "ma" => "ま",
-        "mi" => "み",
-        "mu" => "む",
-        "me" => "め",
-        "mo" => "も",
-        "ya" => "や",
-        "yu" => "ゆ",
-        "yo" => "よ",
-        "ra" => "ら",
-        "ri" => "り",
-        "ru" => "る",
-        "re" => "れ",
-        "ro" => "ろ",
-        "wa" => "わ",
-        "wo" => "を",
-        "fa" => "ふぁ",
-        "fi" => "ふぃ",
-        "fe" => "ふぇ",
-        "fo" => "ふぉ",
-        "va" => "ゔぁ",
-        "vi" => "ゔぃ",
-        "vu" => "ゔ",
-        "ve" => "ゔぇ",
-        "vo" => "ゔぉ",
-        _ => return None,
-    };
-    Some(v)
-}
+    #[test]
+    fn clipboard_mask_patterns() {
+        let patterns = vec!["sk-*".to_string(), RANDOM_TOKEN_PATTERN.to_string()];
+        assert!(should_mask_clipboard("SK-abc123", &patterns));
+        assert!(should_mask_clipboard("aZ3$kP9!qW7@xL2#", &patterns));
+        assert!(!should_mask_clipboard("hello world", &patterns));
+        assert!(!should_mask_clipboard("aZ3$kP9!qW7@xL2#", &[]));
+        assert!(wildcard_match(&['a', '?', 'c'], &['a', 'b', 'c']));
+        assert!(!wildcard_match(&['a', '*', 'd'], &['a', 'b', 'c']));
+    }
 
-fn romaji_map_1(s: &str) -> Option<&'static str> {
-    let v = match s {
-        "a" => "あ",
-        "i" => "い",
-        "u" => "う",
-        "e" => "え",
-        "o" => "お",
-        _ => return None,
-    };
-    Some(v)
+    fn ime_event(kind: ImeEventKind, timestamp: Instant) -> InputEvent {
+        InputEvent::Ime(ImeEvent { kind, timestamp })
+    }
+
+    #[test]
+    fn snapshot_copies_items_and_ime_text() {
+        let mut cfg = AppConfig::default();
+        cfg.behavior.show_ime_composition = true;
+        let mut state = DisplayState::new(&cfg);
+        let t0 = Instant::now();
+
+        let empty = state.snapshot();
+        assert!(empty.items.is_empty() && empty.ime_text.is_none());
+
+        state.process_event(ime_event(ImeEventKind::CompositionUpdate { text: "かんじ".into() }, t0));
+        let snapshot = state.snapshot();
+        state.clear();
+        assert_eq!(snapshot.items.len(), 1);
+        assert!(snapshot.ime_composing);
+        assert_eq!(snapshot.ime_text.as_deref(), Some("かんじ"));
+        assert_eq!(snapshot.stats.items_created, 1);
+
+        let json = serde_json::to_value(&snapshot).unwrap();
+        assert_eq!(json["items"][0]["kind"]["ImeComposition"]["text"], "かんじ");
+        assert!(json["items"][0].get("created_at").is_none());
+    }
+
+    #[test]
+    fn ime_candidate_shown_when_enabled_and_cleared_on_composition_end() {
+        let mut cfg = AppConfig::default();
+        cfg.behavior.show_ime_composition = true;
+        let mut state = DisplayState::new(&cfg);
+        let t0 = Instant::now();
+        let candidate = |text: &str| ImeEventKind::CandidateUpdate { text: text.into() };
+
+        state.process_event(ime_event(ImeEventKind::CompositionUpdate { text: "かんじ".into() }, t0));
+        state.process_event(ime_event(candidate("漢字"), t0));
+        assert_eq!(state.active_items().len(), 1);
+
+        cfg.behavior.show_ime_candidates = true;
+        state.update_config(&cfg);
+        state.process_event(ime_event(candidate("漢字"), t0));
+        state.process_event(ime_event(candidate("感じ"), t0));
+        let candidates: Vec<_> = state
+            .active_items()
+            .iter()
+            .filter_map(|item| match &item.kind {
+                DisplayItemKind::ImeCandidateList { entries, selected } => Some(entries[*selected].clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(candidates, vec!["感じ".to_string()]);
+
+        state.process_event(ime_event(ImeEventKind::CompositionEnd { result: String::new() }, t0));
+        assert!(state.active_items().is_empty());
+    }
+
+    #[test]
+    fn ime_candidate_list_pages_around_selection() {
+        let mut cfg = AppConfig::default();
+        cfg.behavior.show_ime_composition = true;
+        cfg.behavior.show_ime_candidates = true;
+        cfg.behavior.ime_candidate_max_items = 2;
+        let mut state = DisplayState::new(&cfg);
+        let t0 = Instant::now();
+        let list = |state: &DisplayState| {
+            state.active_items().iter().find_map(|item| match &item.kind {
+                DisplayItemKind::ImeCandidateList { entries, selected } => {
+                    Some((entries.clone(), *selected))
+                }
+                _ => None,
+            })
+        };
+
+        let entries: Vec<String> = ["漢字", "感じ", "幹事"].into_iter().map(String::from).collect();
+        state.process_event(InputEvent::ImeCandidates(entries));
+        assert_eq!(list(&state), Some((vec!["漢字".into(), "感じ".into()], 0)));
+
+        state.process_event(ime_event(ImeEventKind::CandidateUpdate { text: "幹事".into() }, t0));
+        assert_eq!(list(&state), Some((vec!["幹事".into()], 0)));
+
+        state.process_event(InputEvent::ImeCandidates(Vec::new()));
+        assert_eq!(list(&state), None);
+    }
+
+    #[test]
+    fn ime_commit_flashes_confirmed_text_when_enabled() {
+        let mut cfg = AppConfig::default();
+        cfg.behavior.show_ime_composition = true;
+        cfg.behavior.show_ime_commit = true;
+        let mut state = DisplayState::new(&cfg);
+        let t0 = Instant::now();
+
+        state.process_event(ime_event(ImeEventKind::CompositionUpdate { text: "にほんご".into() }, t0));
+        state.process_event(ime_event(ImeEventKind::CompositionEnd { result: "日本語".into() }, t0));
+        let items = state.active_items();
+        assert_eq!(items.len(), 1);
+        assert!(matches!(
+            &items[0].kind,
+            DisplayItemKind::Shortcut { keys_label, action_label }
+                if keys_label == "確定" && action_label == "日本語"
+        ));
+
+        // キャンセル（空の確定文字列）では何も出さない
+        state.process_event(ime_event(ImeEventKind::CompositionUpdate { text: "あ".into() }, t0));
+        state.process_event(ime_event(ImeEventKind::CompositionEnd { result: String::new() }, t0));
+        assert_eq!(state.active_items().len(), 1);
+    }
+
+    #[test]
+    fn romaji_overrides_follow_update_config() {
+        let mut cfg = AppConfig::default();
+        cfg.behavior.show_ime_composition = true;
+        let mut state = DisplayState::new(&cfg);
+        cfg.behavior.romaji_overrides.insert("WI".into(), "ゐ".into());
+        state.update_config(&cfg);
+        let t0 = Instant::now();
+        state.process_event(key_down(KeyCode(0x16), t0)); // VK_IME_ON
+        state.process_event(key_down(KeyCode(0x57), t0 + Duration::from_millis(10)));
+        state.process_event(key_down(KeyCode(0x49), t0 + Duration::from_millis(20)));
+        let ime_text = state.active_items().iter().find_map(|item| match &item.kind {
+            DisplayItemKind::ImeComposition { text } => Some(text.clone()),
+            _ => None,
+        });
+        assert_eq!(ime_text.as_deref(), Some("ゐ"));
+    }
+
+    #[test]
+    fn ime_fallback_commit_flushes_trailing_n() {
+        let mut cfg = AppConfig::default();
+        cfg.behavior.show_ime_composition = true;
+        let mut state = DisplayState::new(&cfg);
+        let t0 = Instant::now();
+
+        state.process_event(key_down(KeyCode(0x16), t0)); // VK_IME_ON
+        for (i, vk) in [0x48, 0x4F, 0x4E].into_iter().enumerate() {
+            state.process_event(key_down(KeyCode(vk), t0 + Duration::from_millis(10 * (i as u64 + 1))));
+        }
+        let ime_text = |state: &DisplayState| {
+            state.active_items().iter().find_map(|item| match &item.kind {
+                DisplayItemKind::ImeComposition { text } => Some(text.clone()),
+                _ => None,
+            })
+        };
+        assert_eq!(ime_text(&state).as_deref(), Some("ほ"));
+
+        state.process_event(key_down(KeyCode(0x0D), t0 + Duration::from_millis(50)));
+        assert_eq!(ime_text(&state).as_deref(), Some("ほん"));
+    }
+
+    #[test]
+    fn ime_fallback_katakana_mode() {
+        let mut cfg = AppConfig::default();
+        cfg.behavior.show_ime_composition = true;
+        cfg.behavior.ime_display_mode = ImeDisplayMode::Katakana;
+        let mut state = DisplayState::new(&cfg);
+        let t0 = Instant::now();
+
+        state.process_event(key_down(KeyCode(0x16), t0)); // VK_IME_ON
+        for (i, vk) in [0x53, 0x55, 0x53, 0x48, 0x49].into_iter().enumerate() {
+            state.process_event(key_down(KeyCode(vk), t0 + Duration::from_millis(10 * (i as u64 + 1))));
+        }
+        let ime_text = state.active_items().iter().find_map(|item| match &item.kind {
+            DisplayItemKind::ImeComposition { text } => Some(text.clone()),
+            _ => None,
+        });
+        assert_eq!(ime_text.as_deref(), Some("スシ"));
+    }
+
+    #[test]
+    fn ime_display_mode_auto_follows_ime_conversion_mode() {
+        let mut cfg = AppConfig::default();
+        cfg.behavior.show_ime_composition = true;
+        let mut state = DisplayState::new(&cfg);
+        let t0 = Instant::now();
+        let ime_text = |state: &DisplayState| {
+            state.active_items().iter().find_map(|item| match &item.kind {
+                DisplayItemKind::ImeComposition { text } => Some(text.clone()),
+                _ => None,
+            })
+        };
+
+        state.process_event(ime_event(ImeEventKind::ConversionModeChanged { katakana: true }, t0));
+        state.process_event(key_down(KeyCode(0x16), t0)); // VK_IME_ON
+        state.process_event(key_down(KeyCode(0x4B), t0 + Duration::from_millis(10)));
+        state.process_event(key_down(KeyCode(0x41), t0 + Duration::from_millis(20)));
+        assert_eq!(ime_text(&state).as_deref(), Some("カ"));
+
+        cfg.behavior.ime_display_mode = ImeDisplayMode::Hiragana;
+        state.update_config(&cfg);
+        state.process_event(key_down(KeyCode(0x49), t0 + Duration::from_millis(30)));
+        assert_eq!(ime_text(&state).as_deref(), Some("かい"));
+    }
 }
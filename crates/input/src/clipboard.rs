@@ -1,10 +1,21 @@
-use windows::Win32::Foundation::{HGLOBAL, HWND};
+use windows::core::w;
+use windows::Win32::Foundation::{HANDLE, HGLOBAL, HWND};
+use windows::Win32::Globalization::{MultiByteToWideChar, MULTI_BYTE_TO_WIDE_CHAR_FLAGS};
+use windows::Win32::Graphics::Gdi::{
+    GetObjectW, BITMAP, BITMAPCOREHEADER, BITMAPINFOHEADER, HBITMAP,
+};
 use windows::Win32::System::DataExchange::{
-    AddClipboardFormatListener, CloseClipboard, GetClipboardData, IsClipboardFormatAvailable,
-    OpenClipboard, RemoveClipboardFormatListener,
+    AddClipboardFormatListener, CloseClipboard, GetClipboardData, GetClipboardOwner,
+    IsClipboardFormatAvailable, OpenClipboard, RegisterClipboardFormatW,
+    RemoveClipboardFormatListener,
 };
 use windows::Win32::System::Memory::{GlobalLock, GlobalSize, GlobalUnlock};
-use windows::Win32::System::Ole::CF_UNICODETEXT;
+use windows::Win32::System::Ole::{CF_BITMAP, CF_DIB, CF_DIBV5, CF_HDROP, CF_UNICODETEXT};
+use windows::Win32::UI::Shell::{DragQueryFileW, HDROP};
+
+use ystrokey_core::{ClipboardContent, ClipboardTextFormat};
+
+use crate::privacy::process_name_of_window;
 
 /// クリップボード変更リスナー
 ///
@@ -34,33 +45,27 @@ impl ClipboardListener {
                 return None;
             }
 
-            // CloseClipboard を確実に呼ぶため、クロージャで本体を実行
-            let result = (|| -> Option<String> {
-                let handle = GetClipboardData(CF_UNICODETEXT.0 as u32).ok()?;
-                let hglobal = HGLOBAL(handle.0);
-                let size = GlobalSize(hglobal);
-                if size == 0 {
-                    return None;
-                }
-                let max_u16_len = size / 2;
-                let ptr = GlobalLock(hglobal) as *const u16;
-                if ptr.is_null() {
-                    return None;
-                }
-
-                // null終端までの長さを計算（GlobalSize上限付き）
-                let mut len = 0;
-                while len < max_u16_len && *ptr.add(len) != 0 {
-                    len += 1;
-                }
+            // CloseClipboard を確実に呼ぶため、本体は別関数で実行
+            let result = read_unicode_text();
 
-                let slice = std::slice::from_raw_parts(ptr, len);
-                let text = String::from_utf16_lossy(slice);
+            let _ = CloseClipboard();
+            result
+        }
+    }
 
-                let _ = GlobalUnlock(hglobal);
+    /// クリップボードの内容を種別付きで取得
+    ///
+    /// ファイル(CF_HDROP) → テキスト(CF_UNICODETEXT) → HTML(CF_HTML) → RTF → 画像(CF_DIB/CF_BITMAP)
+    /// の優先順で判定する。Unicode テキストが空白だけなら HTML / RTF を試す。
+    /// いずれも取得できなければ `None`。
+    pub fn get_content(hwnd: HWND) -> Option<ClipboardContent> {
+        unsafe {
+            if OpenClipboard(hwnd).is_err() {
+                return None;
+            }
 
-                Some(text)
-            })();
+            // CloseClipboard を確実に呼ぶため、本体は別関数で実行
+            let result = read_content(hwnd);
 
             let _ = CloseClipboard();
             result
@@ -68,6 +73,507 @@ impl ClipboardListener {
     }
 }
 
+/// クリップボード所有者（最後に書き込んだウィンドウ）のプロセスの実行ファイル名を取得
+///
+/// 所有者がいない場合や、自分自身（`hwnd`）が所有者の場合は None。
+pub fn get_clipboard_owner_name(hwnd: HWND) -> Option<String> {
+    let owner = unsafe { GetClipboardOwner() }.ok()?;
+    if owner == hwnd {
+        return None;
+    }
+    process_name_of_window(owner)
+}
+
+/// クリップボードを開いた状態で呼び出すこと
+unsafe fn read_content(hwnd: HWND) -> Option<ClipboardContent> {
+    if is_format_available(CF_HDROP.0 as u32) {
+        if let Some(files) = read_file_drop() {
+            return Some(ClipboardContent::Files(files));
+        }
+    }
+    let text_content = |text, format| ClipboardContent::Text {
+        text,
+        source_app: get_clipboard_owner_name(hwnd),
+        format,
+    };
+    if is_format_available(CF_UNICODETEXT.0 as u32) {
+        if let Some(text) = read_unicode_text().filter(|t| !t.trim().is_empty()) {
+            return Some(text_content(text, ClipboardTextFormat::Unicode));
+        }
+    }
+    let cf_html = RegisterClipboardFormatW(w!("HTML Format"));
+    if cf_html != 0 && is_format_available(cf_html) {
+        if let Some(text) = read_html_text(cf_html) {
+            return Some(text_content(text, ClipboardTextFormat::Html));
+        }
+    }
+    let cf_rtf = RegisterClipboardFormatW(w!("Rich Text Format"));
+    if cf_rtf != 0 && is_format_available(cf_rtf) {
+        if let Some(text) = read_rtf_text(cf_rtf) {
+            return Some(text_content(text, ClipboardTextFormat::Rtf));
+        }
+    }
+    if is_format_available(CF_DIBV5.0 as u32) || is_format_available(CF_DIB.0 as u32) {
+        if let Some((width, height)) = read_dib_size() {
+            return Some(ClipboardContent::Image { width, height });
+        }
+    }
+    if is_format_available(CF_BITMAP.0 as u32) {
+        if let Some((width, height)) = read_bitmap_size() {
+            return Some(ClipboardContent::Image { width, height });
+        }
+    }
+    None
+}
+
+unsafe fn is_format_available(format: u32) -> bool {
+    IsClipboardFormatAvailable(format).is_ok()
+}
+
+/// GlobalLock したメモリをバイト列として読み出す
+unsafe fn read_global_bytes(handle: HANDLE) -> Option<Vec<u8>> {
+    let hglobal = HGLOBAL(handle.0);
+    let size = GlobalSize(hglobal);
+    if size == 0 {
+        return None;
+    }
+    let ptr = GlobalLock(hglobal) as *const u8;
+    if ptr.is_null() {
+        return None;
+    }
+    let bytes = std::slice::from_raw_parts(ptr, size).to_vec();
+    let _ = GlobalUnlock(hglobal);
+    Some(bytes)
+}
+
+unsafe fn read_unicode_text() -> Option<String> {
+    let handle = GetClipboardData(CF_UNICODETEXT.0 as u32).ok()?;
+    let hglobal = HGLOBAL(handle.0);
+    let size = GlobalSize(hglobal);
+    if size == 0 {
+        return None;
+    }
+    let max_u16_len = size / 2;
+    let ptr = GlobalLock(hglobal) as *const u16;
+    if ptr.is_null() {
+        return None;
+    }
+
+    // null終端までの長さを計算（GlobalSize上限付き）
+    let mut len = 0;
+    while len < max_u16_len && *ptr.add(len) != 0 {
+        len += 1;
+    }
+
+    let slice = std::slice::from_raw_parts(ptr, len);
+    let text = String::from_utf16_lossy(slice);
+
+    let _ = GlobalUnlock(hglobal);
+
+    Some(text)
+}
+
+/// CF_HDROP からファイルパス一覧を取得
+unsafe fn read_file_drop() -> Option<Vec<String>> {
+    let handle = GetClipboardData(CF_HDROP.0 as u32).ok()?;
+    let hdrop = HDROP(handle.0);
+    let count = DragQueryFileW(hdrop, u32::MAX, None);
+    if count == 0 {
+        return None;
+    }
+
+    let mut files = Vec::with_capacity(count as usize);
+    for i in 0..count {
+        let len = DragQueryFileW(hdrop, i, None) as usize;
+        let mut buf = vec![0u16; len + 1];
+        let copied = DragQueryFileW(hdrop, i, Some(&mut buf)) as usize;
+        files.push(String::from_utf16_lossy(&buf[..copied.min(len)]));
+    }
+    Some(files)
+}
+
+/// CF_HTML のフラグメントからプレーンテキストを抽出
+unsafe fn read_html_text(format: u32) -> Option<String> {
+    let handle = GetClipboardData(format).ok()?;
+    let bytes = read_global_bytes(handle)?;
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    let raw = String::from_utf8_lossy(&bytes[..end]);
+    let text = html_to_text(html_fragment(&raw));
+    if text.trim().is_empty() {
+        None
+    } else {
+        Some(text)
+    }
+}
+
+/// CF_HTML ヘッダの StartFragment/EndFragment（バイトオフセット）から本文を切り出す
+fn html_fragment(raw: &str) -> &str {
+    let offset = |key: &str| -> Option<usize> {
+        let start = raw.find(key)? + key.len();
+        let digits: String = raw[start..].chars().take_while(|c| c.is_ascii_digit()).collect();
+        digits.parse().ok()
+    };
+    match (offset("StartFragment:"), offset("EndFragment:")) {
+        (Some(start), Some(end))
+            if start <= end
+                && end <= raw.len()
+                && raw.is_char_boundary(start)
+                && raw.is_char_boundary(end) =>
+        {
+            &raw[start..end]
+        }
+        _ => raw,
+    }
+}
+
+/// 区切りとして空白を入れるブロック要素（インライン要素では単語をつなげたままにする）
+const HTML_BLOCK_TAGS: &[&str] = &["br", "p", "div", "li", "tr"];
+
+/// タグを除去し、主要な文字実体参照をデコードする
+///
+/// `<script>` / `<style>` の中身とコメントは本文ではないので読み飛ばす。
+fn html_to_text(html: &str) -> String {
+    let mut out = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(lt) = rest.find('<') {
+        out.push_str(&rest[..lt]);
+        rest = &rest[lt..];
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        let Some(gt) = rest.find('>') else {
+            // 閉じていないタグは捨てる
+            rest = "";
+            break;
+        };
+        let tag = &rest[1..gt];
+        rest = &rest[gt + 1..];
+
+        let name = tag
+            .trim_start_matches('/')
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .next()
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        if !tag.starts_with('/') && (name == "script" || name == "style") {
+            // ASCII の小文字化はバイト位置を変えないので、そのまま元の文字列に使える
+            let close = format!("</{name}");
+            rest = rest
+                .to_ascii_lowercase()
+                .find(&close)
+                .and_then(|pos| rest[pos..].find('>').map(|gt| &rest[pos + gt + 1..]))
+                .unwrap_or("");
+            continue;
+        }
+        if HTML_BLOCK_TAGS.contains(&name.as_str())
+            && !out.is_empty()
+            && !out.ends_with(char::is_whitespace)
+        {
+            out.push(' ');
+        }
+    }
+    out.push_str(rest);
+
+    out.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// RTF から制御語・グループを除去したプレーンテキストを抽出
+unsafe fn read_rtf_text(format: u32) -> Option<String> {
+    let handle = GetClipboardData(format).ok()?;
+    let bytes = read_global_bytes(handle)?;
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    let text = rtf_to_text(&bytes[..end]);
+    if text.trim().is_empty() {
+        None
+    } else {
+        Some(text)
+    }
+}
+
+/// 本文ではないグループ（フォント表・色表など）の先頭制御語
+const RTF_SKIP_DESTINATIONS: &[&str] = &[
+    "fonttbl", "colortbl", "stylesheet", "info", "pict", "header", "footer", "listtable",
+    "listoverridetable", "generator",
+];
+
+/// `\ansicpg` が無い RTF の既定コードページ（`\ansi` = Windows-1252）
+const RTF_DEFAULT_CODEPAGE: u32 = 1252;
+
+/// RTF から取り出した文字の組み立て
+///
+/// 本文のバイト列と `\'hh` は文書のコードページ、`\uN` は UTF-16 なので、
+/// 種類が切り替わるまで溜めてからまとめて変換する（2 バイト文字が分割されないように）。
+struct RtfText {
+    out: String,
+    ansi: Vec<u8>,
+    units: Vec<u16>,
+    codepage: u32,
+}
+
+impl RtfText {
+    fn new() -> Self {
+        Self {
+            out: String::new(),
+            ansi: Vec::new(),
+            units: Vec::new(),
+            codepage: RTF_DEFAULT_CODEPAGE,
+        }
+    }
+
+    fn push_ansi(&mut self, byte: u8) {
+        self.flush_units();
+        self.ansi.push(byte);
+    }
+
+    fn push_unit(&mut self, unit: u16) {
+        self.flush_ansi();
+        self.units.push(unit);
+    }
+
+    fn push_char(&mut self, c: char) {
+        self.flush_ansi();
+        self.flush_units();
+        self.out.push(c);
+    }
+
+    fn flush_ansi(&mut self) {
+        if !self.ansi.is_empty() {
+            self.out.push_str(&decode_codepage(self.codepage, &self.ansi));
+            self.ansi.clear();
+        }
+    }
+
+    fn flush_units(&mut self) {
+        if !self.units.is_empty() {
+            self.out.push_str(&String::from_utf16_lossy(&self.units));
+            self.units.clear();
+        }
+    }
+
+    fn finish(mut self) -> String {
+        self.flush_ansi();
+        self.flush_units();
+        self.out
+    }
+}
+
+/// コードページのバイト列を文字列にする（変換できなければ Latin-1 とみなす）
+fn decode_codepage(codepage: u32, bytes: &[u8]) -> String {
+    unsafe {
+        let len = MultiByteToWideChar(codepage, MULTI_BYTE_TO_WIDE_CHAR_FLAGS(0), bytes, None);
+        if len > 0 {
+            let mut wide = vec![0u16; len as usize];
+            let written =
+                MultiByteToWideChar(codepage, MULTI_BYTE_TO_WIDE_CHAR_FLAGS(0), bytes, Some(&mut wide));
+            if written > 0 {
+                return String::from_utf16_lossy(&wide[..written as usize]);
+            }
+        }
+    }
+    bytes.iter().map(|&b| b as char).collect()
+}
+
+/// RTF の制御語を読み飛ばしてテキストだけを取り出す
+///
+/// `\par` / `\line` は改行、`\tab` はタブ、`\uN` は UTF-16 として扱い、直後の代替表現
+/// （`\ucN` 文字分、既定 1）は読み飛ばす。本文と `\'hh` は `\ansicpg` のコードページで読む。
+/// `{\*...}` と `RTF_SKIP_DESTINATIONS` で始まるグループは中身ごと捨てる。
+fn rtf_to_text(rtf: &[u8]) -> String {
+    let mut text = RtfText::new();
+    // グループごとの「出力しない」フラグ
+    let mut skip_stack = vec![false];
+    // `\uN` 直後の代替文字の数（`\ucN`）と、読み飛ばす残り数
+    let mut fallback_len = 1;
+    let mut pending_fallback = 0;
+    let mut group_start = false;
+    let mut i = 0;
+
+    while i < rtf.len() {
+        let skipping = *skip_stack.last().unwrap_or(&false);
+        let starts_group = std::mem::take(&mut group_start);
+        match rtf[i] {
+            b'{' => {
+                skip_stack.push(skipping);
+                group_start = true;
+                i += 1;
+            }
+            b'}' => {
+                if skip_stack.len() > 1 {
+                    skip_stack.pop();
+                }
+                pending_fallback = 0;
+                i += 1;
+            }
+            b'\\' => {
+                i += 1;
+                let Some(&next) = rtf.get(i) else { break };
+                if next.is_ascii_alphabetic() {
+                    let word_start = i;
+                    while i < rtf.len() && rtf[i].is_ascii_alphabetic() {
+                        i += 1;
+                    }
+                    let word = std::str::from_utf8(&rtf[word_start..i]).unwrap_or_default();
+                    let num_start = i;
+                    if i < rtf.len() && rtf[i] == b'-' {
+                        i += 1;
+                    }
+                    while i < rtf.len() && rtf[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                    let param: Option<i32> = std::str::from_utf8(&rtf[num_start..i])
+                        .ok()
+                        .and_then(|n| n.parse().ok());
+                    // 制御語の区切りの空白は本文に含めない
+                    if i < rtf.len() && rtf[i] == b' ' {
+                        i += 1;
+                    }
+
+                    if starts_group && RTF_SKIP_DESTINATIONS.contains(&word) {
+                        if let Some(top) = skip_stack.last_mut() {
+                            *top = true;
+                        }
+                        continue;
+                    }
+                    if skipping {
+                        continue;
+                    }
+                    match word {
+                        "par" | "line" => text.push_char('\n'),
+                        "tab" => text.push_char('\t'),
+                        "ansicpg" => {
+                            if let Some(cp) = param.and_then(|n| u32::try_from(n).ok()) {
+                                text.flush_ansi();
+                                text.codepage = cp;
+                            }
+                        }
+                        "uc" => {
+                            if let Some(n) = param {
+                                fallback_len = n.max(0);
+                            }
+                        }
+                        "u" => {
+                            if let Some(n) = param {
+                                // 負数は 16 bit を符号付きで書いたもの
+                                text.push_unit(n as i16 as u16);
+                                pending_fallback = fallback_len;
+                            }
+                        }
+                        _ => {}
+                    }
+                } else {
+                    i += 1;
+                    match next {
+                        b'*' if starts_group => {
+                            if let Some(top) = skip_stack.last_mut() {
+                                *top = true;
+                            }
+                        }
+                        b'\'' => {
+                            let hex = rtf.get(i..i + 2).and_then(|h| std::str::from_utf8(h).ok());
+                            i += 2;
+                            if let Some(byte) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                                if skipping {
+                                    continue;
+                                }
+                                if pending_fallback > 0 {
+                                    pending_fallback -= 1;
+                                } else {
+                                    text.push_ansi(byte);
+                                }
+                            }
+                        }
+                        b'\\' | b'{' | b'}' if !skipping => text.push_char(next as char),
+                        b'~' if !skipping => text.push_char(' '),
+                        _ => {}
+                    }
+                }
+            }
+            b'\r' | b'\n' => i += 1,
+            byte => {
+                i += 1;
+                if skipping {
+                    continue;
+                }
+                if pending_fallback > 0 {
+                    pending_fallback -= 1;
+                    continue;
+                }
+                text.push_ansi(byte);
+            }
+        }
+    }
+    text.finish()
+}
+
+/// CF_DIB / CF_DIBV5 のヘッダから画像サイズを取得
+unsafe fn read_dib_size() -> Option<(u32, u32)> {
+    let format = if is_format_available(CF_DIBV5.0 as u32) {
+        CF_DIBV5
+    } else {
+        CF_DIB
+    };
+    let handle = GetClipboardData(format.0 as u32).ok()?;
+    let hglobal = HGLOBAL(handle.0);
+    let size = GlobalSize(hglobal);
+    let ptr = GlobalLock(hglobal) as *const u8;
+    if ptr.is_null() {
+        return None;
+    }
+
+    // GlobalUnlock を確実に呼ぶため、クロージャで本体を実行
+    let result = (|| -> Option<(u32, u32)> {
+        if size < std::mem::size_of::<u32>() {
+            return None;
+        }
+        let header_size = std::ptr::read_unaligned(ptr as *const u32) as usize;
+        if header_size == std::mem::size_of::<BITMAPCOREHEADER>() {
+            if size < std::mem::size_of::<BITMAPCOREHEADER>() {
+                return None;
+            }
+            // OS/2 形式の古いDIB（幅・高さが u16）
+            let header = std::ptr::read_unaligned(ptr as *const BITMAPCOREHEADER);
+            return Some((header.bcWidth as u32, header.bcHeight as u32));
+        }
+        if header_size < std::mem::size_of::<BITMAPINFOHEADER>()
+            || size < std::mem::size_of::<BITMAPINFOHEADER>()
+        {
+            return None;
+        }
+        // BITMAPV4/V5HEADER も先頭は BITMAPINFOHEADER と同じレイアウト
+        let header = std::ptr::read_unaligned(ptr as *const BITMAPINFOHEADER);
+        if header.biWidth == 0 || header.biHeight == 0 {
+            return None;
+        }
+        // biHeight が負の場合はトップダウンDIB
+        Some((header.biWidth.unsigned_abs(), header.biHeight.unsigned_abs()))
+    })();
+
+    let _ = GlobalUnlock(hglobal);
+    result
+}
+
+/// CF_BITMAP (HBITMAP) から画像サイズを取得
+unsafe fn read_bitmap_size() -> Option<(u32, u32)> {
+    let handle = GetClipboardData(CF_BITMAP.0 as u32).ok()?;
+    let mut bitmap = BITMAP::default();
+    let written = GetObjectW(
+        HBITMAP(handle.0),
+        std::mem::size_of::<BITMAP>() as i32,
+        Some(&mut bitmap as *mut BITMAP as *mut _),
+    );
+    if written == 0 {
+        return None;
+    }
+    Some((bitmap.bmWidth.unsigned_abs(), bitmap.bmHeight.unsigned_abs()))
+}
+
 impl Drop for ClipboardListener {
     fn drop(&mut self) {
         unsafe {
@@ -75,3 +581,106 @@ impl Drop for ClipboardListener {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// StartFragment/EndFragment を実際の位置で埋めた CF_HTML を組み立てる
+    fn cf_html(fragment: &str) -> String {
+        let template = "Version:0.9\r\nStartFragment:{start}\r\nEndFragment:{end}\r\n\
+             <html><body><!--StartFragment-->{fragment}<!--EndFragment--></body></html>";
+        let placeholder = template
+            .replace("{start}", "0000000000")
+            .replace("{end}", "0000000000")
+            .replace("{fragment}", fragment);
+        let start = placeholder.find("<!--StartFragment-->").unwrap() + "<!--StartFragment-->".len();
+        let end = start + fragment.len();
+        template
+            .replace("{start}", &format!("{start:010}"))
+            .replace("{end}", &format!("{end:010}"))
+            .replace("{fragment}", fragment)
+    }
+
+    #[test]
+    fn html_fragment_uses_header_offsets() {
+        let raw = cf_html("<b>\u{3042}\u{3044}</b>");
+        assert_eq!(html_fragment(&raw), "<b>\u{3042}\u{3044}</b>");
+    }
+
+    #[test]
+    fn html_fragment_falls_back_to_whole_input_on_bad_offsets() {
+        // 範囲外
+        let raw = "StartFragment:0000000010\r\nEndFragment:0000009999\r\n<b>x</b>";
+        assert_eq!(html_fragment(raw), raw);
+        // 開始が終了より後
+        let raw = "StartFragment:0000000020\r\nEndFragment:0000000010\r\n<b>x</b>";
+        assert_eq!(html_fragment(raw), raw);
+        // 多バイト文字の途中
+        let raw = cf_html("\u{3042}");
+        let start = raw.find('\u{3042}').unwrap();
+        let broken = raw.replacen(
+            &format!("StartFragment:{start:010}"),
+            &format!("StartFragment:{:010}", start + 1),
+            1,
+        );
+        assert_eq!(html_fragment(&broken), broken);
+        // ヘッダなし
+        assert_eq!(html_fragment("<p>plain</p>"), "<p>plain</p>");
+    }
+
+    #[test]
+    fn html_to_text_spaces_only_block_tags() {
+        assert_eq!(html_to_text("foo<b>bar</b>"), "foobar");
+        assert_eq!(html_to_text("<p>one</p><p>two<br>three</p>"), "one two three ");
+        assert_eq!(html_to_text("<ul><li>a</li><li>b</li></ul>"), "a b ");
+        assert_eq!(html_to_text("<DIV class=\"x\">up</DIV>case"), "up case");
+    }
+
+    #[test]
+    fn html_to_text_skips_script_style_and_comments() {
+        let html = "a<script>var s = '<b>x</b>';</script>b<STYLE>p { color: red }</STYLE>c<!-- <p>note</p> -->d";
+        assert_eq!(html_to_text(html), "abcd");
+        // 閉じていないものは末尾まで捨てる
+        assert_eq!(html_to_text("a<!-- open"), "a");
+        assert_eq!(html_to_text("a<script>x"), "a");
+        assert_eq!(html_to_text("a<b"), "a");
+    }
+
+    #[test]
+    fn html_to_text_decodes_entities() {
+        assert_eq!(
+            html_to_text("&lt;tag&gt; &amp;amp; &quot;q&quot; it&#39;s&nbsp;ok"),
+            "<tag> &amp; \"q\" it's ok"
+        );
+    }
+
+    #[test]
+    fn rtf_par_tab_and_escapes() {
+        let rtf = br"{\rtf1\ansi Hello\par World\tab end \{x\}\\\~!}";
+        assert_eq!(rtf_to_text(rtf), "Hello\nWorld\tend {x}\\ !");
+    }
+
+    #[test]
+    fn rtf_unicode_skips_fallback() {
+        // 既定では代替表現 1 文字、\uc2 なら 2 文字分を読み飛ばす
+        let rtf = br"{\rtf1\ansi \u12354?\u-3?b{\uc2\u12356\'82\'a2c}}";
+        assert_eq!(rtf_to_text(rtf), "\u{3042}\u{fffd}b\u{3044}c");
+    }
+
+    #[test]
+    fn rtf_hex_bytes_use_ansi_codepage() {
+        let latin = br"{\rtf1\ansi caf\'e9}";
+        assert_eq!(rtf_to_text(latin), "caf\u{e9}");
+
+        // Shift_JIS の 2 バイト文字は連続した \'hh をまとめて変換する
+        let sjis = br"{\rtf1\ansi\ansicpg932 \'82\'a0\'82\'a2}";
+        assert_eq!(rtf_to_text(sjis), "\u{3042}\u{3044}");
+    }
+
+    #[test]
+    fn rtf_skips_destination_groups() {
+        let rtf = br"{\rtf1\ansi{\fonttbl{\f0 Arial;}}{\colortbl;\red0;}{\*\generator Foo;}{\*\unknown skip}Body}";
+        assert_eq!(rtf_to_text(rtf), "Body");
+    }
+}
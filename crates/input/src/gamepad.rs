@@ -0,0 +1,141 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::SyncSender;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use windows::Win32::Foundation::ERROR_SUCCESS;
+use windows::Win32::UI::Input::XboxController::*;
+
+use ystrokey_core::{GamepadButton, GamepadEvent, InputEvent, KeyAction};
+
+/// XInput が扱えるコントローラー数
+const MAX_CONTROLLERS: u32 = 4;
+
+/// 未接続スロットの再確認間隔（未接続での XInputGetState は重いため間引く）
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(1);
+
+const BUTTON_MAP: [(XINPUT_GAMEPAD_BUTTON_FLAGS, GamepadButton); 14] = [
+    (XINPUT_GAMEPAD_A, GamepadButton::A),
+    (XINPUT_GAMEPAD_B, GamepadButton::B),
+    (XINPUT_GAMEPAD_X, GamepadButton::X),
+    (XINPUT_GAMEPAD_Y, GamepadButton::Y),
+    (XINPUT_GAMEPAD_LEFT_SHOULDER, GamepadButton::LeftShoulder),
+    (XINPUT_GAMEPAD_RIGHT_SHOULDER, GamepadButton::RightShoulder),
+    (XINPUT_GAMEPAD_DPAD_UP, GamepadButton::DPadUp),
+    (XINPUT_GAMEPAD_DPAD_DOWN, GamepadButton::DPadDown),
+    (XINPUT_GAMEPAD_DPAD_LEFT, GamepadButton::DPadLeft),
+    (XINPUT_GAMEPAD_DPAD_RIGHT, GamepadButton::DPadRight),
+    (XINPUT_GAMEPAD_START, GamepadButton::Start),
+    (XINPUT_GAMEPAD_BACK, GamepadButton::Back),
+    (XINPUT_GAMEPAD_LEFT_THUMB, GamepadButton::LeftThumb),
+    (XINPUT_GAMEPAD_RIGHT_THUMB, GamepadButton::RightThumb),
+];
+
+/// コントローラー1台分の前回状態
+#[derive(Default)]
+struct ControllerSlot {
+    connected: bool,
+    last_packet: u32,
+    pressed: Vec<GamepadButton>,
+    next_probe: Option<Instant>,
+}
+
+/// ポーリングスレッドの停止用ハンドル（Drop でスレッドを止める）
+pub struct GamepadPoller {
+    stop: Arc<AtomicBool>,
+}
+
+impl Drop for GamepadPoller {
+    fn drop(&mut self) {
+        // スレッドは次のポーリング周期で終了する
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// XInput のボタン状態をポーリングするスレッドを起動
+pub fn install_gamepad_poller(tx: SyncSender<InputEvent>, interval: Duration) -> GamepadPoller {
+    let stop = Arc::new(AtomicBool::new(false));
+    let stopped = Arc::clone(&stop);
+    let spawned = std::thread::Builder::new()
+        .name("gamepad-poller".into())
+        .spawn(move || {
+            let mut slots: Vec<ControllerSlot> =
+                (0..MAX_CONTROLLERS).map(|_| ControllerSlot::default()).collect();
+            while !stopped.load(Ordering::Relaxed) {
+                for (index, slot) in slots.iter_mut().enumerate() {
+                    if !poll_controller(index as u32, slot, &tx) {
+                        return;
+                    }
+                }
+                std::thread::sleep(interval);
+            }
+        });
+    if let Err(e) = spawned {
+        eprintln!("gamepad poller thread spawn failed: {e}");
+    }
+    GamepadPoller { stop }
+}
+
+/// 1台分をポーリングしてボタン変化を送信（受信側が閉じたら false）
+fn poll_controller(index: u32, slot: &mut ControllerSlot, tx: &SyncSender<InputEvent>) -> bool {
+    let now = Instant::now();
+    if !slot.connected && slot.next_probe.is_some_and(|t| now < t) {
+        return true;
+    }
+
+    let mut state = XINPUT_STATE::default();
+    if unsafe { XInputGetState(index, &mut state) } != ERROR_SUCCESS.0 {
+        *slot = ControllerSlot {
+            next_probe: Some(now + RECONNECT_INTERVAL),
+            ..ControllerSlot::default()
+        };
+        return true;
+    }
+    slot.connected = true;
+    if state.dwPacketNumber == slot.last_packet {
+        return true;
+    }
+    slot.last_packet = state.dwPacketNumber;
+
+    let pressed = pressed_buttons(&state.Gamepad);
+    let changes = pressed
+        .iter()
+        .filter(|b| !slot.pressed.contains(b))
+        .map(|b| (*b, KeyAction::Down))
+        .chain(
+            slot.pressed
+                .iter()
+                .filter(|b| !pressed.contains(b))
+                .map(|b| (*b, KeyAction::Up)),
+        );
+    for (button, action) in changes {
+        let event = InputEvent::Gamepad(GamepadEvent {
+            controller_id: index as u8,
+            button,
+            action,
+            timestamp: now,
+        });
+        // バッファフルなら破棄（キー入力と同じ方針）
+        if let Err(std::sync::mpsc::TrySendError::Disconnected(_)) = tx.try_send(event) {
+            return false;
+        }
+    }
+    slot.pressed = pressed;
+    true
+}
+
+fn pressed_buttons(pad: &XINPUT_GAMEPAD) -> Vec<GamepadButton> {
+    let threshold = XINPUT_GAMEPAD_TRIGGER_THRESHOLD.0 as u8;
+    let mut pressed: Vec<GamepadButton> = BUTTON_MAP
+        .iter()
+        .filter(|(flag, _)| pad.wButtons.0 & flag.0 != 0)
+        .map(|(_, button)| *button)
+        .collect();
+    if pad.bLeftTrigger > threshold {
+        pressed.push(GamepadButton::LeftTrigger);
+    }
+    if pad.bRightTrigger > threshold {
+        pressed.push(GamepadButton::RightTrigger);
+    }
+    pressed
+}
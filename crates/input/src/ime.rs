@@ -6,11 +6,13 @@ use windows::Win32::Foundation::HWND;
 use windows::Win32::System::Threading::{AttachThreadInput, GetCurrentThreadId};
 use windows::Win32::UI::Input::KeyboardAndMouse::GetFocus;
 use windows::Win32::UI::Input::Ime::{
-    GCS_COMPSTR, GCS_RESULTSTR, ImmGetCompositionStringW, ImmGetContext, ImmGetOpenStatus,
-    ImmReleaseContext,
+    CANDIDATELIST, GCS_COMPSTR, GCS_RESULTSTR, IME_CMODE_KATAKANA, IME_CMODE_NATIVE,
+    IME_CONVERSION_MODE, ImmGetCandidateListW, ImmGetCompositionStringW, ImmGetContext,
+    ImmGetConversionStatus, ImmGetDefaultIMEWnd, ImmGetOpenStatus, ImmReleaseContext,
 };
 use windows::Win32::UI::WindowsAndMessaging::{
-    GetForegroundWindow, GetGUIThreadInfo, GetWindowThreadProcessId, GUITHREADINFO,
+    GetForegroundWindow, GetGUIThreadInfo, GetWindowThreadProcessId, SendMessageTimeoutW,
+    GUITHREADINFO, SMTO_ABORTIFHUNG, WM_IME_CONTROL,
 };
 
 use ystrokey_core::{ImeEvent, ImeEventKind, InputEvent};
@@ -179,6 +181,77 @@ pub fn get_result_string(hwnd: HWND) -> Option<String> {
     }
 }
 
+/// IME候補一覧の全候補を取得（候補一覧が開いていなければ空）
+pub fn get_candidate_list(hwnd: HWND) -> Vec<String> {
+    read_candidate_list(hwnd)
+        .map(|(entries, _)| entries)
+        .unwrap_or_default()
+}
+
+/// IME候補一覧で選択中の候補を取得（候補一覧が開いていなければ None）
+pub fn get_selected_candidate(hwnd: HWND) -> Option<String> {
+    let (mut entries, selected) = read_candidate_list(hwnd)?;
+    (selected < entries.len()).then(|| entries.swap_remove(selected))
+}
+
+/// 候補一覧と選択中の位置を読み出す
+fn read_candidate_list(hwnd: HWND) -> Option<(Vec<String>, usize)> {
+    unsafe {
+        let _attach = InputAttachGuard::maybe_attach(hwnd);
+        let himc = ImmGetContext(hwnd);
+        if himc.is_invalid() {
+            return None;
+        }
+
+        let byte_len = ImmGetCandidateListW(himc, 0, None, 0) as usize;
+        if byte_len < std::mem::size_of::<CANDIDATELIST>() {
+            let _ = ImmReleaseContext(hwnd, himc);
+            return None;
+        }
+
+        // CANDIDATELIST は u32 境界に揃える必要があるので u32 単位で確保する
+        let mut buf: Vec<u32> = vec![0u32; byte_len.div_ceil(4)];
+        let copied = ImmGetCandidateListW(
+            himc,
+            0,
+            Some(buf.as_mut_ptr() as *mut CANDIDATELIST),
+            byte_len as u32,
+        ) as usize;
+
+        let _ = ImmReleaseContext(hwnd, himc);
+
+        if copied < std::mem::size_of::<CANDIDATELIST>() {
+            return None;
+        }
+        let list = &*(buf.as_ptr() as *const CANDIDATELIST);
+        let count = list.dwCount as usize;
+        let offsets_end = std::mem::offset_of!(CANDIDATELIST, dwOffset) + count * 4;
+        if count == 0 || offsets_end > copied {
+            return None;
+        }
+
+        // dwOffset は構造体先頭からのバイトオフセットで、候補数ぶん並んでいる
+        let offsets = std::slice::from_raw_parts(list.dwOffset.as_ptr(), count);
+        let bytes = std::slice::from_raw_parts(buf.as_ptr() as *const u8, copied);
+        let entries = offsets
+            .iter()
+            .map(|&offset| {
+                let start = offset as usize;
+                if start >= copied || !start.is_multiple_of(2) {
+                    return String::new();
+                }
+                let words = std::slice::from_raw_parts(
+                    bytes.as_ptr().add(start) as *const u16,
+                    (copied - start) / 2,
+                );
+                let len = words.iter().position(|&c| c == 0).unwrap_or(words.len());
+                String::from_utf16_lossy(&words[..len])
+            })
+            .collect();
+        Some((entries, list.dwSelection as usize))
+    }
+}
+
 /// IME ON/OFF状態を取得
 pub fn is_ime_open(hwnd: HWND) -> bool {
     unsafe {
@@ -193,6 +266,55 @@ pub fn is_ime_open(hwnd: HWND) -> bool {
     }
 }
 
+/// IMEが全角カタカナ入力モードかを取得
+pub fn is_katakana_ime_mode(hwnd: HWND) -> bool {
+    unsafe {
+        let _attach = InputAttachGuard::maybe_attach(hwnd);
+        let himc = ImmGetContext(hwnd);
+        if himc.is_invalid() {
+            return false;
+        }
+        let mut conversion = IME_CONVERSION_MODE::default();
+        let ok = ImmGetConversionStatus(himc, Some(&mut conversion), None).as_bool();
+        let _ = ImmReleaseContext(hwnd, himc);
+        ok && conversion.contains(IME_CMODE_NATIVE | IME_CMODE_KATAKANA)
+    }
+}
+
+/// WM_IME_CONTROL のサブコマンド（windows クレートに定義がない）
+const IMC_GETCONVERSIONMODE: usize = 0x0001;
+const IMC_GETOPENSTATUS: usize = 0x0005;
+/// 応答しないアプリで待たされないための上限
+const IME_CONTROL_TIMEOUT_MS: u32 = 100;
+
+/// 既定 IME ウィンドウへ問い合わせて (ON/OFF, 全角カタカナか) を取得
+///
+/// WM_IME_CONTROL は別スレッドの IME 状態をスレッドアタッチなしで読める。
+/// 応答がなければ `None`。
+fn query_ime_status(ime_wnd: HWND) -> Option<(bool, bool)> {
+    let send = |command: usize| -> Option<usize> {
+        let mut result = 0usize;
+        let ok = unsafe {
+            SendMessageTimeoutW(
+                ime_wnd,
+                WM_IME_CONTROL,
+                windows::Win32::Foundation::WPARAM(command),
+                windows::Win32::Foundation::LPARAM(0),
+                SMTO_ABORTIFHUNG,
+                IME_CONTROL_TIMEOUT_MS,
+                Some(&mut result),
+            )
+        };
+        (ok.0 != 0).then_some(result)
+    };
+    let open = send(IMC_GETOPENSTATUS)? != 0;
+    if !open {
+        return Some((false, false));
+    }
+    let conversion = IME_CONVERSION_MODE(send(IMC_GETCONVERSIONMODE)? as u32);
+    Some((true, conversion.contains(IME_CMODE_NATIVE | IME_CMODE_KATAKANA)))
+}
+
 /// IME状態をポーリングしてイベントを送信
 ///
 /// フォアグラウンドウィンドウのIME状態と変換中文字列をチェックし、
@@ -201,6 +323,12 @@ pub fn poll_ime_state(tx: &SyncSender<InputEvent>) {
     thread_local! {
         static PREV_IME_OPEN: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
         static PREV_COMPOSITION: std::cell::RefCell<String> = const { std::cell::RefCell::new(String::new()) };
+        static PREV_CANDIDATE: std::cell::RefCell<String> = const { std::cell::RefCell::new(String::new()) };
+        static PREV_CANDIDATE_LIST: std::cell::RefCell<Vec<String>> = const { std::cell::RefCell::new(Vec::new()) };
+        static LAST_FOREGROUND_TID: std::cell::Cell<u32> = const { std::cell::Cell::new(0) };
+        static PREV_KATAKANA: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+        /// フォアグラウンドスレッドの既定 IME ウィンドウ（スレッドが変わるまで使い回す）
+        static IME_WINDOW: std::cell::Cell<Option<HWND>> = const { std::cell::Cell::new(None) };
     }
 
     let targets = collect_ime_targets();
@@ -208,23 +336,52 @@ pub fn poll_ime_state(tx: &SyncSender<InputEvent>) {
         return;
     }
 
-    // IME ON/OFF状態チェック
-    let mut comp = String::new();
-    let mut ime_open = false;
-
-    for hwnd in &targets {
-        ime_open |= is_ime_open(*hwnd);
+    let fg_tid = unsafe { GetWindowThreadProcessId(GetForegroundWindow(), None) };
+    let focus_changed = LAST_FOREGROUND_TID.with(|c| c.replace(fg_tid)) != fg_tid;
+    let prev_open = PREV_IME_OPEN.with(|c| c.get());
+    let prev_katakana = PREV_KATAKANA.with(|c| c.get());
 
-        if comp.is_empty() {
+    // IME ON/OFF状態チェック（既定 IME ウィンドウ経由なのでスレッドアタッチしない）
+    if focus_changed {
+        IME_WINDOW.with(|c| c.set(None));
+    }
+    let ime_wnd = IME_WINDOW.with(|c| {
+        c.get().unwrap_or_else(|| {
+            let wnd = unsafe { ImmGetDefaultIMEWnd(targets[0]) };
+            c.set(Some(wnd));
+            wnd
+        })
+    });
+    let (ime_open, katakana) = if ime_wnd.is_invalid() {
+        // IME を使わないスレッド
+        (false, false)
+    } else {
+        query_ime_status(ime_wnd).unwrap_or_else(|| {
+            // 応答がない・ウィンドウが消えた場合は前回の状態のまま、次回取り直す
+            IME_WINDOW.with(|c| c.set(None));
+            (prev_open, prev_katakana)
+        })
+    };
+
+    // 同じウィンドウで IME が閉じたままなら、スレッドアタッチを伴う変換中文字列の取得を省く
+    let mut comp = String::new();
+    let mut candidate = None;
+    let mut candidate_list = Vec::new();
+    if focus_changed || prev_open || ime_open {
+        for hwnd in &targets {
             if let Some(s) = get_composition_string(*hwnd) {
                 if !s.is_empty() {
                     comp = s;
+                    if let Some((entries, selected)) = read_candidate_list(*hwnd) {
+                        candidate = entries.get(selected).cloned();
+                        candidate_list = entries;
+                    }
+                    break;
                 }
             }
         }
     }
 
-    let prev_open = PREV_IME_OPEN.with(|c| c.get());
     if ime_open != prev_open {
         PREV_IME_OPEN.with(|c| c.set(ime_open));
         let event = InputEvent::Ime(ImeEvent {
@@ -234,6 +391,14 @@ pub fn poll_ime_state(tx: &SyncSender<InputEvent>) {
         let _ = tx.try_send(event);
     }
 
+    if katakana != prev_katakana {
+        PREV_KATAKANA.with(|c| c.set(katakana));
+        let _ = tx.try_send(InputEvent::Ime(ImeEvent {
+            kind: ImeEventKind::ConversionModeChanged { katakana },
+            timestamp: Instant::now(),
+        }));
+    }
+
     // 変換中文字列チェック（IME ON/OFF判定に依存せず文字列変化で更新）
     let changed = PREV_COMPOSITION.with(|c| {
         let prev = c.borrow();
@@ -241,7 +406,12 @@ pub fn poll_ime_state(tx: &SyncSender<InputEvent>) {
     });
     if changed {
         let kind = if comp.is_empty() {
-            ImeEventKind::CompositionEnd { result: String::new() }
+            // 変換が終わった直後なら確定文字列がまだ取れる（キャンセル時は空）
+            let result = targets
+                .iter()
+                .find_map(|hwnd| get_result_string(*hwnd).filter(|s| !s.is_empty()))
+                .unwrap_or_default();
+            ImeEventKind::CompositionEnd { result }
         } else {
             ImeEventKind::CompositionUpdate { text: comp.clone() }
         };
@@ -253,4 +423,28 @@ pub fn poll_ime_state(tx: &SyncSender<InputEvent>) {
     PREV_COMPOSITION.with(|c| {
         *c.borrow_mut() = comp;
     });
+
+    // 候補一覧チェック（閉じたときは空の一覧を送って表示を消す）
+    let changed = PREV_CANDIDATE_LIST.with(|c| candidate_list != *c.borrow());
+    if changed {
+        let _ = tx.try_send(InputEvent::ImeCandidates(candidate_list.clone()));
+    }
+    PREV_CANDIDATE_LIST.with(|c| {
+        *c.borrow_mut() = candidate_list;
+    });
+
+    // 選択中の候補チェック（候補一覧がない間は何も送らない）
+    let candidate = candidate.unwrap_or_default();
+    let changed = PREV_CANDIDATE.with(|c| candidate != *c.borrow());
+    if changed && !candidate.is_empty() {
+        let _ = tx.try_send(InputEvent::Ime(ImeEvent {
+            kind: ImeEventKind::CandidateUpdate {
+                text: candidate.clone(),
+            },
+            timestamp: Instant::now(),
+        }));
+    }
+    PREV_CANDIDATE.with(|c| {
+        *c.borrow_mut() = candidate;
+    });
 }
@@ -1,4 +1,5 @@
-use std::sync::mpsc::SyncSender;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{SyncSender, TrySendError};
 use std::thread::JoinHandle;
 use std::time::Instant;
 
@@ -16,8 +17,28 @@ use ystrokey_core::{InputEvent, KeyAction, KeyCode, KeyEvent, LockStateEvent, Mo
 thread_local! {
     static HOOK_SENDER: std::cell::RefCell<Option<SyncSender<InputEvent>>> =
         const { std::cell::RefCell::new(None) };
+    /// AltGr 押下時に OS が合成する左Ctrl が押されているか
+    static SYNTHETIC_LCTRL_DOWN: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
 }
 
+/// バッファフルで破棄した入力イベント数（フックスレッド共通）
+static DROPPED_EVENTS: AtomicU64 = AtomicU64::new(0);
+
+/// フックからの try_send 結果を集計（バッファフルのみ数える）
+pub(crate) fn count_dropped<T>(result: Result<(), TrySendError<T>>) {
+    if let Err(TrySendError::Full(_)) = result {
+        DROPPED_EVENTS.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// 前回呼び出し以降に破棄された入力イベント数を取得してリセット
+pub fn take_dropped_event_count() -> u64 {
+    DROPPED_EVENTS.swap(0, Ordering::Relaxed)
+}
+
+/// AltGr が合成する左Ctrl のスキャンコード（0x1D に 0x200 が付く）
+const ALTGR_LCTRL_SCAN_FLAG: u32 = 0x200;
+
 /// KBDLLHOOKSTRUCT からテンキーを区別して KeyCode に変換
 fn to_key_code(kb: &KBDLLHOOKSTRUCT) -> KeyCode {
     let vk = kb.vkCode;
@@ -59,7 +80,7 @@ fn numpad_scan_to_key(scan: u32) -> KeyCode {
 /// GetAsyncKeyState で現在の修飾キー状態を取得
 fn get_current_modifiers() -> Modifiers {
     unsafe {
-        Modifiers {
+        let mut modifiers = Modifiers {
             ctrl: GetAsyncKeyState(VK_LCONTROL.0 as i32) < 0
                 || GetAsyncKeyState(VK_RCONTROL.0 as i32) < 0,
             shift: GetAsyncKeyState(VK_LSHIFT.0 as i32) < 0
@@ -68,7 +89,16 @@ fn get_current_modifiers() -> Modifiers {
                 || GetAsyncKeyState(VK_RMENU.0 as i32) < 0,
             win: GetAsyncKeyState(VK_LWIN.0 as i32) < 0
                 || GetAsyncKeyState(VK_RWIN.0 as i32) < 0,
+            altgr: false,
+        };
+
+        // AltGr: 右Alt + 合成左Ctrl。Ctrl+Alt ではなく AltGr として扱う
+        if GetAsyncKeyState(VK_RMENU.0 as i32) < 0 && SYNTHETIC_LCTRL_DOWN.with(|c| c.get()) {
+            modifiers.altgr = true;
+            modifiers.ctrl = GetAsyncKeyState(VK_RCONTROL.0 as i32) < 0;
+            modifiers.alt = GetAsyncKeyState(VK_LMENU.0 as i32) < 0;
         }
+        modifiers
     }
 }
 
@@ -151,7 +181,7 @@ fn is_lock_key(vk: u32) -> bool {
 }
 
 /// Get current lock state
-fn get_lock_state_event() -> LockStateEvent {
+pub fn get_lock_state_event() -> LockStateEvent {
     unsafe {
         LockStateEvent {
             caps_lock: (GetKeyState(VK_CAPITAL.0 as i32) & 1) != 0,
@@ -176,6 +206,11 @@ unsafe extern "system" fn keyboard_hook_proc(
             _ => return CallNextHookEx(None, code, wparam, lparam),
         };
 
+        if kb.vkCode == VK_LCONTROL.0 as u32 {
+            let synthetic = kb.scanCode & ALTGR_LCTRL_SCAN_FLAG != 0;
+            SYNTHETIC_LCTRL_DOWN.with(|c| c.set(synthetic && action == KeyAction::Down));
+        }
+
         let key_code = to_key_code(kb);
         let modifiers = get_current_modifiers();
         // Down時のみ実文字を解決（Up は不要、デッドキー副作用を避ける意味でも限定）
@@ -198,11 +233,11 @@ unsafe extern "system" fn keyboard_hook_proc(
         // try_send: バッファフルなら破棄（フックコールバックはブロック不可）
         HOOK_SENDER.with(|cell| {
             if let Some(ref tx) = *cell.borrow() {
-                let _ = tx.try_send(event);
+                count_dropped(tx.try_send(event));
                 // Lock key: send toggle state on WM_KEYUP
                 if action == KeyAction::Up && is_lock_key(kb.vkCode) {
                     let lock_event = InputEvent::LockState(get_lock_state_event());
-                    let _ = tx.try_send(lock_event);
+                    count_dropped(tx.try_send(lock_event));
                 }
             }
         });
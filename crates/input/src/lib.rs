@@ -1,9 +1,21 @@
 pub mod clipboard;
+pub mod gamepad;
 pub mod ime;
 pub mod keyboard;
+pub mod mouse;
 pub mod privacy;
+pub mod raw_input;
 
-pub use clipboard::ClipboardListener;
-pub use ime::{get_composition_string, get_result_string, is_ime_open, poll_ime_state};
-pub use keyboard::{install_keyboard_hook, run_hook_thread};
-pub use privacy::is_privacy_target;
+pub use clipboard::{get_clipboard_owner_name, ClipboardListener};
+pub use gamepad::{install_gamepad_poller, GamepadPoller};
+pub use ime::{
+    get_candidate_list, get_composition_string, get_result_string, is_ime_open, is_katakana_ime_mode,
+    poll_ime_state,
+};
+pub use keyboard::{get_lock_state_event, install_keyboard_hook, run_hook_thread, take_dropped_event_count};
+pub use mouse::install_mouse_hook;
+pub use privacy::{
+    get_foreground_process_name, is_privacy_target, is_privacy_target_by_title,
+    take_foreground_changed, ForegroundHook,
+};
+pub use raw_input::{read_raw_mouse_events, register_raw_mouse};
@@ -0,0 +1,99 @@
+use std::sync::mpsc::SyncSender;
+use std::thread::JoinHandle;
+use std::time::Instant;
+
+use windows::Win32::Foundation::*;
+use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::UI::WindowsAndMessaging::*;
+
+use ystrokey_core::{InputEvent, MouseAction, MouseButton, MouseEvent, MouseSource};
+
+thread_local! {
+    static MOUSE_SENDER: std::cell::RefCell<Option<SyncSender<InputEvent>>> =
+        const { std::cell::RefCell::new(None) };
+}
+
+/// ボタン・ホイールのメッセージを MouseEvent のボタン/アクションへ変換
+///
+/// `mouse_data` は MSLLHOOKSTRUCT.mouseData（上位ワードに XBUTTON 番号 / ホイール量）。
+fn to_mouse_input(msg: u32, mouse_data: u32) -> Option<(MouseButton, MouseAction)> {
+    let high_word = (mouse_data >> 16) as u16;
+    let xbutton = || match high_word {
+        1 => Some(MouseButton::X1),
+        2 => Some(MouseButton::X2),
+        _ => None,
+    };
+    match msg {
+        WM_LBUTTONDOWN => Some((MouseButton::Left, MouseAction::Down)),
+        WM_LBUTTONUP => Some((MouseButton::Left, MouseAction::Up)),
+        WM_RBUTTONDOWN => Some((MouseButton::Right, MouseAction::Down)),
+        WM_RBUTTONUP => Some((MouseButton::Right, MouseAction::Up)),
+        WM_MBUTTONDOWN => Some((MouseButton::Middle, MouseAction::Down)),
+        WM_MBUTTONUP => Some((MouseButton::Middle, MouseAction::Up)),
+        WM_XBUTTONDOWN => xbutton().map(|b| (b, MouseAction::Down)),
+        WM_XBUTTONUP => xbutton().map(|b| (b, MouseAction::Up)),
+        WM_MOUSEWHEEL => Some((MouseButton::Middle, MouseAction::Wheel(high_word as i16))),
+        _ => None,
+    }
+}
+
+/// WH_MOUSE_LL フックコールバック
+unsafe extern "system" fn mouse_hook_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    // 移動は表示しないので変換処理自体を省く
+    if code >= 0 && wparam.0 as u32 != WM_MOUSEMOVE {
+        let ms = &*(lparam.0 as *const MSLLHOOKSTRUCT);
+        if let Some((button, action)) = to_mouse_input(wparam.0 as u32, ms.mouseData) {
+            let event = InputEvent::Mouse(MouseEvent {
+                button,
+                action,
+                position: (ms.pt.x, ms.pt.y),
+                source: MouseSource::Hook,
+                timestamp: Instant::now(),
+            });
+            // try_send: バッファフルなら破棄（フックコールバックはブロック不可）
+            MOUSE_SENDER.with(|cell| {
+                if let Some(ref tx) = *cell.borrow() {
+                    crate::keyboard::count_dropped(tx.try_send(event));
+                }
+            });
+        }
+    }
+    CallNextHookEx(None, code, wparam, lparam)
+}
+
+fn run_mouse_hook_thread(tx: SyncSender<InputEvent>) {
+    MOUSE_SENDER.with(|cell| {
+        cell.replace(Some(tx));
+    });
+
+    unsafe {
+        let hmod = GetModuleHandleW(None).ok().map(|h| HINSTANCE(h.0));
+        let hook = match SetWindowsHookExW(WH_MOUSE_LL, Some(mouse_hook_proc), hmod.unwrap_or_default(), 0) {
+            Ok(hook) => hook,
+            Err(e) => {
+                eprintln!("mouse hook install failed: {e}");
+                return;
+            }
+        };
+
+        // LL hookはメッセージループが必須
+        let mut msg = MSG::default();
+        while GetMessageW(&mut msg, None, 0, 0).as_bool() {
+            let _ = TranslateMessage(&msg);
+            DispatchMessageW(&msg);
+        }
+
+        let _ = UnhookWindowsHookEx(hook);
+    }
+}
+
+/// マウスフックを別スレッドで起動するヘルパー
+pub fn install_mouse_hook(tx: SyncSender<InputEvent>) -> JoinHandle<()> {
+    std::thread::Builder::new()
+        .name("mouse-hook".into())
+        .spawn(move || run_mouse_hook_thread(tx))
+        .unwrap_or_else(|e| {
+            eprintln!("mouse hook thread spawn failed: {e}");
+            std::thread::spawn(|| {})
+        })
+}
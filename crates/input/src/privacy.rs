@@ -1,13 +1,114 @@
-use windows::Win32::Foundation::CloseHandle;
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use windows::Win32::Foundation::{CloseHandle, HWND};
 use windows::Win32::System::Threading::*;
-use windows::Win32::UI::WindowsAndMessaging::GetForegroundWindow;
+use windows::Win32::UI::Accessibility::{SetWinEventHook, UnhookWinEvent, HWINEVENTHOOK};
+use windows::Win32::UI::WindowsAndMessaging::{
+    GetForegroundWindow, GetWindowTextLengthW, GetWindowTextW, EVENT_SYSTEM_FOREGROUND,
+    WINEVENT_OUTOFCONTEXT,
+};
+
+use ystrokey_core::config::{PrivacyConfig, PrivacyMode};
+
+/// フォアグラウンド変更通知フラグ（WinEvent コールバックでセット）
+static FOREGROUND_CHANGED: AtomicBool = AtomicBool::new(false);
 
-use ystrokey_core::config::PrivacyConfig;
+/// `EVENT_SYSTEM_FOREGROUND` の WinEvent フック
+///
+/// コールバックはフックを登録したスレッドのメッセージループ上で呼ばれる。
+/// Drop時に `UnhookWinEvent` で解除。
+pub struct ForegroundHook {
+    hook: HWINEVENTHOOK,
+}
+
+impl ForegroundHook {
+    /// フォアグラウンド変更の監視を開始（失敗時は None、呼び出し側はポーリングで代替）
+    pub fn install() -> Option<Self> {
+        let hook = unsafe {
+            SetWinEventHook(
+                EVENT_SYSTEM_FOREGROUND,
+                EVENT_SYSTEM_FOREGROUND,
+                None,
+                Some(foreground_event_proc),
+                0,
+                0,
+                WINEVENT_OUTOFCONTEXT,
+            )
+        };
+        if hook.is_invalid() {
+            None
+        } else {
+            Some(Self { hook })
+        }
+    }
+}
+
+impl Drop for ForegroundHook {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = UnhookWinEvent(self.hook);
+        }
+    }
+}
+
+unsafe extern "system" fn foreground_event_proc(
+    _hook: HWINEVENTHOOK,
+    _event: u32,
+    _hwnd: HWND,
+    _id_object: i32,
+    _id_child: i32,
+    _event_thread: u32,
+    _event_time: u32,
+) {
+    FOREGROUND_CHANGED.store(true, Ordering::Relaxed);
+}
+
+/// 前回呼び出し以降にフォアグラウンドが変わったかを返し、フラグをリセット
+pub fn take_foreground_changed() -> bool {
+    FOREGROUND_CHANGED.swap(false, Ordering::Relaxed)
+}
+
+/// 直近に調べたフォアグラウンドウィンドウとそのプロセス名
+///
+/// IME ポーリングやアプリ別キーフィルタで毎回プロセスを開かないよう、
+/// フォアグラウンドの HWND が変わるまで使い回す。
+struct ProcessNameCache {
+    hwnd: HWND,
+    name: Option<String>,
+}
+
+thread_local! {
+    static PROCESS_NAME_CACHE: RefCell<Option<ProcessNameCache>> = const { RefCell::new(None) };
+}
 
 /// Get the exe name of the foreground window process
+///
+/// フォアグラウンドの HWND が前回と同じならキャッシュを返す。
 pub fn get_foreground_process_name() -> Option<String> {
+    let hwnd = unsafe { GetForegroundWindow() };
+    if let Some(name) = PROCESS_NAME_CACHE.with(|cache| {
+        cache
+            .borrow()
+            .as_ref()
+            .filter(|cached| cached.hwnd == hwnd)
+            .map(|cached| cached.name.clone())
+    }) {
+        return name;
+    }
+    let name = process_name_of_window(hwnd);
+    PROCESS_NAME_CACHE.with(|cache| {
+        cache.replace(Some(ProcessNameCache {
+            hwnd,
+            name: name.clone(),
+        }))
+    });
+    name
+}
+
+/// Get the exe name of the process that owns `hwnd`
+pub(crate) fn process_name_of_window(hwnd: HWND) -> Option<String> {
     unsafe {
-        let hwnd = GetForegroundWindow();
         if hwnd.0.is_null() {
             return None;
         }
@@ -38,17 +139,78 @@ pub fn get_foreground_process_name() -> Option<String> {
     }
 }
 
+/// Get the title text of the foreground window
+pub fn get_foreground_window_title() -> Option<String> {
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd.0.is_null() {
+            return None;
+        }
+        let len = GetWindowTextLengthW(hwnd);
+        if len <= 0 {
+            return Some(String::new());
+        }
+        let mut buf = vec![0u16; len as usize + 1];
+        let copied = GetWindowTextW(hwnd, &mut buf);
+        Some(String::from_utf16_lossy(&buf[..copied.max(0) as usize]))
+    }
+}
+
 /// Check if the foreground app is a privacy target
+///
+/// プロセス名による判定とウィンドウタイトルによる判定の OR。
 pub fn is_privacy_target(config: &PrivacyConfig) -> bool {
-    if !config.enabled || config.blocked_apps.is_empty() {
+    if !config.enabled {
         return false;
     }
-    match get_foreground_process_name() {
-        Some(name) => config
-            .blocked_apps
-            .iter()
-            .any(|app| app.eq_ignore_ascii_case(&name)),
-        // プロセス名取得失敗時は安全側（非表示）に倒す
-        None => true,
+    is_privacy_target_by_process(config) || is_privacy_target_by_title(config)
+}
+
+fn is_privacy_target_by_process(config: &PrivacyConfig) -> bool {
+    match config.mode {
+        PrivacyMode::Blocklist => {
+            if config.blocked_apps.is_empty() {
+                return false;
+            }
+            match get_foreground_process_name() {
+                Some(name) => config
+                    .blocked_apps
+                    .iter()
+                    .any(|app| app.eq_ignore_ascii_case(&name)),
+                // プロセス名取得失敗時は安全側（非表示）に倒す
+                None => true,
+            }
+        }
+        PrivacyMode::Allowlist => match get_foreground_process_name() {
+            Some(name) => {
+                // 自身（設定ウィンドウ等）は常に許可
+                let is_self = std::env::current_exe()
+                    .ok()
+                    .and_then(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()))
+                    .is_some_and(|own| own.eq_ignore_ascii_case(&name));
+                !is_self
+                    && !config
+                        .allowed_apps
+                        .iter()
+                        .any(|app| app.eq_ignore_ascii_case(&name))
+            }
+            None => true,
+        },
+    }
+}
+
+/// Check if the foreground window title contains a blocked substring (case-insensitive)
+pub fn is_privacy_target_by_title(config: &PrivacyConfig) -> bool {
+    if !config.enabled || config.blocked_title_substrings.is_empty() {
+        return false;
     }
+    let Some(title) = get_foreground_window_title() else {
+        return false;
+    };
+    let title = title.to_lowercase();
+    config
+        .blocked_title_substrings
+        .iter()
+        .filter(|s| !s.trim().is_empty())
+        .any(|s| title.contains(&s.trim().to_lowercase()))
 }
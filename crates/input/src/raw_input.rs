@@ -0,0 +1,95 @@
+use std::mem;
+use std::time::Instant;
+
+use windows::Win32::Foundation::*;
+use windows::Win32::UI::Input::*;
+use windows::Win32::UI::WindowsAndMessaging::*;
+
+use ystrokey_core::{MouseAction, MouseButton, MouseEvent, MouseSource};
+
+/// HID_USAGE_PAGE_GENERIC
+const HID_USAGE_PAGE_GENERIC: u16 = 0x01;
+/// HID_USAGE_GENERIC_MOUSE
+const HID_USAGE_GENERIC_MOUSE: u16 = 0x02;
+
+const BUTTON_FLAGS: [(u32, MouseButton, MouseAction); 10] = [
+    (RI_MOUSE_LEFT_BUTTON_DOWN, MouseButton::Left, MouseAction::Down),
+    (RI_MOUSE_LEFT_BUTTON_UP, MouseButton::Left, MouseAction::Up),
+    (RI_MOUSE_RIGHT_BUTTON_DOWN, MouseButton::Right, MouseAction::Down),
+    (RI_MOUSE_RIGHT_BUTTON_UP, MouseButton::Right, MouseAction::Up),
+    (RI_MOUSE_MIDDLE_BUTTON_DOWN, MouseButton::Middle, MouseAction::Down),
+    (RI_MOUSE_MIDDLE_BUTTON_UP, MouseButton::Middle, MouseAction::Up),
+    (RI_MOUSE_BUTTON_4_DOWN, MouseButton::X1, MouseAction::Down),
+    (RI_MOUSE_BUTTON_4_UP, MouseButton::X1, MouseAction::Up),
+    (RI_MOUSE_BUTTON_5_DOWN, MouseButton::X2, MouseAction::Down),
+    (RI_MOUSE_BUTTON_5_UP, MouseButton::X2, MouseAction::Up),
+];
+
+/// マウスの Raw Input を指定ウィンドウで受け取るよう登録
+///
+/// OSD ウィンドウは前面にならないため RIDEV_INPUTSINK でバックグラウンド受信する。
+pub fn register_raw_mouse(hwnd: HWND) -> windows::core::Result<()> {
+    let device = RAWINPUTDEVICE {
+        usUsagePage: HID_USAGE_PAGE_GENERIC,
+        usUsage: HID_USAGE_GENERIC_MOUSE,
+        dwFlags: RIDEV_INPUTSINK,
+        hwndTarget: hwnd,
+    };
+    unsafe { RegisterRawInputDevices(&[device], mem::size_of::<RAWINPUTDEVICE>() as u32) }
+}
+
+/// WM_INPUT の lParam からマウスのボタン・ホイールイベントを取り出す
+///
+/// Raw Input にはイベント時刻が含まれないため、受信時の `Instant`
+/// （Windows では QueryPerformanceCounter ベース）を使う。
+pub fn read_raw_mouse_events(lparam: LPARAM) -> Vec<MouseEvent> {
+    let timestamp = Instant::now();
+    let mut raw = RAWINPUT::default();
+    let mut size = mem::size_of::<RAWINPUT>() as u32;
+    let copied = unsafe {
+        GetRawInputData(
+            HRAWINPUT(lparam.0 as *mut _),
+            RID_INPUT,
+            Some(&mut raw as *mut RAWINPUT as *mut _),
+            &mut size,
+            mem::size_of::<RAWINPUTHEADER>() as u32,
+        )
+    };
+    if copied == u32::MAX || raw.header.dwType != RIM_TYPEMOUSE.0 {
+        return Vec::new();
+    }
+
+    let (flags, data) = unsafe {
+        let buttons = raw.data.mouse.Anonymous.Anonymous;
+        (buttons.usButtonFlags as u32, buttons.usButtonData)
+    };
+    if flags == 0 {
+        return Vec::new();
+    }
+
+    let mut cursor = POINT::default();
+    let _ = unsafe { GetCursorPos(&mut cursor) };
+    let position = (cursor.x, cursor.y);
+
+    let mut events: Vec<MouseEvent> = BUTTON_FLAGS
+        .iter()
+        .filter(|(flag, _, _)| flags & flag != 0)
+        .map(|&(_, button, action)| MouseEvent {
+            button,
+            action,
+            position,
+            source: MouseSource::RawInput,
+            timestamp,
+        })
+        .collect();
+    if flags & RI_MOUSE_WHEEL != 0 {
+        events.push(MouseEvent {
+            button: MouseButton::Middle,
+            action: MouseAction::Wheel(data as i16),
+            position,
+            source: MouseSource::RawInput,
+            timestamp,
+        });
+    }
+    events
+}
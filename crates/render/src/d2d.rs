@@ -1,22 +1,41 @@
-use windows::core::{w, PCWSTR};
+use std::cell::RefCell;
+
+use windows::core::{w, Interface, PCWSTR};
+use windows::Foundation::Numerics::Matrix3x2;
 use windows::Win32::Foundation::*;
 use windows::Win32::Graphics::Direct2D::Common::*;
 use windows::Win32::Graphics::Direct2D::*;
 use windows::Win32::Graphics::DirectWrite::*;
 use windows::Win32::Graphics::Dxgi::Common::*;
-use windows::Win32::Graphics::Gdi::HDC;
+use windows::Win32::Graphics::Gdi::{DeleteDC, DeleteObject, GdiFlush, SelectObject, HDC};
+use windows::Win32::Graphics::Imaging::{
+    CLSID_WICImagingFactory, GUID_WICPixelFormat32bppPBGRA, IWICImagingFactory, WICBitmapCacheOnLoad,
+};
+use windows::Win32::System::Com::{
+    CoCreateInstance, CoInitializeEx, CoUninitialize, CLSCTX_INPROC_SERVER, COINIT_MULTITHREADED,
+};
 
+use crate::png::unpremultiply;
 use ystrokey_core::{
-    DisplayItem, DisplayItemKind, KeyAction, KeyStrokeEntry, RenderError, StyleConfig,
+    DisplayItem, DisplayItemKind, KeyAction, KeyStrokeEntry, OsdLayout, RenderError, StyleConfig,
+    TextRenderingMode,
 };
 
 pub struct D2DRenderer {
-    #[allow(dead_code)]
     factory: ID2D1Factory1,
-    render_target: ID2D1DCRenderTarget,
-    #[allow(dead_code)]
+    render_target: ID2D1RenderTarget,
+    /// ウィンドウ描画用の DC ターゲット（`render_target` と同じもの）。ヘッドレス描画では None
+    dc_target: Option<ID2D1DCRenderTarget>,
     dwrite_factory: IDWriteFactory,
+    /// `custom_font_path` から読み込んだフォントコレクション (None ならシステムフォント)
+    font_collection: Option<IDWriteFontCollection>,
+    custom_font_path: Option<String>,
+    /// 主フォントにないグリフ（かな・漢字など）の代替フォント
+    font_fallback: Option<IDWriteFontFallback>,
+    fallback_font_family: String,
     text_format: IDWriteTextFormat,
+    ime_text_format: IDWriteTextFormat,
+    clipboard_text_format: IDWriteTextFormat,
     label_text_format: IDWriteTextFormat,
     count_text_format: IDWriteTextFormat,
     text_brush: ID2D1SolidColorBrush,
@@ -35,7 +54,24 @@ pub struct D2DRenderer {
     // Ghost-mode 用ブラシ
     ghost_bg_brush: ID2D1SolidColorBrush,
     ghost_border_brush: ID2D1SolidColorBrush,
+    // キーキャップ描画用（色は描画時に背景色から算出）
+    keycap_highlight_brush: ID2D1SolidColorBrush,
+    keycap_shadow_brush: ID2D1SolidColorBrush,
+    text_rendering_mode: TextRenderingMode,
+    /// DrawText に渡すオプション（`style.enable_color_fonts` でカラーグリフを有効化）
+    text_draw_options: D2D1_DRAW_TEXT_OPTIONS,
     dpi_scale: f32,
+    /// DPI 倍率を掛ける前のフォントサイズ（`StyleConfig::font_size`）
+    base_font_size: f32,
+    layout: OsdLayout,
+    text_extents: RefCell<TextExtentCache>,
+}
+
+/// 横並びレイアウトでのアイテム配置（行 0 が最下段）
+struct HorizontalSlot {
+    left: f32,
+    width: f32,
+    row: usize,
 }
 
 impl D2DRenderer {
@@ -45,128 +81,134 @@ impl D2DRenderer {
                 D2D1CreateFactory(D2D1_FACTORY_TYPE_SINGLE_THREADED, None)
                     .map_err(|e: windows::core::Error| RenderError::CreateFailed(e.to_string()))?;
 
-            let render_props = D2D1_RENDER_TARGET_PROPERTIES {
-                r#type: D2D1_RENDER_TARGET_TYPE_DEFAULT,
-                pixelFormat: D2D1_PIXEL_FORMAT {
-                    format: DXGI_FORMAT_B8G8R8A8_UNORM,
-                    alphaMode: D2D1_ALPHA_MODE_PREMULTIPLIED,
-                },
-                dpiX: 0.0,
-                dpiY: 0.0,
-                usage: D2D1_RENDER_TARGET_USAGE_NONE,
-                minLevel: D2D1_FEATURE_LEVEL_DEFAULT,
-            };
-
-            let render_target = factory
-                .CreateDCRenderTarget(&render_props)
-                .map_err(|e: windows::core::Error| RenderError::CreateFailed(e.to_string()))?;
+            let dc_target = create_dc_render_target(&factory)?;
+            Self::with_target(factory, dc_target.clone().into(), Some(dc_target), style)
+        }
+    }
 
+    fn with_target(
+        factory: ID2D1Factory1,
+        render_target: ID2D1RenderTarget,
+        dc_target: Option<ID2D1DCRenderTarget>,
+        style: &StyleConfig,
+    ) -> Result<Self, RenderError> {
+        unsafe {
             let dwrite_factory: IDWriteFactory =
                 DWriteCreateFactory(DWRITE_FACTORY_TYPE_SHARED)
                     .map_err(|e: windows::core::Error| RenderError::CreateFailed(e.to_string()))?;
 
-            let font_wide = to_wide(&style.font_family);
-
-            // メインテキストフォーマット
-            let text_format = dwrite_factory
-                .CreateTextFormat(
-                    PCWSTR(font_wide.as_ptr()),
-                    None,
-                    DWRITE_FONT_WEIGHT_SEMI_BOLD,
-                    DWRITE_FONT_STYLE_NORMAL,
-                    DWRITE_FONT_STRETCH_NORMAL,
-                    style.font_size,
-                    w!("ja-JP"),
-                )
-                .map_err(|e: windows::core::Error| RenderError::CreateFailed(e.to_string()))?;
+            // カスタムフォントファイル指定時はそのコレクションからファミリーを解決
+            let font_collection = style
+                .custom_font_path
+                .as_deref()
+                .and_then(|path| load_custom_font_collection(&dwrite_factory, path));
+            let font_wide = to_wide(&resolve_font_family(
+                font_collection.as_ref(),
+                &style.font_family,
+            ));
+            let font_fallback = create_font_fallback(&dwrite_factory, &style.fallback_font_family);
+
+            // メインテキストフォーマット（キー入力）
+            let text_format = create_text_format(
+                &dwrite_factory,
+                &font_wide,
+                font_collection.as_ref(),
+                font_fallback.as_ref(),
+                DWRITE_FONT_WEIGHT(style.key_font_weight as i32),
+                font_style(style.key_font_italic),
+                style.font_size,
+            )?;
+
+            // IME変換中テキスト用フォーマット
+            let ime_text_format = create_text_format(
+                &dwrite_factory,
+                &font_wide,
+                font_collection.as_ref(),
+                font_fallback.as_ref(),
+                DWRITE_FONT_WEIGHT(style.ime_font_weight as i32),
+                font_style(style.ime_font_italic),
+                style.font_size,
+            )?;
+
+            // クリップボードプレビュー用フォーマット
+            let clipboard_text_format = create_text_format(
+                &dwrite_factory,
+                &font_wide,
+                font_collection.as_ref(),
+                font_fallback.as_ref(),
+                DWRITE_FONT_WEIGHT(style.clipboard_font_weight as i32),
+                DWRITE_FONT_STYLE_NORMAL,
+                style.font_size,
+            )?;
 
             // ショートカットラベル用フォーマット (85%)
-            let label_text_format = dwrite_factory
-                .CreateTextFormat(
-                    PCWSTR(font_wide.as_ptr()),
-                    None,
-                    DWRITE_FONT_WEIGHT_MEDIUM,
-                    DWRITE_FONT_STYLE_NORMAL,
-                    DWRITE_FONT_STRETCH_NORMAL,
-                    style.font_size * 0.85,
-                    w!("ja-JP"),
-                )
-                .map_err(|e: windows::core::Error| RenderError::CreateFailed(e.to_string()))?;
+            let label_text_format = create_text_format(
+                &dwrite_factory,
+                &font_wide,
+                font_collection.as_ref(),
+                font_fallback.as_ref(),
+                DWRITE_FONT_WEIGHT_MEDIUM,
+                DWRITE_FONT_STYLE_NORMAL,
+                style.font_size * LABEL_FONT_RATIO,
+            )?;
 
             // 連打カウント用フォーマット (75%)
-            let count_text_format = dwrite_factory
-                .CreateTextFormat(
-                    PCWSTR(font_wide.as_ptr()),
-                    None,
-                    DWRITE_FONT_WEIGHT_BOLD,
-                    DWRITE_FONT_STYLE_NORMAL,
-                    DWRITE_FONT_STRETCH_NORMAL,
-                    style.font_size * 0.75,
-                    w!("ja-JP"),
-                )
-                .map_err(|e: windows::core::Error| RenderError::CreateFailed(e.to_string()))?;
+            let count_text_format = create_text_format(
+                &dwrite_factory,
+                &font_wide,
+                font_collection.as_ref(),
+                font_fallback.as_ref(),
+                DWRITE_FONT_WEIGHT_BOLD,
+                DWRITE_FONT_STYLE_NORMAL,
+                style.font_size * COUNT_FONT_RATIO,
+            )?;
 
             // テキストブラシ (StyleConfig.text_color)
-            let text_brush = render_target
-                .CreateSolidColorBrush(&parse_color(&style.text_color), None)
-                .map_err(|e: windows::core::Error| RenderError::CreateFailed(e.to_string()))?;
+            let text_brush = solid_brush(&render_target, &style.text_color)?;
 
             // Up状態テキストブラシ (#1565C0)
-            let up_text_brush = render_target
-                .CreateSolidColorBrush(&parse_color("#1565C0"), None)
-                .map_err(|e: windows::core::Error| RenderError::CreateFailed(e.to_string()))?;
+            let up_text_brush = solid_brush(&render_target, "#1565C0")?;
 
             // 背景ブラシ群
-            let key_down_brush = render_target
-                .CreateSolidColorBrush(&parse_color(&style.key_down_color), None)
-                .map_err(|e: windows::core::Error| RenderError::CreateFailed(e.to_string()))?;
+            let key_down_brush = solid_brush(&render_target, &style.key_down_color)?;
 
-            let key_up_brush = render_target
-                .CreateSolidColorBrush(&parse_color("#90CAF9"), None)
-                .map_err(|e: windows::core::Error| RenderError::CreateFailed(e.to_string()))?;
+            let key_up_brush = solid_brush(&render_target, "#90CAF9")?;
 
-            let modifier_brush = render_target
-                .CreateSolidColorBrush(&parse_color("#7C4DFF"), None)
-                .map_err(|e: windows::core::Error| RenderError::CreateFailed(e.to_string()))?;
+            let modifier_brush = solid_brush(&render_target, "#7C4DFF")?;
 
-            let shortcut_brush = render_target
-                .CreateSolidColorBrush(&parse_color(&style.shortcut_color), None)
-                .map_err(|e: windows::core::Error| RenderError::CreateFailed(e.to_string()))?;
+            let shortcut_brush = solid_brush(&render_target, &style.shortcut_color)?;
 
-            let ime_brush = render_target
-                .CreateSolidColorBrush(&parse_color("#F44336"), None)
-                .map_err(|e: windows::core::Error| RenderError::CreateFailed(e.to_string()))?;
+            let ime_brush = solid_brush(&render_target, "#F44336")?;
 
-            let clipboard_brush = render_target
-                .CreateSolidColorBrush(&parse_color("#FF9800"), None)
-                .map_err(|e: windows::core::Error| RenderError::CreateFailed(e.to_string()))?;
+            let clipboard_brush = solid_brush(&render_target, "#FF9800")?;
 
-            let numpad_brush = render_target
-                .CreateSolidColorBrush(&parse_color("#009688"), None)
-                .map_err(|e: windows::core::Error| RenderError::CreateFailed(e.to_string()))?;
+            let numpad_brush = solid_brush(&render_target, "#009688")?;
 
-            let lock_brush = render_target
-                .CreateSolidColorBrush(&parse_color("#607D8B"), None)
-                .map_err(|e: windows::core::Error| RenderError::CreateFailed(e.to_string()))?;
+            let lock_brush = solid_brush(&render_target, "#607D8B")?;
 
-            let count_brush = render_target
-                .CreateSolidColorBrush(&parse_color("#FF9800"), None)
-                .map_err(|e: windows::core::Error| RenderError::CreateFailed(e.to_string()))?;
+            let count_brush = solid_brush(&render_target, "#FF9800")?;
 
             // Ghost-mode ブラシ: 暗めグレー背景 + 白枠線
-            let ghost_bg_brush = render_target
-                .CreateSolidColorBrush(&parse_color("#1A1A1A"), None)
-                .map_err(|e: windows::core::Error| RenderError::CreateFailed(e.to_string()))?;
+            let ghost_bg_brush = solid_brush(&render_target, "#1A1A1A")?;
+
+            let ghost_border_brush = solid_brush(&render_target, "#FFFFFF")?;
+
+            let keycap_highlight_brush = solid_brush(&render_target, "#FFFFFF")?;
 
-            let ghost_border_brush = render_target
-                .CreateSolidColorBrush(&parse_color("#FFFFFF"), None)
-                .map_err(|e: windows::core::Error| RenderError::CreateFailed(e.to_string()))?;
+            let keycap_shadow_brush = solid_brush(&render_target, "#000000")?;
 
-            Ok(Self {
+            let renderer = Self {
                 factory,
                 render_target,
+                dc_target,
                 dwrite_factory,
+                font_collection,
+                custom_font_path: style.custom_font_path.clone(),
+                font_fallback,
+                fallback_font_family: style.fallback_font_family.clone(),
                 text_format,
+                ime_text_format,
+                clipboard_text_format,
                 label_text_format,
                 count_text_format,
                 text_brush,
@@ -182,8 +224,70 @@ impl D2DRenderer {
                 count_brush,
                 ghost_bg_brush,
                 ghost_border_brush,
+                keycap_highlight_brush,
+                keycap_shadow_brush,
+                text_rendering_mode: style.text_rendering_mode,
+                text_draw_options: text_draw_options(style),
                 dpi_scale: 1.0,
-            })
+                base_font_size: style.font_size,
+                layout: OsdLayout::Vertical,
+                text_extents: RefCell::new(TextExtentCache::new(TEXT_EXTENT_CACHE_CAPACITY)),
+            };
+            renderer.apply_text_rendering();
+            Ok(renderer)
+        }
+    }
+
+    /// text_rendering_mode に応じてアンチエイリアス方式とレンダリングパラメータを設定。
+    /// Default は高DPI (150%以上) ではグレースケール、それ以外はシステム既定に任せる。
+    fn apply_text_rendering(&self) {
+        let mode = match self.text_rendering_mode {
+            TextRenderingMode::Default if self.dpi_scale >= 1.5 => TextRenderingMode::GrayscaleAA,
+            other => other,
+        };
+
+        unsafe {
+            let Ok(default_params) = self.dwrite_factory.CreateRenderingParams() else {
+                return;
+            };
+            let (antialias, params) = match mode {
+                TextRenderingMode::Default => (D2D1_TEXT_ANTIALIAS_MODE_DEFAULT, Ok(default_params)),
+                TextRenderingMode::ClearType => (
+                    D2D1_TEXT_ANTIALIAS_MODE_CLEARTYPE,
+                    self.dwrite_factory.CreateCustomRenderingParams(
+                        default_params.GetGamma(),
+                        default_params.GetEnhancedContrast(),
+                        1.0,
+                        default_params.GetPixelGeometry(),
+                        DWRITE_RENDERING_MODE_NATURAL_SYMMETRIC,
+                    ),
+                ),
+                TextRenderingMode::GrayscaleAA => (
+                    D2D1_TEXT_ANTIALIAS_MODE_GRAYSCALE,
+                    self.dwrite_factory.CreateCustomRenderingParams(
+                        default_params.GetGamma(),
+                        default_params.GetEnhancedContrast(),
+                        0.0,
+                        default_params.GetPixelGeometry(),
+                        DWRITE_RENDERING_MODE_NATURAL_SYMMETRIC,
+                    ),
+                ),
+                TextRenderingMode::Aliased => (
+                    D2D1_TEXT_ANTIALIAS_MODE_ALIASED,
+                    self.dwrite_factory.CreateCustomRenderingParams(
+                        default_params.GetGamma(),
+                        default_params.GetEnhancedContrast(),
+                        0.0,
+                        default_params.GetPixelGeometry(),
+                        DWRITE_RENDERING_MODE_ALIASED,
+                    ),
+                ),
+            };
+
+            self.render_target.SetTextAntialiasMode(antialias);
+            if let Ok(params) = params {
+                self.render_target.SetTextRenderingParams(&params);
+            }
         }
     }
 
@@ -225,9 +329,13 @@ impl D2DRenderer {
                 }
             }
             DisplayItemKind::Shortcut { .. } => &self.shortcut_brush,
-            DisplayItemKind::ImeComposition { .. } => &self.ime_brush,
+            DisplayItemKind::ImeComposition { .. } | DisplayItemKind::ImeCandidateList { .. } => {
+                &self.ime_brush
+            }
             DisplayItemKind::ClipboardPreview { .. } => &self.clipboard_brush,
             DisplayItemKind::LockIndicator { .. } => &self.lock_brush,
+            DisplayItemKind::GamepadInput { .. } => &self.key_down_brush,
+            DisplayItemKind::ModifierState { .. } => &self.modifier_brush,
         }
     }
 
@@ -251,8 +359,52 @@ impl D2DRenderer {
         }
     }
 
+    /// アイテム種別ごとのテキストフォーマットを選択
+    fn select_text_format(&self, item: &DisplayItem) -> &IDWriteTextFormat {
+        match &item.kind {
+            DisplayItemKind::ImeComposition { .. } | DisplayItemKind::ImeCandidateList { .. } => {
+                &self.ime_text_format
+            }
+            DisplayItemKind::ClipboardPreview { .. } => &self.clipboard_text_format,
+            _ => &self.text_format,
+        }
+    }
+
+    /// デバイスロスト後の復旧: ファクトリとフォントコレクションは残し、
+    /// レンダーターゲット・ブラシ・テキストフォーマットをその場で作り直す
+    pub fn recreate(&mut self, style: &StyleConfig) -> Result<(), RenderError> {
+        unsafe {
+            let dc_target = create_dc_render_target(&self.factory)?;
+            self.render_target = dc_target.clone().into();
+            self.dc_target = Some(dc_target);
+            let rt = &self.render_target;
+            self.text_brush = solid_brush(rt, &style.text_color)?;
+            self.up_text_brush = solid_brush(rt, "#1565C0")?;
+            self.key_down_brush = solid_brush(rt, &style.key_down_color)?;
+            self.key_up_brush = solid_brush(rt, "#90CAF9")?;
+            self.modifier_brush = solid_brush(rt, "#7C4DFF")?;
+            self.shortcut_brush = solid_brush(rt, &style.shortcut_color)?;
+            self.ime_brush = solid_brush(rt, "#F44336")?;
+            self.clipboard_brush = solid_brush(rt, "#FF9800")?;
+            self.numpad_brush = solid_brush(rt, "#009688")?;
+            self.lock_brush = solid_brush(rt, "#607D8B")?;
+            self.count_brush = solid_brush(rt, "#FF9800")?;
+            self.ghost_bg_brush = solid_brush(rt, "#1A1A1A")?;
+            self.ghost_border_brush = solid_brush(rt, "#FFFFFF")?;
+            self.keycap_highlight_brush = solid_brush(rt, "#FFFFFF")?;
+            self.keycap_shadow_brush = solid_brush(rt, "#000000")?;
+        }
+        // テキストフォーマットの再生成（計測キャッシュも破棄される）
+        self.update_style(style);
+        // 新しいターゲットへ現在の dpi_scale に応じた描画設定を戻す
+        self.apply_text_rendering();
+        Ok(())
+    }
+
     /// StyleConfig変更時にブラシ・フォントを再生成
     pub fn update_style(&mut self, style: &StyleConfig) {
+        // フォーマットを作り直すので計測結果は使えない
+        self.text_extents.get_mut().clear();
         unsafe {
             // ブラシ更新
             if let Ok(b) = self.render_target.CreateSolidColorBrush(&parse_color(&style.text_color), None) {
@@ -266,51 +418,177 @@ impl D2DRenderer {
             }
 
             // フォント更新
-            let font_wide = to_wide(&style.font_family);
-            if let Ok(f) = self.dwrite_factory.CreateTextFormat(
-                PCWSTR(font_wide.as_ptr()),
-                None,
-                DWRITE_FONT_WEIGHT_SEMI_BOLD,
-                DWRITE_FONT_STYLE_NORMAL,
-                DWRITE_FONT_STRETCH_NORMAL,
-                style.font_size,
-                w!("ja-JP"),
+            if self.custom_font_path != style.custom_font_path {
+                self.font_collection = style
+                    .custom_font_path
+                    .as_deref()
+                    .and_then(|path| load_custom_font_collection(&self.dwrite_factory, path));
+                self.custom_font_path = style.custom_font_path.clone();
+            }
+            if self.fallback_font_family != style.fallback_font_family {
+                self.font_fallback = create_font_fallback(&self.dwrite_factory, &style.fallback_font_family);
+                self.fallback_font_family = style.fallback_font_family.clone();
+            }
+            self.base_font_size = style.font_size;
+            let size = self.effective_font_size();
+            let font_wide = to_wide(&resolve_font_family(
+                self.font_collection.as_ref(),
+                &style.font_family,
+            ));
+            if let Ok(f) = create_text_format(
+                &self.dwrite_factory,
+                &font_wide,
+                self.font_collection.as_ref(),
+                self.font_fallback.as_ref(),
+                DWRITE_FONT_WEIGHT(style.key_font_weight as i32),
+                font_style(style.key_font_italic),
+                size,
             ) {
                 self.text_format = f;
             }
-            if let Ok(f) = self.dwrite_factory.CreateTextFormat(
-                PCWSTR(font_wide.as_ptr()),
-                None,
+            if let Ok(f) = create_text_format(
+                &self.dwrite_factory,
+                &font_wide,
+                self.font_collection.as_ref(),
+                self.font_fallback.as_ref(),
+                DWRITE_FONT_WEIGHT(style.ime_font_weight as i32),
+                font_style(style.ime_font_italic),
+                size,
+            ) {
+                self.ime_text_format = f;
+            }
+            if let Ok(f) = create_text_format(
+                &self.dwrite_factory,
+                &font_wide,
+                self.font_collection.as_ref(),
+                self.font_fallback.as_ref(),
+                DWRITE_FONT_WEIGHT(style.clipboard_font_weight as i32),
+                DWRITE_FONT_STYLE_NORMAL,
+                size,
+            ) {
+                self.clipboard_text_format = f;
+            }
+            if let Ok(f) = create_text_format(
+                &self.dwrite_factory,
+                &font_wide,
+                self.font_collection.as_ref(),
+                self.font_fallback.as_ref(),
                 DWRITE_FONT_WEIGHT_MEDIUM,
                 DWRITE_FONT_STYLE_NORMAL,
-                DWRITE_FONT_STRETCH_NORMAL,
-                style.font_size * 0.85,
-                w!("ja-JP"),
+                size * LABEL_FONT_RATIO,
             ) {
                 self.label_text_format = f;
             }
-            if let Ok(f) = self.dwrite_factory.CreateTextFormat(
-                PCWSTR(font_wide.as_ptr()),
-                None,
+            if let Ok(f) = create_text_format(
+                &self.dwrite_factory,
+                &font_wide,
+                self.font_collection.as_ref(),
+                self.font_fallback.as_ref(),
                 DWRITE_FONT_WEIGHT_BOLD,
                 DWRITE_FONT_STYLE_NORMAL,
-                DWRITE_FONT_STRETCH_NORMAL,
-                style.font_size * 0.75,
-                w!("ja-JP"),
+                size * COUNT_FONT_RATIO,
             ) {
                 self.count_text_format = f;
             }
         }
+
+        self.text_draw_options = text_draw_options(style);
+
+        if self.text_rendering_mode != style.text_rendering_mode {
+            self.text_rendering_mode = style.text_rendering_mode;
+            self.apply_text_rendering();
+        }
     }
 
     pub fn update_dpi(&mut self, dpi: u32) {
-        self.dpi_scale = dpi as f32 / 96.0;
+        let scale = dpi as f32 / 96.0;
+        if scale != self.dpi_scale {
+            self.dpi_scale = scale;
+            self.rescale_text_formats();
+        }
+        self.apply_text_rendering();
+    }
+
+    /// `StyleConfig::font_size` を現在のモニターの DPI 倍率で拡大したサイズ
+    fn effective_font_size(&self) -> f32 {
+        self.base_font_size * self.dpi_scale
+    }
+
+    /// DPI 倍率の変更後、各テキストフォーマットを同じ書体のまま新しいサイズで作り直す
+    fn rescale_text_formats(&mut self) {
+        let size = self.effective_font_size();
+        unsafe {
+            if let Some(f) = self.resized_text_format(&self.text_format, size) {
+                self.text_format = f;
+            }
+            if let Some(f) = self.resized_text_format(&self.ime_text_format, size) {
+                self.ime_text_format = f;
+            }
+            if let Some(f) = self.resized_text_format(&self.clipboard_text_format, size) {
+                self.clipboard_text_format = f;
+            }
+            if let Some(f) = self.resized_text_format(&self.label_text_format, size * LABEL_FONT_RATIO) {
+                self.label_text_format = f;
+            }
+            if let Some(f) = self.resized_text_format(&self.count_text_format, size * COUNT_FONT_RATIO) {
+                self.count_text_format = f;
+            }
+        }
+        self.text_extents.get_mut().clear();
+    }
+
+    unsafe fn resized_text_format(&self, format: &IDWriteTextFormat, size: f32) -> Option<IDWriteTextFormat> {
+        let mut family = vec![0u16; format.GetFontFamilyNameLength() as usize + 1];
+        format.GetFontFamilyName(&mut family).ok()?;
+        let collection = format.GetFontCollection().ok();
+        create_text_format(
+            &self.dwrite_factory,
+            &family,
+            collection.as_ref(),
+            self.font_fallback.as_ref(),
+            format.GetFontWeight(),
+            format.GetFontStyle(),
+            size,
+        )
+        .ok()
     }
 
     pub fn dpi_scale(&self) -> f32 {
         self.dpi_scale
     }
 
+    pub fn set_layout(&mut self, layout: OsdLayout) {
+        self.layout = layout;
+    }
+
+    /// 表示中アイテムをオフスクリーンの DIB に描画し、ピクセル列（BGRA・乗算済みα・上→下）を返す
+    ///
+    /// スクリーンショット用。ゴースト背景やプレビューは含めない。
+    pub fn render_to_bitmap(
+        &self,
+        live_items: &[DisplayItem],
+        style: &StyleConfig,
+        width: u32,
+        height: u32,
+    ) -> Result<Vec<u8>, RenderError> {
+        unsafe {
+            let (mem_dc, dib, old_bitmap, bits) =
+                crate::window::create_dib(width as i32, height as i32)?;
+            let result = self
+                .render(live_items, &[], style, mem_dc, width, height, 0.0)
+                .map(|()| {
+                    // GDI のバッチ描画を確定させてからピクセルを読む
+                    let _ = GdiFlush();
+                    let len = width as usize * height as usize * 4;
+                    std::slice::from_raw_parts(bits as *const u8, len).to_vec()
+                });
+            SelectObject(mem_dc, old_bitmap);
+            let _ = DeleteObject(dib);
+            let _ = DeleteDC(mem_dc);
+            result
+        }
+    }
+
     pub fn render(
         &self,
         live_items: &[DisplayItem],
@@ -321,6 +599,9 @@ impl D2DRenderer {
         height: u32,
         ghost_opacity: f32,
     ) -> Result<(), RenderError> {
+        let Some(dc_target) = &self.dc_target else {
+            return Err(RenderError::DrawFailed("renderer has no DC target".into()));
+        };
         unsafe {
             // DCをバインド
             let bind_rect = RECT {
@@ -329,10 +610,72 @@ impl D2DRenderer {
                 right: width as i32,
                 bottom: height as i32,
             };
-            self.render_target
-                .BindDC(hdc, &bind_rect)
-                .map_err(|e: windows::core::Error| RenderError::DrawFailed(e.to_string()))?;
+            dc_target.BindDC(hdc, &bind_rect).map_err(draw_error)?;
+            self.draw_frame(live_items, preview_items, style, ghost_opacity)
+        }
+    }
+
+    /// ウィンドウなしで WIC ビットマップへ描画し、RGBA（非乗算α・上→下）のピクセル列を返す
+    ///
+    /// スナップショットテスト用。縦並びレイアウト・DPI 倍率 1.0 で `items` を
+    /// ライブアイテムとして描画する。
+    pub fn render_to_wic(
+        items: &[DisplayItem],
+        style: &StyleConfig,
+        width: u32,
+        height: u32,
+    ) -> Result<Vec<u8>, RenderError> {
+        unsafe {
+            // 呼び出し側で別モデルに初期化済みでも WIC は使えるので、成功時だけ対にする
+            let com = CoInitializeEx(None, COINIT_MULTITHREADED);
+            let result = Self::render_to_wic_inner(items, style, width, height);
+            if com.is_ok() {
+                CoUninitialize();
+            }
+            result
+        }
+    }
 
+    unsafe fn render_to_wic_inner(
+        items: &[DisplayItem],
+        style: &StyleConfig,
+        width: u32,
+        height: u32,
+    ) -> Result<Vec<u8>, RenderError> {
+        let wic: IWICImagingFactory = CoCreateInstance(&CLSID_WICImagingFactory, None, CLSCTX_INPROC_SERVER)
+            .map_err(|e: windows::core::Error| RenderError::CreateFailed(e.to_string()))?;
+        let bitmap = wic
+            .CreateBitmap(width, height, &GUID_WICPixelFormat32bppPBGRA, WICBitmapCacheOnLoad)
+            .map_err(|e: windows::core::Error| RenderError::CreateFailed(e.to_string()))?;
+        let factory: ID2D1Factory1 = D2D1CreateFactory(D2D1_FACTORY_TYPE_SINGLE_THREADED, None)
+            .map_err(|e: windows::core::Error| RenderError::CreateFailed(e.to_string()))?;
+        let target = factory
+            .CreateWicBitmapRenderTarget(&bitmap, &render_target_properties(96.0))
+            .map_err(|e: windows::core::Error| RenderError::CreateFailed(e.to_string()))?;
+
+        let renderer = Self::with_target(factory, target, None, style)?;
+        renderer.draw_frame(items, &[], style, 0.0)?;
+
+        let stride = width * 4;
+        let mut bgra = vec![0u8; stride as usize * height as usize];
+        bitmap
+            .CopyPixels(std::ptr::null(), stride, &mut bgra)
+            .map_err(|e: windows::core::Error| RenderError::DrawFailed(e.to_string()))?;
+        Ok(bgra
+            .chunks_exact(4)
+            .flat_map(|px| unpremultiply([px[2], px[1], px[0], px[3]]))
+            .collect())
+    }
+
+    /// BeginDraw〜EndDraw。ターゲットのバインドは呼び出し側で済ませておく
+    fn draw_frame(
+        &self,
+        live_items: &[DisplayItem],
+        preview_items: &[DisplayItem],
+        style: &StyleConfig,
+        ghost_opacity: f32,
+    ) -> Result<(), RenderError> {
+        unsafe {
             self.render_target.BeginDraw();
 
             // 透明クリア
@@ -343,14 +686,25 @@ impl D2DRenderer {
                 a: 0.0,
             }));
 
-            // Ghost背景（アイテム描画の前）
-            self.render_ghost_background(live_items, style, ghost_opacity);
-
             let s = self.dpi_scale;
             let line_height = (style.font_size + style.padding * 2.0) * s;
             let spacing = 4.0_f32 * s;
             let size = self.render_target.GetSize();
 
+            let slots = match self.layout {
+                OsdLayout::Vertical => None,
+                OsdLayout::Horizontal => {
+                    Some(self.horizontal_slots(live_items, style, size.width))
+                }
+            };
+            let live_lines = match &slots {
+                Some(slots) => slots.last().map_or(0, |slot| slot.row + 1),
+                None => live_items.len(),
+            };
+
+            // Ghost背景（アイテム描画の前）
+            self.render_ghost_background(live_lines, style, ghost_opacity);
+
             // Preview items (top-down). Render first so live items stay visually dominant.
             for (i, item) in preview_items.iter().enumerate() {
                 let top = (i as f32) * (line_height + spacing);
@@ -358,16 +712,32 @@ impl D2DRenderer {
                 self.render_item_at(item, top, bottom, size.width, style);
             }
 
-            // Live items (bottom-up).
-            for (i, item) in live_items.iter().enumerate() {
-                let bottom = size.height - (i as f32) * (line_height + spacing);
-                let top = bottom - line_height;
-                self.render_item_at(item, top, bottom, size.width, style);
+            match &slots {
+                // Live items (bottom-up).
+                None => {
+                    for (i, item) in live_items.iter().enumerate() {
+                        let bottom = size.height - (i as f32) * (line_height + spacing);
+                        let top = bottom - line_height;
+                        self.render_item_at(item, top, bottom, size.width, style);
+                    }
+                }
+                // Live items (left-to-right, 行が埋まったら上へ折り返す)
+                Some(slots) => {
+                    for (item, slot) in live_items.iter().zip(slots) {
+                        let bottom = size.height - (slot.row as f32) * (line_height + spacing);
+                        let top = bottom - line_height;
+                        // 各描画関数は左端 0 基準なので、平行移動で横位置をずらす
+                        self.render_target
+                            .SetTransform(&Matrix3x2::translation(slot.left, 0.0));
+                        self.render_item_at(item, top, bottom, slot.width, style);
+                    }
+                    self.render_target.SetTransform(&Matrix3x2::identity());
+                }
             }
 
             self.render_target
                 .EndDraw(None, None)
-                .map_err(|e: windows::core::Error| RenderError::DrawFailed(e.to_string()))?;
+                .map_err(draw_error)?;
         }
 
         Ok(())
@@ -420,10 +790,24 @@ impl D2DRenderer {
                     item.opacity,
                 );
             }
+            DisplayItemKind::KeyStroke { .. } if style.keycap_style => {
+                let text = format_item_text(&item.kind);
+                self.render_keycap_item(
+                    &text,
+                    top,
+                    bottom,
+                    width,
+                    style,
+                    bg_brush,
+                    text_brush,
+                    item.opacity,
+                );
+            }
             _ => {
                 let text = format_item_text(&item.kind);
                 self.render_simple_item(
                     &text,
+                    self.select_text_format(item),
                     top,
                     bottom,
                     width,
@@ -435,10 +819,163 @@ impl D2DRenderer {
         }
     }
 
+    /// 横並びレイアウトで各アイテムの横位置・幅・行を決める
+    ///
+    /// 1アイテムの幅は OSD 幅を上限とし、行に収まらなければ次の行（上側）へ送る。
+    unsafe fn horizontal_slots(
+        &self,
+        items: &[DisplayItem],
+        style: &StyleConfig,
+        max_width: f32,
+    ) -> Vec<HorizontalSlot> {
+        let mut slots = Vec::with_capacity(items.len());
+        let mut cursor_x = 0.0_f32;
+        let mut row = 0;
+        for item in items {
+            let width = self.measure_item_width(item, style).min(max_width);
+            if cursor_x > 0.0 && cursor_x + width > max_width {
+                cursor_x = 0.0;
+                row += 1;
+            }
+            slots.push(HorizontalSlot {
+                left: cursor_x,
+                width,
+                row,
+            });
+            cursor_x += width;
+        }
+        slots
+    }
+
+    /// アイテムを描き切るのに必要なウィンドウサイズ（`display.auto_size` 用）
+    ///
+    /// 横並びレイアウトの折り返しは `max_width` で判定し、結果は `max_width`×`max_height` に収める。
+    /// アイテムがなくてもゴースト背景のドラッグ用に最低 1 行分は確保する。
+    pub fn content_size(
+        &self,
+        live_items: &[DisplayItem],
+        preview_items: &[DisplayItem],
+        style: &StyleConfig,
+        max_width: u32,
+        max_height: u32,
+    ) -> (u32, u32) {
+        unsafe {
+            let s = self.dpi_scale;
+            let line_height = (style.font_size + style.padding * 2.0) * s;
+            let spacing = 4.0_f32 * s;
+
+            let (live_width, live_lines) = match self.layout {
+                OsdLayout::Vertical => (
+                    live_items
+                        .iter()
+                        .map(|item| self.measure_item_width(item, style))
+                        .fold(0.0_f32, f32::max),
+                    live_items.len(),
+                ),
+                OsdLayout::Horizontal => {
+                    let slots = self.horizontal_slots(live_items, style, max_width as f32);
+                    (
+                        slots
+                            .iter()
+                            .map(|slot| slot.left + slot.width)
+                            .fold(0.0_f32, f32::max),
+                        slots.last().map_or(0, |slot| slot.row + 1),
+                    )
+                }
+            };
+            let preview_width = preview_items
+                .iter()
+                .map(|item| self.measure_item_width(item, style))
+                .fold(0.0_f32, f32::max);
+
+            let lines = (live_lines + preview_items.len()).max(1) as f32;
+            let width = live_width.max(preview_width).max(line_height * 2.0);
+            let height = lines * (line_height + spacing) - spacing;
+            (
+                (width.ceil() as u32).clamp(1, max_width.max(1)),
+                (height.ceil() as u32).clamp(1, max_height.max(1)),
+            )
+        }
+    }
+
+    /// アイテムを描画したときに必要な幅（左右の余白込み）
+    unsafe fn measure_item_width(&self, item: &DisplayItem, style: &StyleConfig) -> f32 {
+        let s = self.dpi_scale;
+        let padding = style.padding * s;
+        match &item.kind {
+            DisplayItemKind::Shortcut {
+                keys_label,
+                action_label,
+            } => {
+                padding * 3.0
+                    + self.measure_text(keys_label, &self.text_format)
+                    + 8.0 * s
+                    + self.measure_text(action_label, &self.label_text_format)
+                    + 12.0 * s
+            }
+            DisplayItemKind::KeyStrokeGroup { strokes } => {
+                let pill_gap = 4.0_f32 * s;
+                let pills: f32 = strokes
+                    .iter()
+                    .map(|entry| {
+                        self.measure_text(&format_entry_text(entry), &self.text_format) + 16.0 * s
+                    })
+                    .sum();
+                // 打ち切り判定に引っかからないよう末尾のギャップ分を残す
+                padding * 2.0 + pills + pill_gap * strokes.len() as f32
+            }
+            DisplayItemKind::KeyStroke { repeat_count, .. } if *repeat_count > 1 => {
+                padding * 4.0
+                    + self.measure_text(&format_item_text_no_count(&item.kind), &self.text_format)
+                    + self.measure_text(&format!(" x{}", repeat_count), &self.count_text_format)
+            }
+            _ => {
+                padding * 4.0
+                    + self.measure_text(&format_item_text(&item.kind), self.select_text_format(item))
+            }
+        }
+    }
+
+    unsafe fn measure_text(&self, text: &str, text_format: &IDWriteTextFormat) -> f32 {
+        self.text_extent(text, text_format).width
+    }
+
+    /// テキストの描画サイズ（折り返しなし）。同じ文字列とフォーマットの組はキャッシュから返す
+    unsafe fn text_extent(&self, text: &str, text_format: &IDWriteTextFormat) -> TextExtent {
+        let format_key = text_format.as_raw() as usize;
+        if let Some(extent) = self.text_extents.borrow_mut().get(text, format_key) {
+            return extent;
+        }
+        let wide: Vec<u16> = text.encode_utf16().collect();
+        let extent = match self
+            .dwrite_factory
+            .CreateTextLayout(&wide, text_format, f32::MAX, f32::MAX)
+        {
+            Ok(layout) => {
+                let mut metrics = DWRITE_TEXT_METRICS::default();
+                let _ = layout.GetMetrics(&mut metrics);
+                TextExtent {
+                    width: metrics.widthIncludingTrailingWhitespace,
+                    height: metrics.height,
+                }
+            }
+            // フォールバック: 文字数ベース概算（失敗はキャッシュしない）
+            Err(_) => {
+                let size = text_format.GetFontSize();
+                return TextExtent {
+                    width: text.chars().count() as f32 * size * 0.6,
+                    height: size * 1.2,
+                };
+            }
+        };
+        self.text_extents.borrow_mut().insert(text, format_key, extent);
+        extent
+    }
+
     /// Ghost-mode: アクティブアイテムの背後に半透明背景を描画
     unsafe fn render_ghost_background(
         &self,
-        items: &[DisplayItem],
+        lines: usize,
         style: &StyleConfig,
         ghost_opacity: f32,
     ) {
@@ -452,7 +989,7 @@ impl D2DRenderer {
         let size = self.render_target.GetSize();
 
         // アイテムがなくても最低1行分のサイズを確保（ドラッグハンドル用）
-        let visible_lines = items.len().max(1) as f32;
+        let visible_lines = lines.max(1) as f32;
         let total_height = visible_lines * (line_height + spacing) - spacing;
         let bottom = size.height;
         let top = bottom - total_height;
@@ -483,11 +1020,87 @@ impl D2DRenderer {
             .DrawRoundedRectangle(&rounded, &self.ghost_border_brush, 1.0, None);
     }
 
+    /// キーキャップ風アイテムの描画
+    ///
+    /// 本体バッジの左上にハイライト、右下にシャドウを重ねて立体感を出す。
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) unsafe fn render_keycap_item(
+        &self,
+        text: &str,
+        top: f32,
+        bottom: f32,
+        width: f32,
+        style: &StyleConfig,
+        bg_brush: &ID2D1SolidColorBrush,
+        text_brush: &ID2D1SolidColorBrush,
+        opacity: f32,
+    ) {
+        let s = self.dpi_scale;
+        let padding = style.padding * s;
+        let border_radius = style.border_radius * s;
+        let depth = 2.0 * s;
+
+        let base = bg_brush.GetColor();
+        self.keycap_highlight_brush
+            .SetColor(&adjust_lightness(&base, 0.18));
+        self.keycap_highlight_brush.SetOpacity(opacity);
+        self.keycap_shadow_brush
+            .SetColor(&adjust_lightness(&base, -0.22));
+        self.keycap_shadow_brush.SetOpacity(opacity);
+
+        let rect = D2D_RECT_F {
+            left: padding,
+            top,
+            right: width - padding,
+            bottom,
+        };
+
+        // シャドウ（右下）
+        let shadow = D2D1_ROUNDED_RECT {
+            rect: D2D_RECT_F {
+                left: rect.left + depth,
+                top: rect.top + depth,
+                right: rect.right + depth,
+                bottom: rect.bottom + depth,
+            },
+            radiusX: border_radius,
+            radiusY: border_radius,
+        };
+        self.render_target
+            .FillRoundedRectangle(&shadow, &self.keycap_shadow_brush);
+
+        // ハイライト（左上）
+        let highlight = D2D1_ROUNDED_RECT {
+            rect: D2D_RECT_F {
+                left: rect.left - depth * 0.5,
+                top: rect.top - depth * 0.5,
+                right: rect.right - depth,
+                bottom: rect.bottom - depth,
+            },
+            radiusX: border_radius,
+            radiusY: border_radius,
+        };
+        self.render_target
+            .FillRoundedRectangle(&highlight, &self.keycap_highlight_brush);
+
+        self.render_simple_item(
+            text,
+            &self.text_format,
+            top,
+            bottom,
+            width,
+            style,
+            bg_brush,
+            text_brush,
+        );
+    }
+
     /// 通常アイテムの描画
     #[allow(clippy::too_many_arguments)]
     unsafe fn render_simple_item(
         &self,
         text: &str,
+        text_format: &IDWriteTextFormat,
         top: f32,
         bottom: f32,
         width: f32,
@@ -515,20 +1128,20 @@ impl D2DRenderer {
         self.render_target
             .FillRoundedRectangle(&rounded, bg_brush);
 
+        // 縦位置は DWRITE_PARAGRAPH_ALIGNMENT_CENTER に任せる
         let text_rect = D2D_RECT_F {
             left: rect.left + padding,
-            top: rect.top + padding / 2.0,
             right: rect.right - padding,
-            bottom: rect.bottom - padding / 2.0,
+            ..rect
         };
 
         let text_wide: Vec<u16> = text.encode_utf16().collect();
         self.render_target.DrawText(
             &text_wide,
-            &self.text_format,
+            text_format,
             &text_rect,
             text_brush,
-            D2D1_DRAW_TEXT_OPTIONS_NONE,
+            self.text_draw_options,
             DWRITE_MEASURING_MODE_NATURAL,
         );
     }
@@ -582,7 +1195,7 @@ impl D2DRenderer {
             &self.text_format,
             &keys_rect,
             &self.text_brush,
-            D2D1_DRAW_TEXT_OPTIONS_NONE,
+            self.text_draw_options,
             DWRITE_MEASURING_MODE_NATURAL,
         );
 
@@ -650,7 +1263,7 @@ impl D2DRenderer {
                     &self.label_text_format,
                     &action_text_rect,
                     &self.text_brush,
-                    D2D1_DRAW_TEXT_OPTIONS_NONE,
+                    self.text_draw_options,
                     DWRITE_MEASURING_MODE_NATURAL,
                 );
             }
@@ -706,43 +1319,31 @@ impl D2DRenderer {
             &self.text_format,
             &text_rect,
             text_brush,
-            D2D1_DRAW_TEXT_OPTIONS_NONE,
+            self.text_draw_options,
             DWRITE_MEASURING_MODE_NATURAL,
         );
 
         // メインテキスト幅を計測してカウント位置を決定
-        let main_layout = self.dwrite_factory.CreateTextLayout(
-            &main_wide,
-            &self.text_format,
-            rect.right - rect.left,
-            bottom - top,
-        );
+        let main_width = self.measure_text(main_text, &self.text_format);
+        let count_left = rect.left + padding + main_width;
 
-        if let Ok(layout) = main_layout {
-            let mut metrics = DWRITE_TEXT_METRICS::default();
-            let _ = layout.GetMetrics(&mut metrics);
-            let main_width = metrics.width;
-
-            let count_left = rect.left + padding + main_width;
-
-            let count_rect = D2D_RECT_F {
-                left: count_left,
-                top: rect.top + padding / 2.0,
-                right: rect.right - padding,
-                bottom: rect.bottom - padding / 2.0,
-            };
+        let count_rect = D2D_RECT_F {
+            left: count_left,
+            top: rect.top + padding / 2.0,
+            right: rect.right - padding,
+            bottom: rect.bottom - padding / 2.0,
+        };
 
-            self.count_brush.SetOpacity(opacity);
-            let count_wide: Vec<u16> = count_text.encode_utf16().collect();
-            self.render_target.DrawText(
-                &count_wide,
-                &self.count_text_format,
-                &count_rect,
-                &self.count_brush,
-                D2D1_DRAW_TEXT_OPTIONS_NONE,
-                DWRITE_MEASURING_MODE_NATURAL,
-            );
-        }
+        self.count_brush.SetOpacity(opacity);
+        let count_wide: Vec<u16> = count_text.encode_utf16().collect();
+        self.render_target.DrawText(
+            &count_wide,
+            &self.count_text_format,
+            &count_rect,
+            &self.count_brush,
+            self.text_draw_options,
+            DWRITE_MEASURING_MODE_NATURAL,
+        );
     }
 
     /// 連続キーストロークグループの水平描画
@@ -770,20 +1371,7 @@ impl D2DRenderer {
             let text_wide: Vec<u16> = text.encode_utf16().collect();
 
             // テキスト幅計測
-            let layout = self.dwrite_factory.CreateTextLayout(
-                &text_wide,
-                &self.text_format,
-                width,
-                bottom - top,
-            );
-            let text_width = if let Ok(layout) = layout {
-                let mut metrics = DWRITE_TEXT_METRICS::default();
-                let _ = layout.GetMetrics(&mut metrics);
-                metrics.width
-            } else {
-                // フォールバック: 文字数ベース概算
-                text.len() as f32 * style.font_size * 0.6
-            };
+            let text_width = self.measure_text(&text, &self.text_format);
 
             let pill_width = text_width + pill_padding_h * 2.0;
 
@@ -831,7 +1419,7 @@ impl D2DRenderer {
                 &self.text_format,
                 &text_rect,
                 text_brush,
-                D2D1_DRAW_TEXT_OPTIONS_NONE,
+                self.text_draw_options,
                 DWRITE_MEASURING_MODE_NATURAL,
             );
 
@@ -839,32 +1427,23 @@ impl D2DRenderer {
             if entry.repeat_count > 1 {
                 let count_text = format!("x{}", entry.repeat_count);
                 let count_wide: Vec<u16> = count_text.encode_utf16().collect();
-                let count_layout = self.dwrite_factory.CreateTextLayout(
+                let cm = self.text_extent(&count_text, &self.count_text_format);
+                let count_left = pill_rect.right - pill_padding_h / 2.0;
+                let count_rect = D2D_RECT_F {
+                    left: count_left,
+                    top: pill_rect.top - 2.0,
+                    right: count_left + cm.width + 4.0,
+                    bottom: pill_rect.top + cm.height,
+                };
+                self.count_brush.SetOpacity(opacity);
+                self.render_target.DrawText(
                     &count_wide,
                     &self.count_text_format,
-                    width,
-                    bottom - top,
+                    &count_rect,
+                    &self.count_brush,
+                    self.text_draw_options,
+                    DWRITE_MEASURING_MODE_NATURAL,
                 );
-                if let Ok(cl) = count_layout {
-                    let mut cm = DWRITE_TEXT_METRICS::default();
-                    let _ = cl.GetMetrics(&mut cm);
-                    let count_left = pill_rect.right - pill_padding_h / 2.0;
-                    let count_rect = D2D_RECT_F {
-                        left: count_left,
-                        top: pill_rect.top - 2.0,
-                        right: count_left + cm.width + 4.0,
-                        bottom: pill_rect.top + cm.height,
-                    };
-                    self.count_brush.SetOpacity(opacity);
-                    self.render_target.DrawText(
-                        &count_wide,
-                        &self.count_text_format,
-                        &count_rect,
-                        &self.count_brush,
-                        D2D1_DRAW_TEXT_OPTIONS_NONE,
-                        DWRITE_MEASURING_MODE_NATURAL,
-                    );
-                }
             }
 
             cursor_x += pill_width + pill_gap;
@@ -886,6 +1465,241 @@ impl D2DRenderer {
     }
 }
 
+/// BGRA・乗算済みαのレンダーターゲット設定（dpi 0.0 はシステム既定）
+fn render_target_properties(dpi: f32) -> D2D1_RENDER_TARGET_PROPERTIES {
+    D2D1_RENDER_TARGET_PROPERTIES {
+        r#type: D2D1_RENDER_TARGET_TYPE_DEFAULT,
+        pixelFormat: D2D1_PIXEL_FORMAT {
+            format: DXGI_FORMAT_B8G8R8A8_UNORM,
+            alphaMode: D2D1_ALPHA_MODE_PREMULTIPLIED,
+        },
+        dpiX: dpi,
+        dpiY: dpi,
+        usage: D2D1_RENDER_TARGET_USAGE_NONE,
+        minLevel: D2D1_FEATURE_LEVEL_DEFAULT,
+    }
+}
+
+unsafe fn create_dc_render_target(factory: &ID2D1Factory1) -> Result<ID2D1DCRenderTarget, RenderError> {
+    factory
+        .CreateDCRenderTarget(&render_target_properties(0.0))
+        .map_err(|e: windows::core::Error| RenderError::CreateFailed(e.to_string()))
+}
+
+unsafe fn solid_brush(
+    render_target: &ID2D1RenderTarget,
+    color: &str,
+) -> Result<ID2D1SolidColorBrush, RenderError> {
+    render_target
+        .CreateSolidColorBrush(&parse_color(color), None)
+        .map_err(|e: windows::core::Error| RenderError::CreateFailed(e.to_string()))
+}
+
+/// 描画エラーを分類する。ターゲットの再作成が必要なものだけ `DeviceLost` にする
+fn draw_error(e: windows::core::Error) -> RenderError {
+    if e.code() == D2DERR_RECREATE_TARGET {
+        RenderError::DeviceLost
+    } else {
+        RenderError::DrawFailed(e.to_string())
+    }
+}
+
+/// ショートカットラベル・連打カウントのフォントサイズ（本文に対する比率）
+const LABEL_FONT_RATIO: f32 = 0.85;
+const COUNT_FONT_RATIO: f32 = 0.75;
+
+/// テキスト計測結果のキャッシュ上限（表示中アイテムのラベル数に対して十分な大きさ）
+const TEXT_EXTENT_CACHE_CAPACITY: usize = 128;
+
+#[derive(Clone, Copy)]
+struct TextExtent {
+    width: f32,
+    height: f32,
+}
+
+/// (テキスト, フォーマット) → 計測サイズの LRU キャッシュ
+///
+/// アイテムは表示中に何フレームも同じラベルで描画されるため、毎フレームの
+/// `CreateTextLayout` を避ける。フォーマットは COM ポインタで識別するので、
+/// フォーマットを作り直すときは `clear` すること。
+struct TextExtentCache {
+    entries: Vec<TextExtentEntry>,
+    capacity: usize,
+    clock: u64,
+}
+
+struct TextExtentEntry {
+    text: String,
+    format_key: usize,
+    extent: TextExtent,
+    last_used: u64,
+}
+
+impl TextExtentCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            entries: Vec::with_capacity(capacity),
+            capacity,
+            clock: 0,
+        }
+    }
+
+    fn get(&mut self, text: &str, format_key: usize) -> Option<TextExtent> {
+        self.clock += 1;
+        let entry = self
+            .entries
+            .iter_mut()
+            .find(|e| e.format_key == format_key && e.text == text)?;
+        entry.last_used = self.clock;
+        Some(entry.extent)
+    }
+
+    fn insert(&mut self, text: &str, format_key: usize, extent: TextExtent) {
+        self.clock += 1;
+        let entry = TextExtentEntry {
+            text: text.to_string(),
+            format_key,
+            extent,
+            last_used: self.clock,
+        };
+        if self.entries.len() < self.capacity {
+            self.entries.push(entry);
+        } else if let Some(oldest) = self.entries.iter_mut().min_by_key(|e| e.last_used) {
+            *oldest = entry;
+        }
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+unsafe fn create_text_format(
+    dwrite_factory: &IDWriteFactory,
+    font_wide: &[u16],
+    font_collection: Option<&IDWriteFontCollection>,
+    font_fallback: Option<&IDWriteFontFallback>,
+    weight: DWRITE_FONT_WEIGHT,
+    font_style: DWRITE_FONT_STYLE,
+    size: f32,
+) -> Result<IDWriteTextFormat, RenderError> {
+    let format = dwrite_factory
+        .CreateTextFormat(
+            PCWSTR(font_wide.as_ptr()),
+            font_collection,
+            weight,
+            font_style,
+            DWRITE_FONT_STRETCH_NORMAL,
+            size,
+            w!("ja-JP"),
+        )
+        .map_err(|e: windows::core::Error| RenderError::CreateFailed(e.to_string()))?;
+    // 行矩形の中で縦方向中央に配置する（大きいフォントサイズでも上寄りにならない）
+    let _ = format.SetParagraphAlignment(DWRITE_PARAGRAPH_ALIGNMENT_CENTER);
+    // IDWriteTextFormat1 は Windows 8.1 以降。取れなければシステム既定の代替に任せる
+    if let (Some(fallback), Ok(format1)) = (font_fallback, format.cast::<IDWriteTextFormat1>()) {
+        let _ = format1.SetFontFallback(fallback);
+    }
+    Ok(format)
+}
+
+/// 代替フォントの対象にする CJK の範囲（記号・かな・漢字・ハングル・全角形）
+const CJK_RANGES: [DWRITE_UNICODE_RANGE; 6] = [
+    DWRITE_UNICODE_RANGE { first: 0x2E80, last: 0x2FDF },
+    DWRITE_UNICODE_RANGE { first: 0x3000, last: 0x9FFF },
+    DWRITE_UNICODE_RANGE { first: 0xAC00, last: 0xD7AF },
+    DWRITE_UNICODE_RANGE { first: 0xF900, last: 0xFAFF },
+    DWRITE_UNICODE_RANGE { first: 0xFF00, last: 0xFFEF },
+    DWRITE_UNICODE_RANGE { first: 0x20000, last: 0x3FFFF },
+];
+
+/// CJK を `family` に割り当て、それ以外はシステム既定の代替に続けるフォールバック
+///
+/// `family` が空、または IDWriteFactory2 が使えない環境では None（システム既定のまま）。
+unsafe fn create_font_fallback(dwrite_factory: &IDWriteFactory, family: &str) -> Option<IDWriteFontFallback> {
+    if family.trim().is_empty() {
+        return None;
+    }
+    let factory2 = dwrite_factory.cast::<IDWriteFactory2>().ok()?;
+    let builder = factory2.CreateFontFallbackBuilder().ok()?;
+    let family_wide = to_wide(family.trim());
+    builder
+        .AddMapping(&CJK_RANGES, &[family_wide.as_ptr()], None, None, None, 1.0)
+        .ok()?;
+    if let Ok(system) = factory2.GetSystemFontFallback() {
+        let _ = builder.AddMappings(&system);
+    }
+    builder.CreateFontFallback().ok()
+}
+
+fn text_draw_options(style: &StyleConfig) -> D2D1_DRAW_TEXT_OPTIONS {
+    if style.enable_color_fonts {
+        D2D1_DRAW_TEXT_OPTIONS_ENABLE_COLOR_FONT
+    } else {
+        D2D1_DRAW_TEXT_OPTIONS_NONE
+    }
+}
+
+fn font_style(italic: bool) -> DWRITE_FONT_STYLE {
+    if italic {
+        DWRITE_FONT_STYLE_ITALIC
+    } else {
+        DWRITE_FONT_STYLE_NORMAL
+    }
+}
+
+/// フォントファイルから IDWriteFontSetBuilder 経由でフォントコレクションを作成。
+/// IDWriteFactory5 非対応環境やファイル読み込み失敗時は None（システムフォント使用）。
+fn load_custom_font_collection(
+    dwrite_factory: &IDWriteFactory,
+    path: &str,
+) -> Option<IDWriteFontCollection> {
+    if path.trim().is_empty() {
+        return None;
+    }
+    (|| -> windows::core::Result<IDWriteFontCollection> {
+        unsafe {
+            let factory5: IDWriteFactory5 = dwrite_factory.cast()?;
+            let builder = factory5.CreateFontSetBuilder()?;
+            let path_wide = to_wide(path);
+            let font_file = factory5.CreateFontFileReference(PCWSTR(path_wide.as_ptr()), None)?;
+            builder.AddFontFile(&font_file)?;
+            let font_set = builder.CreateFontSet()?;
+            let collection = factory5.CreateFontCollectionFromFontSet(&font_set)?;
+            collection.cast()
+        }
+    })()
+    .ok()
+}
+
+/// コレクション内に `font_family` がなければ先頭ファミリー名を使う
+fn resolve_font_family(collection: Option<&IDWriteFontCollection>, font_family: &str) -> String {
+    let Some(collection) = collection else {
+        return font_family.to_string();
+    };
+    unsafe {
+        let family_wide = to_wide(font_family);
+        let mut index = 0u32;
+        let mut exists = BOOL(0);
+        if collection
+            .FindFamilyName(PCWSTR(family_wide.as_ptr()), &mut index, &mut exists)
+            .is_ok()
+            && exists.as_bool()
+        {
+            return font_family.to_string();
+        }
+
+        let first_name = (|| -> windows::core::Result<String> {
+            let names = collection.GetFontFamily(0)?.GetFamilyNames()?;
+            let len = names.GetStringLength(0)? as usize;
+            let mut buf = vec![0u16; len + 1];
+            names.GetString(0, &mut buf)?;
+            Ok(String::from_utf16_lossy(&buf[..len]))
+        })();
+        first_name.unwrap_or_else(|_| font_family.to_string())
+    }
+}
+
 /// 連打カウントなしのテキスト生成
 fn format_item_text_no_count(kind: &DisplayItemKind) -> String {
     match kind {
@@ -901,6 +1715,9 @@ fn format_item_text_no_count(kind: &DisplayItemKind) -> String {
             if modifiers.alt {
                 s.push_str("Alt+");
             }
+            if modifiers.altgr {
+                s.push_str("AltGr+");
+            }
             if modifiers.shift {
                 s.push_str("Shift+");
             }
@@ -914,7 +1731,8 @@ fn format_item_text_no_count(kind: &DisplayItemKind) -> String {
     }
 }
 
-fn format_item_text(kind: &DisplayItemKind) -> String {
+/// アイテムの表示テキスト（OSD に描画される文字列）
+pub fn format_item_text(kind: &DisplayItemKind) -> String {
     match kind {
         DisplayItemKind::KeyStroke {
             label,
@@ -929,6 +1747,9 @@ fn format_item_text(kind: &DisplayItemKind) -> String {
             if modifiers.alt {
                 s.push_str("Alt+");
             }
+            if modifiers.altgr {
+                s.push_str("AltGr+");
+            }
             if modifiers.shift {
                 s.push_str("Shift+");
             }
@@ -955,8 +1776,25 @@ fn format_item_text(kind: &DisplayItemKind) -> String {
             format!("{} ({})", keys_label, action_label)
         }
         DisplayItemKind::ImeComposition { text } => text.clone(),
+        DisplayItemKind::ImeCandidateList { entries, selected } => {
+            // 選択中の候補だけを括弧で囲み、1 行に並べる
+            entries
+                .iter()
+                .enumerate()
+                .map(|(i, entry)| {
+                    if i == *selected {
+                        format!("[{}]", entry)
+                    } else {
+                        entry.clone()
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        }
         DisplayItemKind::ClipboardPreview { text } => {
-            format!("[Clipboard] {}", text)
+            // 埋め込み改行は行矩形をはみ出すので空白に置換
+            let single_line = text.replace("\r\n", " ").replace(['\r', '\n'], " ");
+            format!("[Clipboard] {}", single_line)
         }
         DisplayItemKind::LockIndicator { caps, num, scroll } => {
             let mut parts = Vec::new();
@@ -971,6 +1809,28 @@ fn format_item_text(kind: &DisplayItemKind) -> String {
             }
             parts.join(" | ")
         }
+        DisplayItemKind::GamepadInput { label } => label.clone(),
+        DisplayItemKind::ModifierState {
+            ctrl,
+            shift,
+            alt,
+            win,
+        } => {
+            let mut parts = Vec::new();
+            if *ctrl {
+                parts.push("Ctrl");
+            }
+            if *shift {
+                parts.push("Shift");
+            }
+            if *alt {
+                parts.push("Alt");
+            }
+            if *win {
+                parts.push("Win");
+            }
+            parts.join(" + ")
+        }
     }
 }
 
@@ -983,6 +1843,9 @@ fn format_entry_text(entry: &KeyStrokeEntry) -> String {
     if entry.modifiers.alt {
         s.push_str("Alt+");
     }
+    if entry.modifiers.altgr {
+        s.push_str("AltGr+");
+    }
     if entry.modifiers.shift {
         s.push_str("Shift+");
     }
@@ -1023,7 +1886,95 @@ pub fn parse_color(hex: &str) -> D2D1_COLOR_F {
     }
 }
 
+/// HSL の明度を delta だけ増減した色を返す（アルファはそのまま）
+fn adjust_lightness(color: &D2D1_COLOR_F, delta: f32) -> D2D1_COLOR_F {
+    let max = color.r.max(color.g).max(color.b);
+    let min = color.r.min(color.g).min(color.b);
+    let l = (max + min) / 2.0;
+    let d = max - min;
+
+    let (h, sat) = if d == 0.0 {
+        (0.0, 0.0)
+    } else {
+        let sat = if l > 0.5 { d / (2.0 - max - min) } else { d / (max + min) };
+        let h = if max == color.r {
+            ((color.g - color.b) / d).rem_euclid(6.0)
+        } else if max == color.g {
+            (color.b - color.r) / d + 2.0
+        } else {
+            (color.r - color.g) / d + 4.0
+        };
+        (h / 6.0, sat)
+    };
+
+    let l = (l + delta).clamp(0.0, 1.0);
+    let q = if l < 0.5 { l * (1.0 + sat) } else { l + sat - l * sat };
+    let p = 2.0 * l - q;
+    let hue_to_rgb = |t: f32| {
+        let t = t.rem_euclid(1.0);
+        if t < 1.0 / 6.0 {
+            p + (q - p) * 6.0 * t
+        } else if t < 0.5 {
+            q
+        } else if t < 2.0 / 3.0 {
+            p + (q - p) * (2.0 / 3.0 - t) * 6.0
+        } else {
+            p
+        }
+    };
+
+    D2D1_COLOR_F {
+        r: hue_to_rgb(h + 1.0 / 3.0),
+        g: hue_to_rgb(h),
+        b: hue_to_rgb(h - 1.0 / 3.0),
+        a: color.a,
+    }
+}
+
 /// &strをnull終端UTF-16に変換
 pub fn to_wide(s: &str) -> Vec<u16> {
     s.encode_utf16().chain(std::iter::once(0)).collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+    use ystrokey_core::{DisplayPhase, Modifiers};
+
+    fn key_item(label: &str) -> DisplayItem {
+        DisplayItem {
+            id: 1,
+            kind: DisplayItemKind::KeyStroke {
+                label: label.to_string(),
+                modifiers: Modifiers::default(),
+                action: KeyAction::Down,
+                repeat_count: 1,
+            },
+            created_at: Instant::now(),
+            opacity: 1.0,
+            phase: DisplayPhase::Active,
+            pinned: false,
+        }
+    }
+
+    #[test]
+    fn render_to_wic_draws_single_keystroke_in_bottom_row() {
+        let style = StyleConfig::default();
+        let (width, height) = (320u32, 200u32);
+        let pixels = D2DRenderer::render_to_wic(&[key_item("A")], &style, width, height).unwrap();
+        assert_eq!(pixels.len(), (width * height * 4) as usize);
+
+        let row_has_pixels = |y: u32| {
+            let start = (y * width * 4) as usize;
+            pixels[start..start + (width * 4) as usize]
+                .chunks_exact(4)
+                .any(|px| px[3] != 0)
+        };
+        // 縦並びのライブアイテムは最下段に 1 行分の高さで描画される
+        let line_height = style.font_size + style.padding * 2.0;
+        let item_top = (height as f32 - line_height).floor() as u32;
+        assert!((item_top..height).any(row_has_pixels));
+        assert!(!(0..item_top.saturating_sub(4)).any(row_has_pixels));
+    }
+}
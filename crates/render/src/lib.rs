@@ -1,5 +1,12 @@
 pub mod d2d;
+pub mod multi;
+pub mod png;
 pub mod window;
 
-pub use d2d::D2DRenderer;
-pub use window::{get_monitor_device_name, OsdWindow};
+pub use d2d::{format_item_text, D2DRenderer};
+pub use multi::MultiOsdManager;
+pub use png::save_png;
+pub use window::{
+    connected_monitor_names, cursor_monitor, get_monitor_device_name, snap_rect_to_work_area,
+    work_area_fraction, OsdWindow,
+};
@@ -0,0 +1,245 @@
+use windows::Win32::Foundation::*;
+use windows::Win32::Graphics::Gdi::*;
+use windows::Win32::UI::WindowsAndMessaging::*;
+
+use ystrokey_core::{AppConfig, DisplayConfig, FollowMode, RenderError};
+
+use crate::d2d::D2DRenderer;
+use crate::window::{
+    cursor_monitor, enumerate_monitors, get_monitor_device_name, monitor_wnd_proc, OsdWindow,
+};
+
+type WndProc = unsafe extern "system" fn(HWND, u32, WPARAM, LPARAM) -> LRESULT;
+
+/// モニタ 1 枚分の OSD
+struct MonitorOsd {
+    monitor: HMONITOR,
+    window: OsdWindow,
+    renderer: D2DRenderer,
+}
+
+/// 物理モニタごとに OsdWindow と D2DRenderer を持ち、アクティブなモニタの分だけ表示する
+///
+/// 先頭はプライマリ（ホスト）ウィンドウで、トレイ・ホットキー・クリップボード通知の
+/// 受け口を兼ねるため破棄しない。モニタ構成が変わったら `refresh_monitors` で追従する。
+pub struct MultiOsdManager {
+    entries: Vec<MonitorOsd>,
+    active: HMONITOR,
+    wnd_proc: Option<WndProc>,
+    /// 対象ウィンドウが `display.enabled_monitors` 外のモニタにあり、全 OSD を隠している
+    suppressed: bool,
+}
+
+impl MultiOsdManager {
+    /// ホストウィンドウだけを作成する（追加モニタ分は `refresh_monitors` で作る）
+    pub fn new(config: &AppConfig) -> Result<Self, RenderError> {
+        let window = OsdWindow::create(
+            config.performance.osd_width,
+            config.performance.osd_height,
+            &config.display,
+        )?;
+        let renderer = create_renderer(&window, config)?;
+        let monitor = unsafe { MonitorFromWindow(window.hwnd(), MONITOR_DEFAULTTOPRIMARY) };
+        Ok(Self {
+            entries: vec![MonitorOsd {
+                monitor,
+                window,
+                renderer,
+            }],
+            active: monitor,
+            wnd_proc: None,
+            suppressed: false,
+        })
+    }
+
+    /// 全ウィンドウ（今後作るものを含む）のウィンドウプロシージャを差し替える
+    pub fn set_wnd_proc(&mut self, proc: WndProc) {
+        self.wnd_proc = Some(proc);
+        for entry in &self.entries {
+            subclass(&entry.window, proc);
+        }
+    }
+
+    /// メッセージの受け口になるホストウィンドウ
+    pub fn host(&self) -> &OsdWindow {
+        &self.entries[0].window
+    }
+
+    fn active_index(&self) -> usize {
+        self.entries
+            .iter()
+            .position(|e| e.monitor == self.active)
+            .unwrap_or(0)
+    }
+
+    pub fn active(&self) -> (&OsdWindow, &D2DRenderer) {
+        let entry = &self.entries[self.active_index()];
+        (&entry.window, &entry.renderer)
+    }
+
+    pub fn active_mut(&mut self) -> (&mut OsdWindow, &mut D2DRenderer) {
+        let index = self.active_index();
+        let entry = &mut self.entries[index];
+        (&mut entry.window, &mut entry.renderer)
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&mut OsdWindow, &mut D2DRenderer)> {
+        self.entries
+            .iter_mut()
+            .map(|e| (&mut e.window, &mut e.renderer))
+    }
+
+    /// 対象ウィンドウのモニタの OSD を表示して配置し、他のモニタの OSD は隠す
+    pub fn reposition_to_monitor(&mut self, hwnd_target: HWND, display_config: &DisplayConfig) {
+        let monitor = unsafe { MonitorFromWindow(hwnd_target, MONITOR_DEFAULTTONEAREST) };
+        self.reposition_on_monitor(monitor, display_config);
+    }
+
+    /// `display.follow` で決まるモニタへ配置し直す（`Fixed` は今のモニタのまま位置だけ反映）
+    pub fn reposition_follow(&mut self, display_config: &DisplayConfig) {
+        let monitor = match display_config.follow {
+            FollowMode::ForegroundWindow => {
+                let fg = unsafe { GetForegroundWindow() };
+                if fg.0.is_null() {
+                    return;
+                }
+                unsafe { MonitorFromWindow(fg, MONITOR_DEFAULTTONEAREST) }
+            }
+            FollowMode::MouseCursor => match cursor_monitor() {
+                Some(monitor) => monitor,
+                None => return,
+            },
+            FollowMode::Fixed => self.active,
+        };
+        self.reposition_on_monitor(monitor, display_config);
+    }
+
+    /// 指定モニタの OSD を表示して配置し、他のモニタの OSD は隠す
+    ///
+    /// `display.enabled_monitors` に含まれないモニタなら、どのモニタにも表示しない。
+    pub fn reposition_on_monitor(&mut self, monitor: HMONITOR, display_config: &DisplayConfig) {
+        let enabled = display_config.enabled_monitors.is_empty()
+            || get_monitor_device_name(monitor)
+                .is_some_and(|name| display_config.enabled_monitors.contains(&name));
+        if !enabled {
+            if !self.suppressed {
+                self.suppressed = true;
+                self.apply_visibility();
+            }
+            return;
+        }
+        // 列挙前のモニタなら、今のアクティブなウィンドウをそのまま移動させる
+        let switch = self.entries.iter().any(|e| e.monitor == monitor) && monitor != self.active;
+        if switch {
+            self.active = monitor;
+        }
+        if switch || self.suppressed {
+            self.suppressed = false;
+            self.apply_visibility();
+        }
+        let index = self.active_index();
+        self.entries[index]
+            .window
+            .reposition_on_monitor(monitor, display_config);
+    }
+
+    /// WM_DPICHANGED の推奨矩形が載っているモニタのウィンドウへ DPI 変更を反映する
+    pub fn update_for_dpi(&mut self, dpi: u32, suggested_rect: &RECT) {
+        let monitor = unsafe { MonitorFromRect(suggested_rect, MONITOR_DEFAULTTONEAREST) };
+        let index = self
+            .entries
+            .iter()
+            .position(|e| e.monitor == monitor)
+            .unwrap_or_else(|| self.active_index());
+        let entry = &mut self.entries[index];
+        entry.window.update_for_dpi(dpi, suggested_rect);
+        entry.renderer.update_dpi(dpi);
+    }
+
+    /// モニタを列挙し直し、増えたモニタのウィンドウを作成・消えたモニタのウィンドウを破棄する
+    pub fn refresh_monitors(&mut self, config: &AppConfig) -> Result<(), RenderError> {
+        let monitors = enumerate_monitors();
+        if monitors.is_empty() {
+            return Ok(());
+        }
+
+        // ホストが載っていたモニタが消えたら、OS が移動させた先のモニタへ付け替える
+        if !monitors.contains(&self.entries[0].monitor) {
+            let nearest =
+                unsafe { MonitorFromWindow(self.entries[0].window.hwnd(), MONITOR_DEFAULTTONEAREST) };
+            self.entries[0].monitor = if monitors.contains(&nearest) {
+                nearest
+            } else {
+                monitors[0]
+            };
+        }
+
+        let host_monitor = self.entries[0].monitor;
+        let (kept, removed): (Vec<_>, Vec<_>) = std::mem::take(&mut self.entries)
+            .into_iter()
+            .enumerate()
+            .partition(|(index, e)| {
+                *index == 0 || (e.monitor != host_monitor && monitors.contains(&e.monitor))
+            });
+        self.entries = kept.into_iter().map(|(_, e)| e).collect();
+        for (_, entry) in removed {
+            // 差し替えたプロシージャは WM_DESTROY で終了してしまうので戻してから破棄する
+            subclass(&entry.window, monitor_wnd_proc);
+        }
+
+        let mut result = Ok(());
+        for monitor in monitors {
+            if self.entries.iter().any(|e| e.monitor == monitor) {
+                continue;
+            }
+            match self.create_entry(monitor, config) {
+                Ok(entry) => self.entries.push(entry),
+                Err(e) => result = Err(e),
+            }
+        }
+
+        if !self.entries.iter().any(|e| e.monitor == self.active) {
+            self.active = host_monitor;
+        }
+        self.apply_visibility();
+        result
+    }
+
+    fn create_entry(&self, monitor: HMONITOR, config: &AppConfig) -> Result<MonitorOsd, RenderError> {
+        let window = OsdWindow::create_on_monitor(
+            config.performance.osd_width,
+            config.performance.osd_height,
+            &config.display,
+            monitor,
+        )?;
+        window.set_display_affinity(config.behavior.exclude_from_capture);
+        if let Some(proc) = self.wnd_proc {
+            subclass(&window, proc);
+        }
+        let renderer = create_renderer(&window, config)?;
+        Ok(MonitorOsd {
+            monitor,
+            window,
+            renderer,
+        })
+    }
+
+    fn apply_visibility(&self) {
+        for entry in &self.entries {
+            entry.window.set_visible(!self.suppressed && entry.monitor == self.active);
+        }
+    }
+}
+
+fn create_renderer(window: &OsdWindow, config: &AppConfig) -> Result<D2DRenderer, RenderError> {
+    let mut renderer = D2DRenderer::new(&config.style)?;
+    renderer.update_dpi(window.dpi);
+    renderer.set_layout(config.display.layout);
+    Ok(renderer)
+}
+
+fn subclass(window: &OsdWindow, proc: WndProc) {
+    unsafe {
+        SetWindowLongPtrW(window.hwnd(), GWL_WNDPROC, proc as usize as isize);
+    }
+}
@@ -0,0 +1,130 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+use flate2::write::ZlibEncoder;
+use flate2::{Compression, Crc};
+
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+
+/// 32bit DIB のピクセル列（BGRA・乗算済みα・上→下）を PNG として保存
+pub fn save_png(bits: &[u8], width: u32, height: u32, path: &Path) -> io::Result<()> {
+    let data = encode_png(bits, width, height)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, data)
+}
+
+fn encode_png(bits: &[u8], width: u32, height: u32) -> io::Result<Vec<u8>> {
+    let row_bytes = width as usize * 4;
+    if bits.len() < row_bytes * height as usize {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "bitmap is smaller than width * height"));
+    }
+
+    // 各行の先頭にフィルタ種別 0 (None) を付けた RGBA（非乗算α）へ変換
+    let mut raw = Vec::with_capacity((row_bytes + 1) * height as usize);
+    for row in bits.chunks_exact(row_bytes).take(height as usize) {
+        raw.push(0);
+        for px in row.chunks_exact(4) {
+            raw.extend_from_slice(&unpremultiply([px[2], px[1], px[0], px[3]]));
+        }
+    }
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&raw)?;
+    let idat = encoder.finish()?;
+
+    let mut ihdr = Vec::with_capacity(13);
+    ihdr.extend_from_slice(&width.to_be_bytes());
+    ihdr.extend_from_slice(&height.to_be_bytes());
+    // bit depth 8 / color type 6 (RGBA) / compression 0 / filter 0 / interlace 0
+    ihdr.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+    let mut png = PNG_SIGNATURE.to_vec();
+    write_chunk(&mut png, b"IHDR", &ihdr);
+    write_chunk(&mut png, b"IDAT", &idat);
+    write_chunk(&mut png, b"IEND", &[]);
+    Ok(png)
+}
+
+fn write_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let mut crc = Crc::new();
+    crc.update(kind);
+    crc.update(data);
+    out.extend_from_slice(&crc.sum().to_be_bytes());
+}
+
+/// D2D の乗算済みαを PNG の非乗算αへ戻す
+pub(crate) fn unpremultiply([r, g, b, a]: [u8; 4]) -> [u8; 4] {
+    if a == 0 || a == 255 {
+        return [r, g, b, a];
+    }
+    let scale = |c: u8| ((c as u32 * 255 + a as u32 / 2) / a as u32).min(255) as u8;
+    [scale(r), scale(g), scale(b), a]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::ZlibDecoder;
+    use std::io::Read;
+
+    /// チャンクを (種別, データ) で取り出し、CRC も確かめる
+    fn chunks(png: &[u8]) -> Vec<([u8; 4], Vec<u8>)> {
+        assert_eq!(&png[..8], &PNG_SIGNATURE);
+        let mut out = Vec::new();
+        let mut rest = &png[8..];
+        while !rest.is_empty() {
+            let len = u32::from_be_bytes(rest[..4].try_into().unwrap()) as usize;
+            let kind: [u8; 4] = rest[4..8].try_into().unwrap();
+            let data = rest[8..8 + len].to_vec();
+            let mut crc = Crc::new();
+            crc.update(&kind);
+            crc.update(&data);
+            assert_eq!(rest[8 + len..12 + len], crc.sum().to_be_bytes());
+            out.push((kind, data));
+            rest = &rest[12 + len..];
+        }
+        out
+    }
+
+    #[test]
+    fn unpremultiply_restores_straight_alpha() {
+        assert_eq!(unpremultiply([0, 0, 0, 0]), [0, 0, 0, 0]);
+        assert_eq!(unpremultiply([10, 20, 30, 0])[3], 0);
+        assert_eq!(unpremultiply([1, 2, 3, 255]), [1, 2, 3, 255]);
+        assert_eq!(unpremultiply([64, 32, 128, 128]), [128, 64, 255, 128]);
+    }
+
+    #[test]
+    fn encode_png_writes_rgba_rows() {
+        // 2x2 の BGRA（乗算済み）: 赤・半透明の緑 / 透明・白
+        let bits = [
+            0, 0, 255, 255, 0, 64, 0, 128, //
+            0, 0, 0, 0, 255, 255, 255, 255,
+        ];
+        let png = encode_png(&bits, 2, 2).unwrap();
+        let chunks = chunks(&png);
+        let kinds: Vec<&[u8; 4]> = chunks.iter().map(|(kind, _)| kind).collect();
+        assert_eq!(kinds, [b"IHDR", b"IDAT", b"IEND"]);
+        assert_eq!(chunks[0].1, [0, 0, 0, 2, 0, 0, 0, 2, 8, 6, 0, 0, 0]);
+
+        let mut raw = Vec::new();
+        ZlibDecoder::new(&chunks[1].1[..]).read_to_end(&mut raw).unwrap();
+        assert_eq!(
+            raw,
+            [
+                0, 255, 0, 0, 255, 0, 128, 0, 128, //
+                0, 0, 0, 0, 0, 255, 255, 255, 255,
+            ]
+        );
+
+        assert_eq!(
+            encode_png(&bits[..8], 2, 2).unwrap_err().kind(),
+            io::ErrorKind::InvalidInput
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use std::mem;
 
-use windows::core::w;
+use windows::core::{w, PCWSTR};
 use windows::Win32::Foundation::*;
 use windows::Win32::Graphics::Gdi::*;
 use windows::Win32::System::LibraryLoader::GetModuleHandleW;
@@ -23,61 +23,87 @@ pub struct OsdWindow {
 impl OsdWindow {
     pub fn create(width: i32, height: i32, display_config: &DisplayConfig) -> Result<Self, RenderError> {
         unsafe {
-            let instance = GetModuleHandleW(None)
-                .map_err(|e| RenderError::CreateFailed(e.to_string()))?;
-
-            let class_name = w!("yStrokeyOSD");
-            let wc = WNDCLASSEXW {
-                cbSize: mem::size_of::<WNDCLASSEXW>() as u32,
-                lpfnWndProc: Some(wnd_proc),
-                hInstance: HINSTANCE(instance.0),
-                lpszClassName: class_name,
-                ..Default::default()
-            };
-            RegisterClassExW(&wc);
             // Primary monitor work area
             let (x, y) = get_primary_monitor_position(width, height, display_config);
+            let window = Self::create_at(w!("yStrokeyOSD"), wnd_proc, x, y, width, height)?;
+            let _ = ShowWindow(window.hwnd, SW_SHOWNOACTIVATE);
+            Ok(window)
+        }
+    }
 
-            let ex_style = WS_EX_LAYERED
-                | WS_EX_TOPMOST
-                | WS_EX_TRANSPARENT
-                | WS_EX_NOACTIVATE
-                | WS_EX_TOOLWINDOW;
-
-            let hwnd = CreateWindowExW(
-                ex_style,
-                class_name,
-                w!("yStrokey"),
-                WS_POPUP,
-                x,
-                y,
-                width,
-                height,
-                None,
-                None,
-                HINSTANCE(instance.0),
-                None,
-            )
+    /// 指定モニタ用の追加 OSD ウィンドウを非表示で作成する
+    ///
+    /// 破棄してもアプリを終了させないよう、プライマリとは別のウィンドウクラスを使う。
+    pub fn create_on_monitor(
+        width: i32,
+        height: i32,
+        display_config: &DisplayConfig,
+        hmon: HMONITOR,
+    ) -> Result<Self, RenderError> {
+        unsafe {
+            let (x, y) = get_monitor_position(hmon, width, height, display_config)
+                .unwrap_or_else(|| get_primary_monitor_position(width, height, display_config));
+            Self::create_at(w!("yStrokeyOSDMonitor"), monitor_wnd_proc, x, y, width, height)
+        }
+    }
+
+    unsafe fn create_at(
+        class_name: PCWSTR,
+        proc: unsafe extern "system" fn(HWND, u32, WPARAM, LPARAM) -> LRESULT,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+    ) -> Result<Self, RenderError> {
+        let instance = GetModuleHandleW(None)
             .map_err(|e| RenderError::CreateFailed(e.to_string()))?;
 
-            let _ = ShowWindow(hwnd, SW_SHOWNOACTIVATE);
+        let wc = WNDCLASSEXW {
+            cbSize: mem::size_of::<WNDCLASSEXW>() as u32,
+            lpfnWndProc: Some(proc),
+            hInstance: HINSTANCE(instance.0),
+            lpszClassName: class_name,
+            ..Default::default()
+        };
+        RegisterClassExW(&wc);
+
+        let ex_style = WS_EX_LAYERED
+            | WS_EX_TOPMOST
+            | WS_EX_TRANSPARENT
+            | WS_EX_NOACTIVATE
+            | WS_EX_TOOLWINDOW;
+
+        let hwnd = CreateWindowExW(
+            ex_style,
+            class_name,
+            w!("yStrokey"),
+            WS_POPUP,
+            x,
+            y,
+            width,
+            height,
+            None,
+            None,
+            HINSTANCE(instance.0),
+            None,
+        )
+        .map_err(|e| RenderError::CreateFailed(e.to_string()))?;
 
-            let dpi = GetDpiForWindow(hwnd);
-            let dpi = if dpi == 0 { 96 } else { dpi };
+        let dpi = GetDpiForWindow(hwnd);
+        let dpi = if dpi == 0 { 96 } else { dpi };
 
-            // DIBセクション + メモリDC作成
-            let (mem_dc, dib_bitmap, old_bitmap) = create_dib(width, height)?;
+        // DIBセクション + メモリDC作成
+        let (mem_dc, dib_bitmap, old_bitmap, _) = create_dib(width, height)?;
 
-            Ok(Self {
-                hwnd,
-                width,
-                height,
-                dpi,
-                mem_dc,
-                dib_bitmap,
-                old_bitmap,
-            })
-        }
+        Ok(Self {
+            hwnd,
+            width,
+            height,
+            dpi,
+            mem_dc,
+            dib_bitmap,
+            old_bitmap,
+        })
     }
 
     pub fn hwnd(&self) -> HWND {
@@ -161,6 +187,13 @@ impl OsdWindow {
         }
     }
 
+    /// 複数モニタ運用時の表示切替（フォーカスは奪わない）
+    pub fn set_visible(&self, visible: bool) {
+        unsafe {
+            let _ = ShowWindow(self.hwnd, if visible { SW_SHOWNOACTIVATE } else { SW_HIDE });
+        }
+    }
+
     pub fn set_position(&self, x: i32, y: i32) {
         unsafe {
             let _ = SetWindowPos(
@@ -212,6 +245,16 @@ impl OsdWindow {
         }
     }
 
+    /// 大きさを変え、今載っているモニタ上で配置し直す（アンカー側の辺を保つ）
+    pub fn resize_in_place(&mut self, width: i32, height: i32, display_config: &DisplayConfig) {
+        if width == self.width && height == self.height {
+            return;
+        }
+        self.resize(width, height);
+        let hmon = unsafe { MonitorFromWindow(self.hwnd, MONITOR_DEFAULTTONEAREST) };
+        self.reposition_on_monitor(hmon, display_config);
+    }
+
     pub fn update_for_dpi(&mut self, dpi: u32, suggested_rect: &RECT) {
         self.dpi = dpi;
         let new_w = suggested_rect.right - suggested_rect.left;
@@ -227,8 +270,23 @@ impl OsdWindow {
         hwnd_target: HWND,
         display_config: &DisplayConfig,
     ) {
+        let hmon = unsafe { MonitorFromWindow(hwnd_target, MONITOR_DEFAULTTONEAREST) };
+        self.reposition_on_monitor(hmon, display_config);
+    }
+
+    /// `(x, y)` に置いたときの中心が、接続中のモニタのうち `hmon` 上にあるか
+    fn is_on_monitor(&self, hmon: HMONITOR, x: i32, y: i32) -> bool {
+        let center = POINT {
+            x: x + self.width / 2,
+            y: y + self.height / 2,
+        };
+        let found = unsafe { MonitorFromPoint(center, MONITOR_DEFAULTTONULL) };
+        !found.is_invalid() && found == hmon
+    }
+
+    /// 指定モニタの作業領域内へ OSD を配置する（位置の決め方は `reposition_to_monitor` と同じ）
+    pub fn reposition_on_monitor(&self, hmon: HMONITOR, display_config: &DisplayConfig) {
         unsafe {
-            let hmon = MonitorFromWindow(hwnd_target, MONITOR_DEFAULTTONEAREST);
             let mut mi = MONITORINFOEXW::default();
             mi.monitorInfo.cbSize = mem::size_of::<MONITORINFOEXW>() as u32;
             if GetMonitorInfoW(hmon, &mut mi as *mut _ as *mut MONITORINFO).as_bool() {
@@ -241,8 +299,24 @@ impl OsdWindow {
                 let device_name = String::from_utf16_lossy(&name_slice[..len]);
 
                 let work = mi.monitorInfo.rcWork;
-                let (x, y) = if let Some(&[x, y]) = display_config.monitor_positions.get(&device_name) {
-                    // 保存済み位置を使用
+                let (x, y) = if let Some(&[x_pct, y_pct]) =
+                    display_config.monitor_relative_positions.get(&device_name)
+                {
+                    // 割合で保存した位置を今の作業領域と OSD サイズで px に戻す
+                    compute_position(
+                        &Position::Custom { x_pct, y_pct },
+                        &work,
+                        self.width,
+                        self.height,
+                        0,
+                        0,
+                    )
+                } else if let Some(&[x, y]) = display_config
+                    .monitor_positions
+                    .get(&device_name)
+                    .filter(|&&[x, y]| self.is_on_monitor(hmon, x, y))
+                {
+                    // 保存済み位置を使用（モニタの配置が変わって外れた位置は捨てて既定位置へ）
                     (x, y)
                 } else {
                     // 設定ベースの位置計算
@@ -282,11 +356,11 @@ impl Drop for OsdWindow {
     }
 }
 
-/// 32bit ARGB top-down DIBセクションとメモリDCを作成
-unsafe fn create_dib(
+/// 32bit ARGB top-down DIBセクションとメモリDCを作成（最後の要素はピクセル領域）
+pub(crate) unsafe fn create_dib(
     width: i32,
     height: i32,
-) -> Result<(HDC, HBITMAP, HGDIOBJ), RenderError> {
+) -> Result<(HDC, HBITMAP, HGDIOBJ, *mut std::ffi::c_void), RenderError> {
     let mem_dc = CreateCompatibleDC(HDC::default());
 
     let bmi = create_bitmapinfo(width, height);
@@ -303,7 +377,7 @@ unsafe fn create_dib(
 
     let old_bitmap = SelectObject(mem_dc, HGDIOBJ(dib_bitmap.0));
 
-    Ok((mem_dc, dib_bitmap, old_bitmap))
+    Ok((mem_dc, dib_bitmap, old_bitmap, bits))
 }
 
 fn create_bitmapinfo(width: i32, height: i32) -> BITMAPINFO {
@@ -339,6 +413,11 @@ fn compute_position(
         Position::BottomLeft => (work.left, work.top + mon_h - height),
         Position::BottomCenter => (work.left + (mon_w - width) / 2, work.top + mon_h - height),
         Position::BottomRight => (work.right - width, work.top + mon_h - height),
+        // 割合は OSD を置ける範囲（作業領域 - OSD サイズ）に対して掛ける
+        Position::Custom { x_pct, y_pct } => (
+            work.left + ((mon_w - width) as f32 * x_pct).round() as i32,
+            work.top + ((mon_h - height) as f32 * y_pct).round() as i32,
+        ),
     };
 
     (base_x + offset_x, base_y + offset_y)
@@ -348,11 +427,25 @@ fn compute_position(
 unsafe fn get_primary_monitor_position(width: i32, height: i32, display_config: &DisplayConfig) -> (i32, i32) {
     let pt = POINT { x: 0, y: 0 };
     let hmon = MonitorFromPoint(pt, MONITOR_DEFAULTTOPRIMARY);
+    get_monitor_position(hmon, width, height, display_config).unwrap_or_else(|| {
+        let screen_w = GetSystemMetrics(SM_CXSCREEN);
+        let screen_h = GetSystemMetrics(SM_CYSCREEN);
+        ((screen_w - width) / 2, screen_h - height - 48)
+    })
+}
+
+/// 指定モニタの作業領域から OSD の座標を計算
+unsafe fn get_monitor_position(
+    hmon: HMONITOR,
+    width: i32,
+    height: i32,
+    display_config: &DisplayConfig,
+) -> Option<(i32, i32)> {
     let mut mi = MONITORINFO {
         cbSize: mem::size_of::<MONITORINFO>() as u32,
         ..Default::default()
     };
-    if GetMonitorInfoW(hmon, &mut mi).as_bool() {
+    GetMonitorInfoW(hmon, &mut mi).as_bool().then(|| {
         compute_position(
             &display_config.position,
             &mi.rcWork,
@@ -361,11 +454,64 @@ unsafe fn get_primary_monitor_position(width: i32, height: i32, display_config:
             display_config.offset_x,
             display_config.offset_y,
         )
-    } else {
-        let screen_w = GetSystemMetrics(SM_CXSCREEN);
-        let screen_h = GetSystemMetrics(SM_CYSCREEN);
-        ((screen_w - width) / 2, screen_h - height - 48)
+    })
+}
+
+/// ウィンドウ矩形がモニタの作業領域内のどこにあるかを割合で返す（`Position::Custom` と同じ基準）
+pub fn work_area_fraction(hmon: HMONITOR, rect: &RECT) -> Option<[f32; 2]> {
+    let mut mi = MONITORINFO {
+        cbSize: mem::size_of::<MONITORINFO>() as u32,
+        ..Default::default()
+    };
+    if !unsafe { GetMonitorInfoW(hmon, &mut mi) }.as_bool() {
+        return None;
     }
+    let work = mi.rcWork;
+    // OSD を置ける範囲がなければ左端/上端扱い
+    let fraction = |offset: i32, extent: i32, size: i32| {
+        if extent > size {
+            (offset as f32 / (extent - size) as f32).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    };
+    Some([
+        fraction(
+            rect.left - work.left,
+            work.right - work.left,
+            rect.right - rect.left,
+        ),
+        fraction(
+            rect.top - work.top,
+            work.bottom - work.top,
+            rect.bottom - rect.top,
+        ),
+    ])
+}
+
+/// 接続中の全モニタを列挙する
+pub fn enumerate_monitors() -> Vec<HMONITOR> {
+    unsafe extern "system" fn collect(
+        hmon: HMONITOR,
+        _hdc: HDC,
+        _rect: *mut RECT,
+        data: LPARAM,
+    ) -> BOOL {
+        let monitors = &mut *(data.0 as *mut Vec<HMONITOR>);
+        monitors.push(hmon);
+        TRUE
+    }
+
+    let mut monitors: Vec<HMONITOR> = Vec::new();
+    unsafe {
+        let _ = EnumDisplayMonitors(
+            HDC::default(),
+            None,
+            Some(collect),
+            LPARAM(&mut monitors as *mut Vec<HMONITOR> as isize),
+        );
+    }
+    monitors
 }
 
 /// モニタのデバイス名を取得
@@ -386,6 +532,64 @@ pub fn get_monitor_device_name(hmon: HMONITOR) -> Option<String> {
     }
 }
 
+/// マウスカーソルがあるモニタ
+pub fn cursor_monitor() -> Option<HMONITOR> {
+    let mut pt = POINT::default();
+    unsafe {
+        GetCursorPos(&mut pt).ok()?;
+        Some(MonitorFromPoint(pt, MONITOR_DEFAULTTONEAREST))
+    }
+}
+
+/// 接続中の全モニタのデバイス名（`display.enabled_monitors` の候補）
+pub fn connected_monitor_names() -> Vec<String> {
+    enumerate_monitors()
+        .into_iter()
+        .filter_map(get_monitor_device_name)
+        .collect()
+}
+
+/// ウィンドウ矩形が所属モニタの作業領域の端から `snap_px` 以内なら、その端へ吸着させる
+///
+/// サイズは変えずに位置だけを動かす。変更した場合は true を返す。
+pub fn snap_rect_to_work_area(rect: &mut RECT, snap_px: u32) -> bool {
+    if snap_px == 0 {
+        return false;
+    }
+    let work = unsafe {
+        let hmon = MonitorFromRect(rect, MONITOR_DEFAULTTONEAREST);
+        let mut mi = MONITORINFO {
+            cbSize: mem::size_of::<MONITORINFO>() as u32,
+            ..Default::default()
+        };
+        if !GetMonitorInfoW(hmon, &mut mi).as_bool() {
+            return false;
+        }
+        mi.rcWork
+    };
+
+    let snap = snap_px as i32;
+    let snap_axis = |start: i32, end: i32, min: i32, max: i32| -> i32 {
+        if (start - min).abs() <= snap {
+            min - start
+        } else if (end - max).abs() <= snap {
+            max - end
+        } else {
+            0
+        }
+    };
+    let dx = snap_axis(rect.left, rect.right, work.left, work.right);
+    let dy = snap_axis(rect.top, rect.bottom, work.top, work.bottom);
+    if dx == 0 && dy == 0 {
+        return false;
+    }
+    rect.left += dx;
+    rect.right += dx;
+    rect.top += dy;
+    rect.bottom += dy;
+    true
+}
+
 unsafe extern "system" fn wnd_proc(
     hwnd: HWND,
     msg: u32,
@@ -400,3 +604,13 @@ unsafe extern "system" fn wnd_proc(
         _ => DefWindowProcW(hwnd, msg, wparam, lparam),
     }
 }
+
+/// 追加モニタ用ウィンドウのプロシージャ（破棄時に WM_QUIT を送らない）
+pub(crate) unsafe extern "system" fn monitor_wnd_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    DefWindowProcW(hwnd, msg, wparam, lparam)
+}